@@ -0,0 +1,105 @@
+//! Counts heap allocations made while parsing a large config
+//!
+//! Run with `cargo bench --bench allocations`. The `owned` line parses and
+//! then calls `into_owned()`, which copies every borrowed string.
+//!
+//! To compare with the parser which copied every string, check out the
+//! commit before "Borrow strings from the source text in the AST" and run
+//! this file there with the `owned` measurement removed. On a config of
+//! 500 servers (330 KB) this gave, per parse:
+//!
+//! | parser              | allocations |     bytes | time  |
+//! |---------------------|------------:|----------:|------:|
+//! | before (all copied) |      29 683 | 5 705 612 | 85 ms |
+//! | borrowed            |      12 187 | 5 690 376 | 79 ms |
+//! | borrowed + owned    |      23 187 | 5 821 216 | 79 ms |
+//!
+//! So borrowing cuts the number of allocations by 59%, but not the memory:
+//! the strings are small, most of the bytes are vectors of directives,
+//! whose size is dominated by the largest `Item` variant. Time is within
+//! the noise of the measurement.
+extern crate clia_nginx_config;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use clia_nginx_config::parse_directives;
+
+
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn config(servers: usize) -> String {
+    let mut text = String::from("http {\n");
+    for i in 0..servers {
+        text.push_str(&format!("    server {{
+        listen 80;
+        server_name site{0}.example.com www.site{0}.example.com;
+        root /srv/www/site{0};
+        access_log /var/log/nginx/site{0}.log combined;
+        add_header X-Frame-Options SAMEORIGIN always;
+        location / {{
+            try_files $uri $uri/ /index.html;
+        }}
+        location /api/ {{
+            proxy_set_header Host $host;
+            proxy_set_header X-Real-IP $remote_addr;
+            proxy_pass http://backend{0}/v1/;
+        }}
+        location ~ \\.php$ {{
+            fastcgi_param SCRIPT_FILENAME $document_root$fastcgi_script_name;
+            fastcgi_pass unix:/run/php/site{0}.sock;
+        }}
+    }}
+", i));
+    }
+    text.push_str("}\n");
+    text
+}
+
+fn measure<F: FnMut()>(name: &str, mut f: F) {
+    const ROUNDS: usize = 10;
+    ALLOCATIONS.store(0, Ordering::Relaxed);
+    BYTES.store(0, Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        f();
+    }
+    let elapsed = start.elapsed() / ROUNDS as u32;
+    println!("{:<10} {:>9} allocations {:>11} bytes {:>10.2?}", name,
+        ALLOCATIONS.load(Ordering::Relaxed) / ROUNDS,
+        BYTES.load(Ordering::Relaxed) / ROUNDS,
+        elapsed);
+}
+
+fn main() {
+    let text = config(500);
+    println!("config of {} bytes, per parse:", text.len());
+    measure("borrowed", || {
+        let dirs = parse_directives(&text).unwrap();
+        drop(dirs);
+    });
+    measure("owned", || {
+        let dirs = parse_directives(&text).unwrap().into_iter()
+            .map(|d| d.into_owned())
+            .collect::<Vec<_>>();
+        drop(dirs);
+    });
+}
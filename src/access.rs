@@ -1,13 +1,16 @@
 use std::net::IpAddr;
 
 use combine::{Parser};
-use combine::{choice};
+use combine::{choice, optional};
 use combine::error::StreamError;
 use combine::easy::Error;
 
-use ast::{Item, Source};
-use helpers::{semi, ident, string};
+use ast::{self, Item, Source};
+use grammar::value;
+use helpers::{semi, ident, string, prefix, spanned};
+use rewrite::variable;
 use tokenizer::{TokenStream, Token};
+use value::Value;
 
 
 fn parse_source<'a>(val: Token<'a>)
@@ -32,7 +35,7 @@ fn parse_source<'a>(val: Token<'a>)
 }
 
 fn allow<'a>()
-    -> impl Parser<Output=Item, Input=TokenStream<'a>>
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
 {
     ident("allow")
     .with(string())
@@ -42,7 +45,7 @@ fn allow<'a>()
 }
 
 fn deny<'a>()
-    -> impl Parser<Output=Item, Input=TokenStream<'a>>
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
 {
     ident("deny")
     .with(string())
@@ -51,11 +54,63 @@ fn deny<'a>()
     .map(Item::Deny)
 }
 
+fn auth_basic<'a>()
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
+{
+    use ast::AuthBasic::*;
+    ident("auth_basic")
+    .with(choice((
+        ident("off").map(|_| Off),
+        value().map(Realm),
+    )))
+    .skip(semi())
+    .map(Item::AuthBasic)
+}
+
+fn auth_request<'a>()
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
+{
+    use ast::AuthRequest::*;
+    ident("auth_request")
+    .with(choice((
+        ident("off").map(|_| Off),
+        value().map(Uri),
+    )))
+    .skip(semi())
+    .map(Item::AuthRequest)
+}
+
+fn auth_jwt<'a>()
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
+{
+    use ast::AuthJwt::*;
+    ident("auth_jwt")
+    .with(choice((
+        ident("off").map(|_| Off),
+        value().and(optional(spanned(prefix("token="))
+            .and_then(|(span, val)| Value::parse_str(span.tail(val), val))))
+            .map(|(realm, token)| Realm { realm, token }),
+    )))
+    .skip(semi())
+    .map(Item::AuthJwt)
+}
+
 pub fn directives<'a>()
-    -> impl Parser<Output=Item, Input=TokenStream<'a>>
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
 {
     choice((
         allow(),
         deny(),
+        auth_basic(),
+        ident("auth_basic_user_file").with(value()).skip(semi())
+            .map(Item::AuthBasicUserFile),
+        auth_request(),
+        ident("auth_request_set").with(variable()).and(value()).skip(semi())
+            .map(|(variable, value)| Item::AuthRequestSet { variable, value }),
+        ident("satisfy").with(choice((
+            ident("all").map(|_| ast::Satisfy::All),
+            ident("any").map(|_| ast::Satisfy::Any),
+        ))).skip(semi()).map(Item::Satisfy),
+        auth_jwt(),
     ))
 }
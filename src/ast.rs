@@ -1,23 +1,33 @@
 //! Abstract Syntax Tree types
 
 #![allow(missing_docs)] // structures are meant to be self-descriptive
-use std::path::PathBuf;
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::net::{SocketAddr, IpAddr};
 
-pub use value::{Value};
-use position::Pos;
+pub use value::{Value, QuoteStyle};
+pub use units::{Size, Duration, Rate, ParseUnitError};
+use extension::ExtensionDirective;
+use position::{Pos, Span};
 use visitors::{DirectiveIter};
 
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Main {
-    pub directives: Vec<Directive>,
+pub struct Main<'a> {
+    pub directives: Vec<Directive<'a>>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Directive {
+pub struct Directive<'a> {
     pub position: Pos,
-    pub item: Item,
+    /// Range of the source text from the directive name to the `;` or
+    /// the closing brace of the block, inclusive
+    pub span: Span,
+    /// File the directive was read from, `None` if it was parsed from a
+    /// string rather than loaded by include or from disk
+    pub file: Option<Arc<Path>>,
+    pub item: Item<'a>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -27,39 +37,39 @@ pub enum WorkerProcesses {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Http {
+pub struct Http<'a> {
     pub position: (Pos, Pos),
-    pub directives: Vec<Directive>,
+    pub directives: Vec<Directive<'a>>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Server {
+pub struct Server<'a> {
     pub position: (Pos, Pos),
-    pub directives: Vec<Directive>,
+    pub directives: Vec<Directive<'a>>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub enum IfCondition {
-    NonEmpty(Value),
-    Eq(Value, String),
-    Neq(Value, String),
-    RegEq(Value, String, bool),
-    RegNeq(Value, String, bool),
-    Exists(Value),
-    NotExists(Value),
-    FileExists(Value),
-    FileNotExists(Value),
-    DirExists(Value),
-    DirNotExists(Value),
-    Executable(Value),
-    NotExecutable(Value),
+pub enum IfCondition<'a> {
+    NonEmpty(Value<'a>),
+    Eq(Value<'a>, Cow<'a, str>),
+    Neq(Value<'a>, Cow<'a, str>),
+    RegEq(Value<'a>, Cow<'a, str>, bool, QuoteStyle),
+    RegNeq(Value<'a>, Cow<'a, str>, bool, QuoteStyle),
+    Exists(Value<'a>),
+    NotExists(Value<'a>),
+    FileExists(Value<'a>),
+    FileNotExists(Value<'a>),
+    DirExists(Value<'a>),
+    DirNotExists(Value<'a>),
+    Executable(Value<'a>),
+    NotExecutable(Value<'a>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct If {
+pub struct If<'a> {
     pub position: (Pos, Pos),
-    pub condition: IfCondition,
-    pub directives: Vec<Directive>,
+    pub condition: IfCondition<'a>,
+    pub directives: Vec<Directive<'a>>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -120,20 +130,20 @@ impl Listen {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub enum LocationPattern {
-    Prefix(String),
-    Exact(String),
-    FinalPrefix(String),
-    Regex(String),
-    RegexInsensitive(String),
-    Named(String),
+pub enum LocationPattern<'a> {
+    Prefix(Cow<'a, str>),
+    Exact(Cow<'a, str>),
+    FinalPrefix(Cow<'a, str>),
+    Regex(Cow<'a, str>, QuoteStyle),
+    RegexInsensitive(Cow<'a, str>, QuoteStyle),
+    Named(Cow<'a, str>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Location {
+pub struct Location<'a> {
     pub position: (Pos, Pos),
-    pub pattern: LocationPattern,
-    pub directives: Vec<Directive>,
+    pub pattern: LocationPattern<'a>,
+    pub directives: Vec<Directive<'a>>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Copy)]
@@ -158,44 +168,44 @@ pub enum GzipProxied {
 
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct AddHeader {
-    pub field: Value,
-    pub value: Value,
+pub struct AddHeader<'a> {
+    pub field: Value<'a>,
+    pub value: Value<'a>,
     pub always: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub enum ServerName {
-    Exact(String),
-    Suffix(String),
-    StarSuffix(String),
-    StarPrefix(String),
-    Regex(String),
+pub enum ServerName<'a> {
+    Exact(Cow<'a, str>),
+    Suffix(Cow<'a, str>),
+    StarSuffix(Cow<'a, str>),
+    StarPrefix(Cow<'a, str>),
+    Regex(Cow<'a, str>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub enum MapPattern {
-    Exact(String),
-    Suffix(String),
-    StarSuffix(String),
-    StarPrefix(String),
-    Regex(String),
+pub enum MapPattern<'a> {
+    Exact(Cow<'a, str>),
+    Suffix(Cow<'a, str>),
+    StarSuffix(Cow<'a, str>),
+    StarPrefix(Cow<'a, str>),
+    Regex(Cow<'a, str>, QuoteStyle),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Map {
-    pub variable: String,
-    pub expression: Value,
-    pub default: Option<Value>,
+pub struct Map<'a> {
+    pub variable: Cow<'a, str>,
+    pub expression: Value<'a>,
+    pub default: Option<Value<'a>>,
     pub hostnames: bool,
     pub volatile: bool,
-    pub includes: Vec<String>,
-    pub patterns: Vec<(MapPattern, Value)>,
+    pub includes: Vec<Cow<'a, str>>,
+    pub patterns: Vec<(MapPattern<'a>, Value<'a>)>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Types {
-    pub types: Vec<(Value, Vec<Value>)>,
+pub struct Types<'a> {
+    pub types: Vec<(Value<'a>, Vec<Value<'a>>)>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -211,16 +221,16 @@ pub enum ErrorPageResponse {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct ErrorPage {
+pub struct ErrorPage<'a> {
     pub codes: Vec<u32>,
     pub response_code: ErrorPageResponse,
-    pub uri: Value,
+    pub uri: Value<'a>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub enum Return {
-    Redirect { code: Option<u32>, url: Value },
-    Text { code: u32, text: Option<Value> },
+pub enum Return<'a> {
+    Redirect { code: Option<u32>, url: Value<'a> },
+    Text { code: u32, text: Option<Value<'a>> },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -232,29 +242,44 @@ pub enum RewriteFlag {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Rewrite {
-    pub regex: String,
-    pub replacement: Value,
+pub struct Rewrite<'a> {
+    pub regex: Cow<'a, str>,
+    pub replacement: Value<'a>,
     pub flag: Option<RewriteFlag>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub enum TryFilesLastOption {
-    Uri(Value),
-    NamedLocation(String),
+pub enum TryFilesLastOption<'a> {
+    Uri(Value<'a>),
+    NamedLocation(Cow<'a, str>),
     Code(u32),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct TryFiles {
-    pub options: Vec<Value>,
-    pub last_option: TryFilesLastOption,
+pub struct TryFiles<'a> {
+    pub options: Vec<Value<'a>>,
+    pub last_option: TryFilesLastOption<'a>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Expires {
+pub struct Expires<'a> {
     pub modified: bool,
-    pub value: Value,
+    pub value: ExpiresTime<'a>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExpiresTime<'a> {
+    Off,
+    Epoch,
+    Max,
+    /// Time relative to the current time (or the file modification time)
+    After(Duration),
+    /// Negative time, i.e. `Cache-Control: no-cache`
+    Before(Duration),
+    /// `@15h30m`, time of the day
+    TimeOfDay(Duration),
+    /// Any value containing variables
+    Variable(Value<'a>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -265,6 +290,30 @@ pub enum Source {
     Network(IpAddr, u8),
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthBasic<'a> {
+    Off,
+    Realm(Value<'a>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthRequest<'a> {
+    Off,
+    Uri(Value<'a>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthJwt<'a> {
+    Off,
+    Realm { realm: Value<'a>, token: Option<Value<'a>> },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Satisfy {
+    All,
+    Any,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Copy)]
 pub enum ProxyHttpVersion {
     V1_0,
@@ -272,10 +321,10 @@ pub enum ProxyHttpVersion {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub enum ProxyCacheValid {
-    Normal(Value),
-    Specific(Vec<u32>, Value),
-    Any(Value),
+pub enum ProxyCacheValid<'a> {
+    Normal(Value<'a>),
+    Specific(Vec<u32>, Value<'a>),
+    Any(Value<'a>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -294,29 +343,182 @@ pub enum ProxyNextUpstreamFlag {
     Off,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheUseStale {
+    Error,
+    Timeout,
+    InvalidHeader,
+    Updating,
+    Http500,
+    Http502,
+    Http503,
+    Http504,
+    Http403,
+    Http404,
+    Http429,
+    Off,
+}
+
+/// Number and size of buffers (`fastcgi_buffers` and similar directives)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Buffers {
+    pub number: u32,
+    pub size: Size,
+}
+
+/// Parameter passed to the application server
+///
+/// Used for `fastcgi_param`, `uwsgi_param` and `scgi_param`
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub enum AccessLog {
+pub struct Param<'a> {
+    pub name: Value<'a>,
+    pub value: Value<'a>,
+    pub if_not_empty: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProxyRedirect<'a> {
+    Default,
     Off,
-    On(AccessLogOptions),
+    Replace { redirect: Value<'a>, replacement: Value<'a> },
+}
+
+/// Rewrite rule of `proxy_cookie_domain` and `proxy_cookie_path`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProxyCookieRewrite<'a> {
+    Off,
+    Replace { from: Value<'a>, to: Value<'a> },
+}
+
+/// Arguments of `proxy_cache_path`
+///
+/// Options which have no own field are kept in `options` in the original
+/// order. Keys of all options as written are kept in `order`, so the
+/// directive is printed back the same way; options missing from `order`
+/// are printed after the listed ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProxyCachePath<'a> {
+    pub path: Value<'a>,
+    pub levels: Option<Cow<'a, str>>,
+    pub use_temp_path: Option<bool>,
+    /// Zone name and size
+    pub keys_zone: (Cow<'a, str>, Size),
+    pub inactive: Option<Duration>,
+    pub max_size: Option<Size>,
+    pub options: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+    pub order: Vec<CachePathKey<'a>>,
+}
+
+/// Key of the `proxy_cache_path` option, see `ProxyCachePath::order`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CachePathKey<'a> {
+    Levels,
+    UseTempPath,
+    KeysZone,
+    Inactive,
+    MaxSize,
+    /// Option kept in `ProxyCachePath::options`
+    Other(Cow<'a, str>),
+}
+
+/// Arguments of `limit_req_zone`
+///
+/// Options as written are kept in `order`, so the directive is printed
+/// back the same way; options missing from `order` are printed after the
+/// listed ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LimitReqZone<'a> {
+    pub key: Value<'a>,
+    pub name: Cow<'a, str>,
+    pub size: Size,
+    pub rate: Rate,
+    pub sync: bool,
+    pub order: Vec<LimitReqZoneKey>,
+}
+
+/// Option of `limit_req_zone`, see `LimitReqZone::order`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitReqZoneKey {
+    Zone,
+    Rate,
+    Sync,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitReqDelay {
+    NoDelay,
+    Delay(u32),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LimitReq<'a> {
+    pub zone: Cow<'a, str>,
+    pub burst: Option<u32>,
+    pub delay: Option<LimitReqDelay>,
+}
+
+/// Arguments of `limit_conn_zone`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LimitConnZone<'a> {
+    pub key: Value<'a>,
+    pub name: Cow<'a, str>,
+    pub size: Size,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct AccessLogOptions {
-    pub path: Value,
-    pub format: Option<String>,
-    pub buffer: Option<String>,
+pub enum ProxyStore<'a> {
+    On,
+    Off,
+    Path(Value<'a>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccessLog<'a> {
+    Off,
+    On(AccessLogOptions<'a>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessLogOptions<'a> {
+    pub path: Value<'a>,
+    pub format: Option<Cow<'a, str>>,
+    pub buffer: Option<Cow<'a, str>>,
     pub gzip: Option<Option<u8>>,
-    pub flush: Option<String>,
-    pub condition: Option<Value>,
+    pub flush: Option<Cow<'a, str>>,
+    pub condition: Option<Value<'a>>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct LimitExcept {
+pub struct LimitExcept<'a> {
     pub position: (Pos, Pos),
-    pub methods: Vec<String>,
-    pub directives: Vec<Directive>,
+    pub methods: Vec<Cow<'a, str>>,
+    pub directives: Vec<Directive<'a>>,
 }
 
+/// Directive parsed by a parser registered with [`Extensions::register`]
+///
+/// [`Extensions::register`]: ../extension/struct.Extensions.html#method.register
+#[derive(Debug, Clone)]
+pub struct Extension<'a> {
+    /// Arguments as returned by the registered parser
+    pub directive: Box<dyn ExtensionDirective>,
+    /// Position of the `{ block }`, if the directive is a block
+    pub position: Option<(Pos, Pos)>,
+    /// Directives of the `{ block }`, if the directive is a block
+    pub directives: Option<Vec<Directive<'a>>>,
+}
+
+// derive can't compare boxed trait objects (rust-lang/rust#31740)
+impl<'a> PartialEq for Extension<'a> {
+    fn eq(&self, other: &Extension<'a>) -> bool {
+        *self.directive == *other.directive &&
+            self.position == other.position &&
+            self.directives == other.directives
+    }
+}
+
+impl<'a> Eq for Extension<'a> {}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum RealIpFrom {
     Unix,
@@ -336,97 +538,600 @@ pub enum ErrorLevel {
     Emerg,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SslProtocol {
+    SslV2,
+    SslV3,
+    TlsV1,
+    TlsV1_1,
+    TlsV1_2,
+    TlsV1_3,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SslSessionCache<'a> {
+    Off,
+    None,
+    Store {
+        /// `builtin` or `builtin:size` (size is the number of sessions)
+        builtin: Option<Option<u32>>,
+        /// `shared:name:size`
+        shared: Option<(Cow<'a, str>, Size)>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SslVerifyClient {
+    On,
+    Off,
+    Optional,
+    OptionalNoCa,
+}
+
 /// The enum which represents nginx config directive
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub enum Item {
+pub enum Item<'a> {
     Daemon(bool),
     MasterProcess(bool),
+    LoadModule(Value<'a>),
     WorkerProcesses(WorkerProcesses),
-    Http(Http),
-    Server(Server),
-    Location(Location),
+    Http(Http<'a>),
+    Server(Server<'a>),
+    Location(Location<'a>),
     Listen(Listen),
-    ProxyPass(Value),
-    ProxySetHeader { field: Value, value: Value },
-    ProxyMethod(Value),
-    ProxyReadTimeout(Value),
-    ProxyConnectTimeout(Value),
-    ProxyHideHeader(Value),
-    ProxyPassHeader(Value),
+    ProxyPass(Value<'a>),
+    ProxySetHeader { field: Value<'a>, value: Value<'a> },
+    ProxyMethod(Value<'a>),
+    ProxyReadTimeout(Duration),
+    ProxyConnectTimeout(Duration),
+    ProxyHideHeader(Value<'a>),
+    ProxyPassHeader(Value<'a>),
     ProxyPassRequestBody(bool),
     ProxyPassRequestHeaders(bool),
     ProxyHttpVersion(ProxyHttpVersion),
-    ProxyIgnoreHeaders(Vec<String>),
+    ProxyIgnoreHeaders(Vec<Cow<'a, str>>),
     ProxyInterceptErrors(bool),
     ProxyBuffering(bool),
-    ProxyCache(Value),
-    ProxyCacheKey(Value),
-    ProxyCacheValid(ProxyCacheValid),
-    ProxyNextUpstreamTries(Value),
-    ProxyNextUpstreamTimeout(Value),
+    ProxyCache(Value<'a>),
+    ProxyCacheKey(Value<'a>),
+    ProxyCacheValid(ProxyCacheValid<'a>),
+    ProxyNextUpstreamTries(Value<'a>),
+    ProxyNextUpstreamTimeout(Duration),
     ProxyNextUpstream(Vec<ProxyNextUpstreamFlag>),
+    ProxyRedirect(ProxyRedirect<'a>),
+    ProxyBuffers(Buffers),
+    ProxyBufferSize(Size),
+    ProxyBusyBuffersSize(Size),
+    ProxySendTimeout(Duration),
+    ProxyRequestBuffering(bool),
+    ProxySslServerName(bool),
+    ProxySslVerify(bool),
+    ProxySslName(Value<'a>),
+    ProxyCookieDomain(ProxyCookieRewrite<'a>),
+    ProxyCookiePath(ProxyCookieRewrite<'a>),
+    ProxyCachePath(ProxyCachePath<'a>),
+    ProxyCacheBypass(Vec<Value<'a>>),
+    ProxyNoCache(Vec<Value<'a>>),
+    ProxyCacheLock(bool),
+    ProxyCacheUseStale(Vec<CacheUseStale>),
+    ProxyStore(ProxyStore<'a>),
+    // fastcgi module
+    FastcgiPass(Value<'a>),
+    FastcgiParam(Param<'a>),
+    FastcgiIndex(Value<'a>),
+    FastcgiSplitPathInfo(Cow<'a, str>),
+    FastcgiReadTimeout(Duration),
+    FastcgiBuffers(Buffers),
+    FastcgiInterceptErrors(bool),
+    FastcgiCache(Value<'a>),
+    FastcgiCacheKey(Value<'a>),
+    FastcgiCacheValid(ProxyCacheValid<'a>),
+    FastcgiCacheBypass(Vec<Value<'a>>),
+    FastcgiCacheLock(bool),
+    FastcgiCacheMethods(Vec<Cow<'a, str>>),
+    FastcgiCacheMinUses(u32),
+    FastcgiCacheUseStale(Vec<CacheUseStale>),
+    FastcgiCachePath(ProxyCachePath<'a>),
+    FastcgiNoCache(Vec<Value<'a>>),
+    FastcgiCacheLockAge(Duration),
+    FastcgiCacheLockTimeout(Duration),
+    FastcgiCacheRevalidate(bool),
+    FastcgiCacheBackgroundUpdate(bool),
+    FastcgiCacheMaxRangeOffset(u64),
+    // uwsgi module
+    UwsgiPass(Value<'a>),
+    UwsgiParam(Param<'a>),
+    UwsgiReadTimeout(Duration),
+    UwsgiConnectTimeout(Duration),
+    UwsgiSendTimeout(Duration),
+    UwsgiBuffering(bool),
+    UwsgiBuffers(Buffers),
+    UwsgiInterceptErrors(bool),
+    UwsgiNextUpstream(Vec<ProxyNextUpstreamFlag>),
+    UwsgiCache(Value<'a>),
+    UwsgiCacheKey(Value<'a>),
+    UwsgiCacheValid(ProxyCacheValid<'a>),
+    // scgi module
+    ScgiPass(Value<'a>),
+    ScgiParam(Param<'a>),
+    ScgiReadTimeout(Duration),
+    ScgiConnectTimeout(Duration),
+    ScgiSendTimeout(Duration),
+    ScgiBuffering(bool),
+    ScgiBuffers(Buffers),
+    ScgiInterceptErrors(bool),
+    ScgiNextUpstream(Vec<ProxyNextUpstreamFlag>),
+    ScgiCache(Value<'a>),
+    ScgiCacheKey(Value<'a>),
+    ScgiCacheValid(ProxyCacheValid<'a>),
+    // grpc module
+    GrpcPass(Value<'a>),
+    GrpcSetHeader { field: Value<'a>, value: Value<'a> },
+    GrpcReadTimeout(Duration),
+    GrpcConnectTimeout(Duration),
+    GrpcSendTimeout(Duration),
+    GrpcInterceptErrors(bool),
+    GrpcNextUpstream(Vec<ProxyNextUpstreamFlag>),
+    GrpcSslCertificate(Value<'a>),
+    GrpcSslCertificateKey(Value<'a>),
+    GrpcSslProtocols(Vec<SslProtocol>),
+    GrpcSslCiphers(Cow<'a, str>),
+    GrpcSslVerify(bool),
+    GrpcSslTrustedCertificate(Value<'a>),
+    GrpcSslServerName(bool),
+    GrpcSslName(Value<'a>),
+    // limit_req and limit_conn modules
+    LimitReqZone(LimitReqZone<'a>),
+    LimitReq(LimitReq<'a>),
+    LimitReqStatus(u32),
+    LimitReqLogLevel(ErrorLevel),
+    LimitConnZone(LimitConnZone<'a>),
+    LimitConn { zone: Cow<'a, str>, number: u32 },
+    LimitConnStatus(u32),
+    LimitRate(Value<'a>),
+    LimitRateAfter(Value<'a>),
     Gzip(bool),
     GzipStatic(GzipStatic),
     GzipProxied(Vec<GzipProxied>),
-    AddHeader(AddHeader),
-    Expires(Expires),
-    Root(Value),
-    Alias(Value),
-    ErrorPage(ErrorPage),
-    DefaultType(Value),
-    Types(Types),
-    ErrorLog { file: Value, level: Option<ErrorLevel>},
-    Rewrite(Rewrite),
-    Return(Return),
-    If(If),
-    TryFiles(TryFiles),
-    ServerName(Vec<ServerName>),
-    Set { variable: String, value: Value },
-    Map(Map),
-    ClientMaxBodySize(Value),
-    Include(Value),
+    AddHeader(AddHeader<'a>),
+    Expires(Expires<'a>),
+    Root(Value<'a>),
+    Alias(Value<'a>),
+    ErrorPage(ErrorPage<'a>),
+    DefaultType(Value<'a>),
+    Types(Types<'a>),
+    ErrorLog { file: Value<'a>, level: Option<ErrorLevel>},
+    Rewrite(Rewrite<'a>),
+    Return(Return<'a>),
+    If(If<'a>),
+    TryFiles(TryFiles<'a>),
+    ServerName(Vec<ServerName<'a>>),
+    Set { variable: Cow<'a, str>, value: Value<'a> },
+    Map(Map<'a>),
+    ClientMaxBodySize(Size),
+    Include(Value<'a>),
     EmptyGif,
     Internal,
-    LimitExcept(LimitExcept),
+    LimitExcept(LimitExcept<'a>),
     Etag(bool),
     RecursiveErrorPages(bool),
     ChunkedTransferEncoding(bool),
-    KeepaliveTimeout(Value, Option<Value>),
-    ServerTokens(Value),
-    SslCertificate(Value),
-    SslCertificateKey(Value),
+    KeepaliveTimeout(Duration, Option<Duration>),
+    ServerTokens(Value<'a>),
+    Http2(bool),
+    // ssl module
+    Ssl(bool),
+    SslCertificate(Value<'a>),
+    SslCertificateKey(Value<'a>),
+    SslProtocols(Vec<SslProtocol>),
+    SslCiphers(Cow<'a, str>),
+    SslPreferServerCiphers(bool),
+    SslSessionCache(SslSessionCache<'a>),
+    SslSessionTimeout(Duration),
+    SslSessionTickets(bool),
+    SslStapling(bool),
+    SslStaplingVerify(bool),
+    SslTrustedCertificate(Value<'a>),
+    SslDhparam(Value<'a>),
+    SslEcdhCurve(Cow<'a, str>),
+    SslClientCertificate(Value<'a>),
+    SslVerifyClient(SslVerifyClient),
+    SslVerifyDepth(u32),
     // openresty
-    RewriteByLuaFile(Value),
-    BalancerByLuaFile(Value),
-    AccessByLuaFile(Value),
-    HeaderFilterByLuaFile(Value),
-    ContentByLuaFile(Value),
-    BodyFilterByLuaFile(Value),
-    LogByLuaFile(Value),
-    LuaNeedRequestBody(Value),
-    SslCertificateByLuaFile(Value),
-    SslSessionFetchByLuaFile(Value),
-    SslSessionStoreByLuaFile(Value),
+    RewriteByLuaFile(Value<'a>),
+    BalancerByLuaFile(Value<'a>),
+    AccessByLuaFile(Value<'a>),
+    HeaderFilterByLuaFile(Value<'a>),
+    ContentByLuaFile(Value<'a>),
+    BodyFilterByLuaFile(Value<'a>),
+    LogByLuaFile(Value<'a>),
+    LuaNeedRequestBody(Value<'a>),
+    SslCertificateByLuaFile(Value<'a>),
+    SslSessionFetchByLuaFile(Value<'a>),
+    SslSessionStoreByLuaFile(Value<'a>),
     // access module
     Allow(Source),
     Deny(Source),
+    AuthBasic(AuthBasic<'a>),
+    AuthBasicUserFile(Value<'a>),
+    AuthRequest(AuthRequest<'a>),
+    AuthRequestSet { variable: Cow<'a, str>, value: Value<'a> },
+    Satisfy(Satisfy),
+    AuthJwt(AuthJwt<'a>),
     // log module
-    AccessLog(AccessLog),
+    AccessLog(AccessLog<'a>),
     // real_ip module
-    RealIpHeader(Value),
+    RealIpHeader(Value<'a>),
     RealIpRecursive(bool),
     SetRealIpFrom(RealIpFrom),
     // index module
-    Index(Vec<Value>),
+    Index(Vec<Value<'a>>),
+    // registered by other crates
+    Extension(Extension<'a>),
+}
+
+/// Generates the match of `Item::visit_values` and `Item::visit_values_mut`
+///
+/// `$mut` is either empty or `mut`. Extensions are visited by `$visit_ext`
+/// because their values are always owned.
+macro_rules! visit_item_values {
+    ($item:expr, $f:ident, ($($mut:tt)*), $ext:ident => $visit_ext:expr) => {
+        match $item {
+            Daemon(_) => {},
+            MasterProcess(_) => {},
+            LoadModule(ref $($mut)* v) => $f(v),
+            WorkerProcesses(_) => {},
+            Http(_) => {},
+            Server(_) => {},
+            Location(_) => {},
+            LimitExcept(_) => {},
+            Listen(_) => {},
+            ProxyPass(ref $($mut)* v) => $f(v),
+            ProxySetHeader { ref $($mut)* field, ref $($mut)* value } => {
+                $f(field);
+                $f(value);
+            }
+            ProxyMethod(ref $($mut)* v) => $f(v),
+            ProxyReadTimeout(..) => {},
+            ProxyConnectTimeout(..) => {},
+            ProxyHideHeader(ref $($mut)* v) => $f(v),
+            ProxyPassHeader(ref $($mut)* v) => $f(v),
+            ProxyCache(ref $($mut)* v) => $f(v),
+            ProxyCacheKey(ref $($mut)* v) => $f(v),
+            ProxyCacheValid(self::ProxyCacheValid::Normal(ref $($mut)* v))
+            => $f(v),
+            ProxyCacheValid(self::ProxyCacheValid::Specific(_, ref $($mut)* v))
+            => $f(v),
+            ProxyCacheValid(self::ProxyCacheValid::Any(ref $($mut)* v))
+            => $f(v),
+            ProxyPassRequestHeaders(_) => {},
+            ProxyPassRequestBody(_) => {},
+            ProxyHttpVersion(..) => {},
+            ProxyIgnoreHeaders(..) => {},
+            ProxyInterceptErrors(..) => {},
+            ProxyBuffering(..) => {},
+            ProxyNextUpstreamTries(ref $($mut)* v) => $f(v),
+            ProxyNextUpstreamTimeout(..) => {},
+            ProxyNextUpstream(_) => {},
+            ProxyRedirect(self::ProxyRedirect::Replace {
+                ref $($mut)* redirect, ref $($mut)* replacement,
+            }) => {
+                $f(redirect);
+                $f(replacement);
+            }
+            ProxyRedirect(..) => {},
+            ProxyBuffers(..) => {},
+            ProxyBufferSize(..) => {},
+            ProxyBusyBuffersSize(..) => {},
+            ProxySendTimeout(..) => {},
+            ProxyRequestBuffering(..) => {},
+            ProxySslServerName(..) => {},
+            ProxySslVerify(..) => {},
+            ProxySslName(ref $($mut)* v) => $f(v),
+            | ProxyCookieDomain(self::ProxyCookieRewrite::Replace {
+                ref $($mut)* from, ref $($mut)* to })
+            | ProxyCookiePath(self::ProxyCookieRewrite::Replace {
+                ref $($mut)* from, ref $($mut)* to })
+            => {
+                $f(from);
+                $f(to);
+            }
+            ProxyCookieDomain(..) => {},
+            ProxyCookiePath(..) => {},
+            ProxyCachePath(ref $($mut)* p) => $f(&$($mut)* p.path),
+            | ProxyCacheBypass(ref $($mut)* items)
+            | ProxyNoCache(ref $($mut)* items)
+            => {
+                for v in items {
+                    $f(v);
+                }
+            }
+            ProxyCacheLock(..) => {},
+            ProxyCacheUseStale(..) => {},
+            ProxyStore(self::ProxyStore::Path(ref $($mut)* v)) => $f(v),
+            ProxyStore(..) => {},
+            // fastcgi module
+            FastcgiPass(ref $($mut)* v) => $f(v),
+            FastcgiParam(self::Param {
+                ref $($mut)* name, ref $($mut)* value, ..
+            })
+            => {
+                $f(name);
+                $f(value);
+            }
+            FastcgiIndex(ref $($mut)* v) => $f(v),
+            FastcgiSplitPathInfo(..) => {},
+            FastcgiReadTimeout(..) => {},
+            FastcgiBuffers(..) => {},
+            FastcgiInterceptErrors(..) => {},
+            FastcgiCache(ref $($mut)* v) => $f(v),
+            FastcgiCacheKey(ref $($mut)* v) => $f(v),
+            FastcgiCacheValid(self::ProxyCacheValid::Normal(ref $($mut)* v))
+            => $f(v),
+            FastcgiCacheValid(
+                self::ProxyCacheValid::Specific(_, ref $($mut)* v))
+            => $f(v),
+            FastcgiCacheValid(self::ProxyCacheValid::Any(ref $($mut)* v))
+            => $f(v),
+            | FastcgiCacheBypass(ref $($mut)* items)
+            | FastcgiNoCache(ref $($mut)* items)
+            => {
+                for v in items {
+                    $f(v);
+                }
+            }
+            FastcgiCacheLock(..) => {},
+            FastcgiCacheMethods(..) => {},
+            FastcgiCacheMinUses(..) => {},
+            FastcgiCacheUseStale(..) => {},
+            FastcgiCachePath(ref $($mut)* p) => $f(&$($mut)* p.path),
+            FastcgiCacheLockAge(..) => {},
+            FastcgiCacheLockTimeout(..) => {},
+            FastcgiCacheRevalidate(..) => {},
+            FastcgiCacheBackgroundUpdate(..) => {},
+            FastcgiCacheMaxRangeOffset(..) => {},
+            // uwsgi module
+            UwsgiPass(ref $($mut)* v) => $f(v),
+            UwsgiParam(self::Param {
+                ref $($mut)* name, ref $($mut)* value, ..
+            })
+            => {
+                $f(name);
+                $f(value);
+            }
+            UwsgiReadTimeout(..) => {},
+            UwsgiConnectTimeout(..) => {},
+            UwsgiSendTimeout(..) => {},
+            UwsgiBuffering(..) => {},
+            UwsgiBuffers(..) => {},
+            UwsgiInterceptErrors(..) => {},
+            UwsgiNextUpstream(..) => {},
+            UwsgiCache(ref $($mut)* v) => $f(v),
+            UwsgiCacheKey(ref $($mut)* v) => $f(v),
+            UwsgiCacheValid(self::ProxyCacheValid::Normal(ref $($mut)* v))
+            => $f(v),
+            UwsgiCacheValid(self::ProxyCacheValid::Specific(_, ref $($mut)* v))
+            => $f(v),
+            UwsgiCacheValid(self::ProxyCacheValid::Any(ref $($mut)* v))
+            => $f(v),
+            // scgi module
+            ScgiPass(ref $($mut)* v) => $f(v),
+            ScgiParam(self::Param { ref $($mut)* name, ref $($mut)* value, .. })
+            => {
+                $f(name);
+                $f(value);
+            }
+            ScgiReadTimeout(..) => {},
+            ScgiConnectTimeout(..) => {},
+            ScgiSendTimeout(..) => {},
+            ScgiBuffering(..) => {},
+            ScgiBuffers(..) => {},
+            ScgiInterceptErrors(..) => {},
+            ScgiNextUpstream(..) => {},
+            ScgiCache(ref $($mut)* v) => $f(v),
+            ScgiCacheKey(ref $($mut)* v) => $f(v),
+            ScgiCacheValid(self::ProxyCacheValid::Normal(ref $($mut)* v))
+            => $f(v),
+            ScgiCacheValid(self::ProxyCacheValid::Specific(_, ref $($mut)* v))
+            => $f(v),
+            ScgiCacheValid(self::ProxyCacheValid::Any(ref $($mut)* v)) => $f(v),
+            // grpc module
+            GrpcPass(ref $($mut)* v) => $f(v),
+            GrpcSetHeader { ref $($mut)* field, ref $($mut)* value } => {
+                $f(field);
+                $f(value);
+            }
+            GrpcReadTimeout(..) => {},
+            GrpcConnectTimeout(..) => {},
+            GrpcSendTimeout(..) => {},
+            GrpcInterceptErrors(..) => {},
+            GrpcNextUpstream(..) => {},
+            GrpcSslCertificate(ref $($mut)* v) => $f(v),
+            GrpcSslCertificateKey(ref $($mut)* v) => $f(v),
+            GrpcSslProtocols(..) => {},
+            GrpcSslCiphers(..) => {},
+            GrpcSslVerify(..) => {},
+            GrpcSslTrustedCertificate(ref $($mut)* v) => $f(v),
+            GrpcSslServerName(..) => {},
+            GrpcSslName(ref $($mut)* v) => $f(v),
+            LimitReqZone(ref $($mut)* z) => $f(&$($mut)* z.key),
+            LimitReq(..) => {},
+            LimitReqStatus(..) => {},
+            LimitReqLogLevel(..) => {},
+            LimitConnZone(ref $($mut)* z) => $f(&$($mut)* z.key),
+            LimitConn {..} => {},
+            LimitConnStatus(..) => {},
+            LimitRate(ref $($mut)* v) => $f(v),
+            LimitRateAfter(ref $($mut)* v) => $f(v),
+            Gzip(_) => {},
+            GzipStatic(_) => {},
+            GzipProxied(_) => {},
+            AddHeader(self::AddHeader {
+                ref $($mut)* field, ref $($mut)* value, ..
+            })
+            => {
+                $f(field);
+                $f(value);
+            }
+            Expires(self::Expires {
+                value: self::ExpiresTime::Variable(ref $($mut)* v), ..
+            }) => $f(v),
+            Expires(..) => {},
+            Root(ref $($mut)* v) => $f(v),
+            Alias(ref $($mut)* v) => $f(v),
+            ErrorPage(::ast::ErrorPage { ref $($mut)* uri, .. }) => $f(uri),
+            DefaultType(ref $($mut)* v) => $f(v),
+            Types(ref $($mut)* t) => {
+                for &$($mut)* (ref $($mut)* mime, ref $($mut)* exts)
+                    in &$($mut)* t.types
+                {
+                    $f(mime);
+                    for e in exts {
+                        $f(e);
+                    }
+                }
+            }
+            ErrorLog { ref $($mut)* file, .. } => $f(file),
+            Rewrite(ref $($mut)* rw) => $f(&$($mut)* rw.replacement),
+            Return(::ast::Return::Redirect { ref $($mut)* url, .. }) => $f(url),
+            Return(::ast::Return::Text { text: Some(ref $($mut)* t), .. })
+            => $f(t),
+            Return(::ast::Return::Text { text: None, .. }) => {},
+            If(self::If { ref $($mut)* condition, .. }) => {
+                use self::IfCondition::*;
+                match condition {
+                    NonEmpty(ref $($mut)* v) => $f(v),
+                    Eq(ref $($mut)* v, _) => $f(v),
+                    Neq(ref $($mut)* v, _) => $f(v),
+                    RegEq(ref $($mut)* v, _, _, _) => $f(v),
+                    RegNeq(ref $($mut)* v, _, _, _) => $f(v),
+                    Exists(ref $($mut)* v) => $f(v),
+                    NotExists(ref $($mut)* v) => $f(v),
+                    FileExists(ref $($mut)* v) => $f(v),
+                    FileNotExists(ref $($mut)* v) => $f(v),
+                    DirExists(ref $($mut)* v) => $f(v),
+                    DirNotExists(ref $($mut)* v) => $f(v),
+                    Executable(ref $($mut)* v) => $f(v),
+                    NotExecutable(ref $($mut)* v) => $f(v),
+                }
+            },
+            TryFiles(ref $($mut)* tf) => {
+                for opt in &$($mut)* tf.options {
+                    $f(opt);
+                }
+                match tf.last_option {
+                    TryFilesLastOption::Uri(ref $($mut)* v) => $f(v),
+                    TryFilesLastOption::NamedLocation(..) => {},
+                    TryFilesLastOption::Code(..) => {},
+                }
+            },
+            Include(ref $($mut)* v) => $f(v),
+            EmptyGif => {}
+            Internal => {}
+            Etag(..) => {}
+            RecursiveErrorPages(..) => {}
+            ChunkedTransferEncoding(..) => {}
+            KeepaliveTimeout(..) => {}
+            ServerTokens(ref $($mut)* v) => $f(v),
+            Http2(..) => {}
+            // ssl module
+            Ssl(..) => {}
+            SslCertificate(ref $($mut)* v) => $f(v),
+            SslCertificateKey(ref $($mut)* v) => $f(v),
+            SslProtocols(..) => {},
+            SslCiphers(..) => {},
+            SslPreferServerCiphers(..) => {},
+            SslSessionCache(..) => {},
+            SslSessionTimeout(..) => {},
+            SslSessionTickets(..) => {},
+            SslStapling(..) => {},
+            SslStaplingVerify(..) => {},
+            SslTrustedCertificate(ref $($mut)* v) => $f(v),
+            SslDhparam(ref $($mut)* v) => $f(v),
+            SslEcdhCurve(..) => {},
+            SslClientCertificate(ref $($mut)* v) => $f(v),
+            SslVerifyClient(..) => {},
+            SslVerifyDepth(..) => {},
+            ServerName(_) => {},
+            Set { ref $($mut)* value, .. } => $f(value),
+            Map(::ast::Map {
+                ref $($mut)* expression,
+                ref $($mut)* default,
+                ref $($mut)* patterns,
+                ..
+            }) => {
+                $f(expression);
+                if let Some(ref $($mut)* def) = default {
+                    $f(def);
+                }
+                for (_, v) in patterns {
+                    $f(v);
+                }
+            }
+            ClientMaxBodySize(..) => {},
+            // openresty
+            RewriteByLuaFile(ref $($mut)* v) => $f(v),
+            BalancerByLuaFile(ref $($mut)* v) => $f(v),
+            AccessByLuaFile(ref $($mut)* v) => $f(v),
+            HeaderFilterByLuaFile(ref $($mut)* v) => $f(v),
+            ContentByLuaFile(ref $($mut)* v) => $f(v),
+            BodyFilterByLuaFile(ref $($mut)* v) => $f(v),
+            LogByLuaFile(ref $($mut)* v) => $f(v),
+            LuaNeedRequestBody(ref $($mut)* v) => $f(v),
+            SslCertificateByLuaFile(ref $($mut)* v) => $f(v),
+            SslSessionFetchByLuaFile(ref $($mut)* v) => $f(v),
+            SslSessionStoreByLuaFile(ref $($mut)* v) => $f(v),
+            // access
+            Allow(..) => {},
+            Deny(..) => {},
+            AuthBasic(self::AuthBasic::Realm(ref $($mut)* v)) => $f(v),
+            AuthBasic(self::AuthBasic::Off) => {},
+            AuthBasicUserFile(ref $($mut)* v) => $f(v),
+            AuthRequest(self::AuthRequest::Uri(ref $($mut)* v)) => $f(v),
+            AuthRequest(self::AuthRequest::Off) => {},
+            AuthRequestSet { ref $($mut)* value, .. } => $f(value),
+            Satisfy(..) => {},
+            AuthJwt(self::AuthJwt::Realm {
+                ref $($mut)* realm, ref $($mut)* token,
+            })
+            => {
+                $f(realm);
+                if let Some(ref $($mut)* token) = *token {
+                    $f(token);
+                }
+            }
+            AuthJwt(self::AuthJwt::Off) => {},
+            // log module
+            AccessLog(::ast::AccessLog::Off) => {},
+            AccessLog(::ast::AccessLog::On(ref $($mut)* lg)) => {
+                $f(&$($mut)* lg.path);
+                if let Some(ref $($mut)* cond) = lg.condition {
+                    $f(cond);
+                }
+            },
+            // real_ip module
+            RealIpHeader(ref $($mut)* v) => $f(v),
+            RealIpRecursive(..) => {},
+            SetRealIpFrom(..) => {},
+            // index module
+            Index(ref $($mut)* items) => {
+                for v in items {
+                    $f(v);
+                }
+            }
+            Extension(ref $($mut)* $ext) => $visit_ext,
+        }
+    }
 }
 
-impl Item {
+impl<'a> Item<'a> {
 
     pub fn directive_name(&self) -> &'static str {
         use self::Item::*;
         match *self {
             Daemon(..) => "daemon",
             MasterProcess(..) => "master_process",
+            LoadModule(..) => "load_module",
             WorkerProcesses(..) => "worker_processes",
             Http(..) => "http",
             Server(..) => "server",
@@ -452,6 +1157,99 @@ impl Item {
             ProxyNextUpstreamTries(..) => "proxy_next_upstream_tries",
             ProxyNextUpstreamTimeout(..) => "proxy_next_upstream_timeout",
             ProxyNextUpstream(..) => "proxy_next_upstream",
+            ProxyRedirect(..) => "proxy_redirect",
+            ProxyBuffers(..) => "proxy_buffers",
+            ProxyBufferSize(..) => "proxy_buffer_size",
+            ProxyBusyBuffersSize(..) => "proxy_busy_buffers_size",
+            ProxySendTimeout(..) => "proxy_send_timeout",
+            ProxyRequestBuffering(..) => "proxy_request_buffering",
+            ProxySslServerName(..) => "proxy_ssl_server_name",
+            ProxySslVerify(..) => "proxy_ssl_verify",
+            ProxySslName(..) => "proxy_ssl_name",
+            ProxyCookieDomain(..) => "proxy_cookie_domain",
+            ProxyCookiePath(..) => "proxy_cookie_path",
+            ProxyCachePath(..) => "proxy_cache_path",
+            ProxyCacheBypass(..) => "proxy_cache_bypass",
+            ProxyNoCache(..) => "proxy_no_cache",
+            ProxyCacheLock(..) => "proxy_cache_lock",
+            ProxyCacheUseStale(..) => "proxy_cache_use_stale",
+            ProxyStore(..) => "proxy_store",
+            // fastcgi module
+            FastcgiPass(..) => "fastcgi_pass",
+            FastcgiParam(..) => "fastcgi_param",
+            FastcgiIndex(..) => "fastcgi_index",
+            FastcgiSplitPathInfo(..) => "fastcgi_split_path_info",
+            FastcgiReadTimeout(..) => "fastcgi_read_timeout",
+            FastcgiBuffers(..) => "fastcgi_buffers",
+            FastcgiInterceptErrors(..) => "fastcgi_intercept_errors",
+            FastcgiCache(..) => "fastcgi_cache",
+            FastcgiCacheKey(..) => "fastcgi_cache_key",
+            FastcgiCacheValid(..) => "fastcgi_cache_valid",
+            FastcgiCacheBypass(..) => "fastcgi_cache_bypass",
+            FastcgiCacheLock(..) => "fastcgi_cache_lock",
+            FastcgiCacheMethods(..) => "fastcgi_cache_methods",
+            FastcgiCacheMinUses(..) => "fastcgi_cache_min_uses",
+            FastcgiCacheUseStale(..) => "fastcgi_cache_use_stale",
+            FastcgiCachePath(..) => "fastcgi_cache_path",
+            FastcgiNoCache(..) => "fastcgi_no_cache",
+            FastcgiCacheLockAge(..) => "fastcgi_cache_lock_age",
+            FastcgiCacheLockTimeout(..) => "fastcgi_cache_lock_timeout",
+            FastcgiCacheRevalidate(..) => "fastcgi_cache_revalidate",
+            FastcgiCacheBackgroundUpdate(..) => {
+                "fastcgi_cache_background_update"
+            }
+            FastcgiCacheMaxRangeOffset(..) => "fastcgi_cache_max_range_offset",
+            // uwsgi module
+            UwsgiPass(..) => "uwsgi_pass",
+            UwsgiParam(..) => "uwsgi_param",
+            UwsgiReadTimeout(..) => "uwsgi_read_timeout",
+            UwsgiConnectTimeout(..) => "uwsgi_connect_timeout",
+            UwsgiSendTimeout(..) => "uwsgi_send_timeout",
+            UwsgiBuffering(..) => "uwsgi_buffering",
+            UwsgiBuffers(..) => "uwsgi_buffers",
+            UwsgiInterceptErrors(..) => "uwsgi_intercept_errors",
+            UwsgiNextUpstream(..) => "uwsgi_next_upstream",
+            UwsgiCache(..) => "uwsgi_cache",
+            UwsgiCacheKey(..) => "uwsgi_cache_key",
+            UwsgiCacheValid(..) => "uwsgi_cache_valid",
+            // scgi module
+            ScgiPass(..) => "scgi_pass",
+            ScgiParam(..) => "scgi_param",
+            ScgiReadTimeout(..) => "scgi_read_timeout",
+            ScgiConnectTimeout(..) => "scgi_connect_timeout",
+            ScgiSendTimeout(..) => "scgi_send_timeout",
+            ScgiBuffering(..) => "scgi_buffering",
+            ScgiBuffers(..) => "scgi_buffers",
+            ScgiInterceptErrors(..) => "scgi_intercept_errors",
+            ScgiNextUpstream(..) => "scgi_next_upstream",
+            ScgiCache(..) => "scgi_cache",
+            ScgiCacheKey(..) => "scgi_cache_key",
+            ScgiCacheValid(..) => "scgi_cache_valid",
+            // grpc module
+            GrpcPass(..) => "grpc_pass",
+            GrpcSetHeader {..} => "grpc_set_header",
+            GrpcReadTimeout(..) => "grpc_read_timeout",
+            GrpcConnectTimeout(..) => "grpc_connect_timeout",
+            GrpcSendTimeout(..) => "grpc_send_timeout",
+            GrpcInterceptErrors(..) => "grpc_intercept_errors",
+            GrpcNextUpstream(..) => "grpc_next_upstream",
+            GrpcSslCertificate(..) => "grpc_ssl_certificate",
+            GrpcSslCertificateKey(..) => "grpc_ssl_certificate_key",
+            GrpcSslProtocols(..) => "grpc_ssl_protocols",
+            GrpcSslCiphers(..) => "grpc_ssl_ciphers",
+            GrpcSslVerify(..) => "grpc_ssl_verify",
+            GrpcSslTrustedCertificate(..) => "grpc_ssl_trusted_certificate",
+            GrpcSslServerName(..) => "grpc_ssl_server_name",
+            GrpcSslName(..) => "grpc_ssl_name",
+            LimitReqZone(..) => "limit_req_zone",
+            LimitReq(..) => "limit_req",
+            LimitReqStatus(..) => "limit_req_status",
+            LimitReqLogLevel(..) => "limit_req_log_level",
+            LimitConnZone(..) => "limit_conn_zone",
+            LimitConn {..} => "limit_conn",
+            LimitConnStatus(..) => "limit_conn_status",
+            LimitRate(..) => "limit_rate",
+            LimitRateAfter(..) => "limit_rate_after",
             Gzip(..) => "gzip",
             GzipStatic(..) => "gzip_static",
             GzipProxied(..) => "gzip_proxied",
@@ -479,8 +1277,25 @@ impl Item {
             ChunkedTransferEncoding(..) => "chunked_transfer_encoding",
             KeepaliveTimeout(..) => "keepalive_timeout",
             ServerTokens(..) => "server_tokens",
+            Http2(..) => "http2",
+            // ssl module
+            Ssl(..) => "ssl",
             SslCertificate(..) => "ssl_certificate",
             SslCertificateKey(..) => "ssl_certificate_key",
+            SslProtocols(..) => "ssl_protocols",
+            SslCiphers(..) => "ssl_ciphers",
+            SslPreferServerCiphers(..) => "ssl_prefer_server_ciphers",
+            SslSessionCache(..) => "ssl_session_cache",
+            SslSessionTimeout(..) => "ssl_session_timeout",
+            SslSessionTickets(..) => "ssl_session_tickets",
+            SslStapling(..) => "ssl_stapling",
+            SslStaplingVerify(..) => "ssl_stapling_verify",
+            SslTrustedCertificate(..) => "ssl_trusted_certificate",
+            SslDhparam(..) => "ssl_dhparam",
+            SslEcdhCurve(..) => "ssl_ecdh_curve",
+            SslClientCertificate(..) => "ssl_client_certificate",
+            SslVerifyClient(..) => "ssl_verify_client",
+            SslVerifyDepth(..) => "ssl_verify_depth",
             // openresty
             RewriteByLuaFile(..) => "rewrite_by_lua_file",
             BalancerByLuaFile(..) => "balancer_by_lua_file",
@@ -496,6 +1311,12 @@ impl Item {
             // access module
             Allow(..) => "allow",
             Deny(..) => "deny",
+            AuthBasic(..) => "auth_basic",
+            AuthBasicUserFile(..) => "auth_basic_user_file",
+            AuthRequest(..) => "auth_request",
+            AuthRequestSet {..} => "auth_request_set",
+            Satisfy(..) => "satisfy",
+            AuthJwt(..) => "auth_jwt",
             // log module
             AccessLog(..) => "access_log",
             // real_ip module
@@ -504,14 +1325,16 @@ impl Item {
             SetRealIpFrom(..) => "set_real_ip_from",
             // index module
             Index(..) => "index",
+            Extension(ref ext) => ext.directive.name(),
         }
     }
 
-    pub fn children(&self) -> Option<&[Directive]> {
+    pub fn children(&self) -> Option<&[Directive<'a>]> {
         use self::Item::*;
         match *self {
             Daemon(_) => None,
             MasterProcess(_) => None,
+            LoadModule(..) => None,
             WorkerProcesses(_) => None,
             Http(ref h) => Some(&h.directives[..]),
             Server(ref s) => Some(&s.directives[..]),
@@ -537,6 +1360,97 @@ impl Item {
             ProxyNextUpstreamTries(..) => None,
             ProxyNextUpstreamTimeout(..) => None,
             ProxyNextUpstream(..) => None,
+            ProxyRedirect(..) => None,
+            ProxyBuffers(..) => None,
+            ProxyBufferSize(..) => None,
+            ProxyBusyBuffersSize(..) => None,
+            ProxySendTimeout(..) => None,
+            ProxyRequestBuffering(..) => None,
+            ProxySslServerName(..) => None,
+            ProxySslVerify(..) => None,
+            ProxySslName(..) => None,
+            ProxyCookieDomain(..) => None,
+            ProxyCookiePath(..) => None,
+            ProxyCachePath(..) => None,
+            ProxyCacheBypass(..) => None,
+            ProxyNoCache(..) => None,
+            ProxyCacheLock(..) => None,
+            ProxyCacheUseStale(..) => None,
+            ProxyStore(..) => None,
+            // fastcgi module
+            FastcgiPass(..) => None,
+            FastcgiParam(..) => None,
+            FastcgiIndex(..) => None,
+            FastcgiSplitPathInfo(..) => None,
+            FastcgiReadTimeout(..) => None,
+            FastcgiBuffers(..) => None,
+            FastcgiInterceptErrors(..) => None,
+            FastcgiCache(..) => None,
+            FastcgiCacheKey(..) => None,
+            FastcgiCacheValid(..) => None,
+            FastcgiCacheBypass(..) => None,
+            FastcgiCacheLock(..) => None,
+            FastcgiCacheMethods(..) => None,
+            FastcgiCacheMinUses(..) => None,
+            FastcgiCacheUseStale(..) => None,
+            FastcgiCachePath(..) => None,
+            FastcgiNoCache(..) => None,
+            FastcgiCacheLockAge(..) => None,
+            FastcgiCacheLockTimeout(..) => None,
+            FastcgiCacheRevalidate(..) => None,
+            FastcgiCacheBackgroundUpdate(..) => None,
+            FastcgiCacheMaxRangeOffset(..) => None,
+            // uwsgi module
+            UwsgiPass(..) => None,
+            UwsgiParam(..) => None,
+            UwsgiReadTimeout(..) => None,
+            UwsgiConnectTimeout(..) => None,
+            UwsgiSendTimeout(..) => None,
+            UwsgiBuffering(..) => None,
+            UwsgiBuffers(..) => None,
+            UwsgiInterceptErrors(..) => None,
+            UwsgiNextUpstream(..) => None,
+            UwsgiCache(..) => None,
+            UwsgiCacheKey(..) => None,
+            UwsgiCacheValid(..) => None,
+            // scgi module
+            ScgiPass(..) => None,
+            ScgiParam(..) => None,
+            ScgiReadTimeout(..) => None,
+            ScgiConnectTimeout(..) => None,
+            ScgiSendTimeout(..) => None,
+            ScgiBuffering(..) => None,
+            ScgiBuffers(..) => None,
+            ScgiInterceptErrors(..) => None,
+            ScgiNextUpstream(..) => None,
+            ScgiCache(..) => None,
+            ScgiCacheKey(..) => None,
+            ScgiCacheValid(..) => None,
+            // grpc module
+            GrpcPass(..) => None,
+            GrpcSetHeader {..} => None,
+            GrpcReadTimeout(..) => None,
+            GrpcConnectTimeout(..) => None,
+            GrpcSendTimeout(..) => None,
+            GrpcInterceptErrors(..) => None,
+            GrpcNextUpstream(..) => None,
+            GrpcSslCertificate(..) => None,
+            GrpcSslCertificateKey(..) => None,
+            GrpcSslProtocols(..) => None,
+            GrpcSslCiphers(..) => None,
+            GrpcSslVerify(..) => None,
+            GrpcSslTrustedCertificate(..) => None,
+            GrpcSslServerName(..) => None,
+            GrpcSslName(..) => None,
+            LimitReqZone(..) => None,
+            LimitReq(..) => None,
+            LimitReqStatus(..) => None,
+            LimitReqLogLevel(..) => None,
+            LimitConnZone(..) => None,
+            LimitConn {..} => None,
+            LimitConnStatus(..) => None,
+            LimitRate(..) => None,
+            LimitRateAfter(..) => None,
             Gzip(..) => None,
             GzipStatic(..) => None,
             GzipProxied(..) => None,
@@ -564,8 +1478,25 @@ impl Item {
             ChunkedTransferEncoding(..) => None,
             KeepaliveTimeout(..) => None,
             ServerTokens(..) => None,
+            Http2(..) => None,
+            // ssl module
+            Ssl(..) => None,
             SslCertificate(..) => None,
             SslCertificateKey(..) => None,
+            SslProtocols(..) => None,
+            SslCiphers(..) => None,
+            SslPreferServerCiphers(..) => None,
+            SslSessionCache(..) => None,
+            SslSessionTimeout(..) => None,
+            SslSessionTickets(..) => None,
+            SslStapling(..) => None,
+            SslStaplingVerify(..) => None,
+            SslTrustedCertificate(..) => None,
+            SslDhparam(..) => None,
+            SslEcdhCurve(..) => None,
+            SslClientCertificate(..) => None,
+            SslVerifyClient(..) => None,
+            SslVerifyDepth(..) => None,
             // openresty
             RewriteByLuaFile(..) => None,
             BalancerByLuaFile(..) => None,
@@ -581,6 +1512,12 @@ impl Item {
             // access
             Allow(..) => None,
             Deny(..) => None,
+            AuthBasic(..) => None,
+            AuthBasicUserFile(..) => None,
+            AuthRequest(..) => None,
+            AuthRequestSet {..} => None,
+            Satisfy(..) => None,
+            AuthJwt(..) => None,
             // log module
             AccessLog(..) => None,
             // real_ip module
@@ -589,14 +1526,16 @@ impl Item {
             SetRealIpFrom(..) => None,
             // index module
             Index(..) => None,
+            Extension(ref ext) => ext.directives.as_ref().map(|d| &d[..]),
         }
     }
 
-    pub fn children_mut(&mut self) -> Option<&mut Vec<Directive>> {
+    pub fn children_mut(&mut self) -> Option<&mut Vec<Directive<'a>>> {
         use self::Item::*;
         match *self {
             Daemon(_) => None,
             MasterProcess(_) => None,
+            LoadModule(..) => None,
             WorkerProcesses(_) => None,
             Http(ref mut h) => Some(&mut h.directives),
             Server(ref mut s) => Some(&mut s.directives),
@@ -622,6 +1561,97 @@ impl Item {
             ProxyNextUpstreamTries(..) => None,
             ProxyNextUpstreamTimeout(..) => None,
             ProxyNextUpstream(..) => None,
+            ProxyRedirect(..) => None,
+            ProxyBuffers(..) => None,
+            ProxyBufferSize(..) => None,
+            ProxyBusyBuffersSize(..) => None,
+            ProxySendTimeout(..) => None,
+            ProxyRequestBuffering(..) => None,
+            ProxySslServerName(..) => None,
+            ProxySslVerify(..) => None,
+            ProxySslName(..) => None,
+            ProxyCookieDomain(..) => None,
+            ProxyCookiePath(..) => None,
+            ProxyCachePath(..) => None,
+            ProxyCacheBypass(..) => None,
+            ProxyNoCache(..) => None,
+            ProxyCacheLock(..) => None,
+            ProxyCacheUseStale(..) => None,
+            ProxyStore(..) => None,
+            // fastcgi module
+            FastcgiPass(..) => None,
+            FastcgiParam(..) => None,
+            FastcgiIndex(..) => None,
+            FastcgiSplitPathInfo(..) => None,
+            FastcgiReadTimeout(..) => None,
+            FastcgiBuffers(..) => None,
+            FastcgiInterceptErrors(..) => None,
+            FastcgiCache(..) => None,
+            FastcgiCacheKey(..) => None,
+            FastcgiCacheValid(..) => None,
+            FastcgiCacheBypass(..) => None,
+            FastcgiCacheLock(..) => None,
+            FastcgiCacheMethods(..) => None,
+            FastcgiCacheMinUses(..) => None,
+            FastcgiCacheUseStale(..) => None,
+            FastcgiCachePath(..) => None,
+            FastcgiNoCache(..) => None,
+            FastcgiCacheLockAge(..) => None,
+            FastcgiCacheLockTimeout(..) => None,
+            FastcgiCacheRevalidate(..) => None,
+            FastcgiCacheBackgroundUpdate(..) => None,
+            FastcgiCacheMaxRangeOffset(..) => None,
+            // uwsgi module
+            UwsgiPass(..) => None,
+            UwsgiParam(..) => None,
+            UwsgiReadTimeout(..) => None,
+            UwsgiConnectTimeout(..) => None,
+            UwsgiSendTimeout(..) => None,
+            UwsgiBuffering(..) => None,
+            UwsgiBuffers(..) => None,
+            UwsgiInterceptErrors(..) => None,
+            UwsgiNextUpstream(..) => None,
+            UwsgiCache(..) => None,
+            UwsgiCacheKey(..) => None,
+            UwsgiCacheValid(..) => None,
+            // scgi module
+            ScgiPass(..) => None,
+            ScgiParam(..) => None,
+            ScgiReadTimeout(..) => None,
+            ScgiConnectTimeout(..) => None,
+            ScgiSendTimeout(..) => None,
+            ScgiBuffering(..) => None,
+            ScgiBuffers(..) => None,
+            ScgiInterceptErrors(..) => None,
+            ScgiNextUpstream(..) => None,
+            ScgiCache(..) => None,
+            ScgiCacheKey(..) => None,
+            ScgiCacheValid(..) => None,
+            // grpc module
+            GrpcPass(..) => None,
+            GrpcSetHeader {..} => None,
+            GrpcReadTimeout(..) => None,
+            GrpcConnectTimeout(..) => None,
+            GrpcSendTimeout(..) => None,
+            GrpcInterceptErrors(..) => None,
+            GrpcNextUpstream(..) => None,
+            GrpcSslCertificate(..) => None,
+            GrpcSslCertificateKey(..) => None,
+            GrpcSslProtocols(..) => None,
+            GrpcSslCiphers(..) => None,
+            GrpcSslVerify(..) => None,
+            GrpcSslTrustedCertificate(..) => None,
+            GrpcSslServerName(..) => None,
+            GrpcSslName(..) => None,
+            LimitReqZone(..) => None,
+            LimitReq(..) => None,
+            LimitReqStatus(..) => None,
+            LimitReqLogLevel(..) => None,
+            LimitConnZone(..) => None,
+            LimitConn {..} => None,
+            LimitConnStatus(..) => None,
+            LimitRate(..) => None,
+            LimitRateAfter(..) => None,
             Gzip(..) => None,
             GzipStatic(..) => None,
             GzipProxied(..) => None,
@@ -649,8 +1679,25 @@ impl Item {
             ChunkedTransferEncoding(..) => None,
             KeepaliveTimeout(..) => None,
             ServerTokens(..) => None,
+            Http2(..) => None,
+            // ssl module
+            Ssl(..) => None,
             SslCertificate(..) => None,
             SslCertificateKey(..) => None,
+            SslProtocols(..) => None,
+            SslCiphers(..) => None,
+            SslPreferServerCiphers(..) => None,
+            SslSessionCache(..) => None,
+            SslSessionTimeout(..) => None,
+            SslSessionTickets(..) => None,
+            SslStapling(..) => None,
+            SslStaplingVerify(..) => None,
+            SslTrustedCertificate(..) => None,
+            SslDhparam(..) => None,
+            SslEcdhCurve(..) => None,
+            SslClientCertificate(..) => None,
+            SslVerifyClient(..) => None,
+            SslVerifyDepth(..) => None,
             // openresty
             RewriteByLuaFile(..) => None,
             BalancerByLuaFile(..) => None,
@@ -666,6 +1713,12 @@ impl Item {
             // access
             Allow(..) => None,
             Deny(..) => None,
+            AuthBasic(..) => None,
+            AuthBasicUserFile(..) => None,
+            AuthRequest(..) => None,
+            AuthRequestSet {..} => None,
+            Satisfy(..) => None,
+            AuthJwt(..) => None,
             // log module
             AccessLog(..) => None,
             // real_ip module
@@ -674,6 +1727,7 @@ impl Item {
             SetRealIpFrom(..) => None,
             // index module
             Index(..) => None,
+            Extension(ref mut ext) => ext.directives.as_mut(),
         }
     }
 
@@ -686,164 +1740,34 @@ impl Item {
     ///
     /// [`visit_mutable`]: ../visitors/fn.visit_mutable.html
     pub(crate) fn visit_values_mut<F>(&mut self, mut f: F)
-        where F: FnMut(&mut Value)
+        where F: FnMut(&mut Value<'a>)
     {
         use self::Item::*;
-        match *self {
-            Daemon(_) => {},
-            MasterProcess(_) => {},
-            WorkerProcesses(_) => {},
-            Http(_) => {},
-            Server(_) => {},
-            Location(_) => {},
-            LimitExcept(_) => {},
-            Listen(_) => {},
-            ProxyPass(ref mut v) => f(v),
-            ProxySetHeader { ref mut field, ref mut value } => {
-                f(field);
-                f(value);
-            }
-            ProxyMethod(ref mut v) => f(v),
-            ProxyReadTimeout(ref mut v) => f(v),
-            ProxyConnectTimeout(ref mut v) => f(v),
-            ProxyHideHeader(ref mut v) => f(v),
-            ProxyPassHeader(ref mut v) => f(v),
-            ProxyCache(ref mut v) => f(v),
-            ProxyCacheKey(ref mut v) => f(v),
-            ProxyCacheValid(self::ProxyCacheValid::Normal(ref mut v)) => f(v),
-            ProxyCacheValid(self::ProxyCacheValid::Specific(_, ref mut v)) => f(v),
-            ProxyCacheValid(self::ProxyCacheValid::Any(ref mut v)) => f(v),
-            ProxyPassRequestHeaders(_) => {},
-            ProxyPassRequestBody(_) => {},
-            ProxyHttpVersion(..) => {},
-            ProxyIgnoreHeaders(..) => {},
-            ProxyInterceptErrors(..) => {},
-            ProxyBuffering(..) => {},
-            ProxyNextUpstreamTries(ref mut v) => f(v),
-            ProxyNextUpstreamTimeout(ref mut v) => f(v),
-            ProxyNextUpstream(_) => {},
-            Gzip(_) => {},
-            GzipStatic(_) => {},
-            GzipProxied(_) => {},
-            AddHeader(self::AddHeader { ref mut field, ref mut value, .. })
-            => {
-                f(field);
-                f(value);
-            }
-            Expires(self::Expires { ref mut value, .. }) => f(value),
-            Root(ref mut v) => f(v),
-            Alias(ref mut v) => f(v),
-            ErrorPage(::ast::ErrorPage { ref mut uri, .. }) => f(uri),
-            DefaultType(ref mut v) => f(v),
-            Types(ref mut t) => {
-                for (ref mut mime, ref mut exts) in &mut t.types {
-                    f(mime);
-                    for e in exts {
-                        f(e);
-                    }
-                }
-            }
-            ErrorLog { ref mut file, .. } => f(file),
-            Rewrite(ref mut rw) => f(&mut rw.replacement),
-            Return(::ast::Return::Redirect { ref mut url, .. }) => f(url),
-            Return(::ast::Return::Text { text: Some(ref mut t), .. }) => f(t),
-            Return(::ast::Return::Text { text: None, .. }) => {},
-            If(self::If { ref mut condition, .. }) => {
-                use self::IfCondition::*;
-                match condition {
-                    NonEmpty(ref mut v) => f(v),
-                    Eq(ref mut v, _) => f(v),
-                    Neq(ref mut v, _) => f(v),
-                    RegEq(ref mut v, _, _) => f(v),
-                    RegNeq(ref mut v, _, _) => f(v),
-                    Exists(ref mut v) => f(v),
-                    NotExists(ref mut v) => f(v),
-                    FileExists(ref mut v) => f(v),
-                    FileNotExists(ref mut v) => f(v),
-                    DirExists(ref mut v) => f(v),
-                    DirNotExists(ref mut v) => f(v),
-                    Executable(ref mut v) => f(v),
-                    NotExecutable(ref mut v) => f(v),
-                }
-            },
-            TryFiles(ref mut tf) => {
-                for opt in &mut tf.options {
-                    f(opt);
-                }
-                match tf.last_option {
-                    TryFilesLastOption::Uri(ref mut v) => f(v),
-                    TryFilesLastOption::NamedLocation(..) => {},
-                    TryFilesLastOption::Code(..) => {},
-                }
-            },
-            Include(ref mut v) => f(v),
-            EmptyGif => {}
-            Internal => {}
-            Etag(..) => {}
-            RecursiveErrorPages(..) => {}
-            ChunkedTransferEncoding(..) => {}
-            KeepaliveTimeout(ref mut a, ref mut b) => {
-                f(a);
-                if let Some(b) = b {
-                    f(b);
-                }
-            }
-            ServerTokens(ref mut v) => f(v),
-            SslCertificate(ref mut v) => f(v),
-            SslCertificateKey(ref mut v) => f(v),
-            ServerName(_) => {},
-            Set { ref mut value, .. } => f(value),
-            Map(::ast::Map {
-                ref mut expression,
-                ref mut default,
-                ref mut patterns,
-                ..
-            }) => {
-                f(expression);
-                if let Some(ref mut def) = default {
-                    f(def);
-                }
-                for (_, v) in patterns {
-                    f(v);
-                }
-            }
-            ClientMaxBodySize(ref mut v) => f(v),
-            // openresty
-            RewriteByLuaFile(ref mut v) => f(v),
-            BalancerByLuaFile(ref mut v) => f(v),
-            AccessByLuaFile(ref mut v) => f(v),
-            HeaderFilterByLuaFile(ref mut v) => f(v),
-            ContentByLuaFile(ref mut v) => f(v),
-            BodyFilterByLuaFile(ref mut v) => f(v),
-            LogByLuaFile(ref mut v) => f(v),
-            LuaNeedRequestBody(ref mut v) => f(v),
-            SslCertificateByLuaFile(ref mut v) => f(v),
-            SslSessionFetchByLuaFile(ref mut v) => f(v),
-            SslSessionStoreByLuaFile(ref mut v) => f(v),
-            // access
-            Allow(..) => {},
-            Deny(..) => {},
-            // log module
-            AccessLog(::ast::AccessLog::Off) => {},
-            AccessLog(::ast::AccessLog::On(ref mut lg)) => {
-                f(&mut lg.path);
-                lg.condition.as_mut().map(f);
-            },
-            // real_ip module
-            RealIpHeader(ref mut v) => f(v),
-            RealIpRecursive(..) => {},
-            SetRealIpFrom(..) => {},
-            // index module
-            Index(ref mut items) => {
-                for v in items {
-                    f(v);
-                }
-            }
-        }
+        visit_item_values!(*self, f, (mut), ext => {
+            // `f` might put borrowed strings into the values, so it gets
+            // a copy
+            ext.directive.visit_values_mut(&mut |v| {
+                let mut value: Value<'a> = v.clone();
+                f(&mut value);
+                *v = value.into_owned();
+            })
+        })
+    }
+
+    /// Executes function on all the Value things (not recursively)
+    ///
+    /// Read-only counterpart of `visit_values_mut`.
+    pub(crate) fn visit_values<'b, F>(&'b self, mut f: F)
+        where F: FnMut(&'b Value<'a>)
+    {
+        use self::Item::*;
+        visit_item_values!(*self, f, (), ext => {
+            ext.directive.visit_values(&mut |v| f(v))
+        })
     }
 }
 
-impl Directive {
+impl<'a> Directive<'a> {
     /// Executes function on all the Value things (not recursively)
     ///
     /// This is useful for substituting variables.
@@ -853,13 +1777,23 @@ impl Directive {
     ///
     /// [`visit_mutable`]: ../visitors/fn.visit_mutable.html
     pub fn visit_values_mut<F>(&mut self, f: F)
-        where F: FnMut(&mut Value)
+        where F: FnMut(&mut Value<'a>)
     {
         self.item.visit_values_mut(f)
     }
+
+    /// Executes function on all the Value things (not recursively)
+    ///
+    /// Like [`visit_values_mut`](#method.visit_values_mut) but for
+    /// read-only access.
+    pub fn visit_values<'b, F>(&'b self, f: F)
+        where F: FnMut(&'b Value<'a>)
+    {
+        self.item.visit_values(f)
+    }
 }
 
-impl Main {
+impl<'a> Main<'a> {
     pub fn all_directives(&self) -> DirectiveIter {
         DirectiveIter::depth_first(&self.directives)
     }
@@ -6,19 +6,19 @@ use combine::error::StreamError;
 use combine::easy::Error;
 
 use ast::{self, Item};
-use grammar::{value, bool, block, Code};
+use grammar::{value, bool, block, duration, Code};
 use helpers::{semi, ident, string, prefix};
 use tokenizer::{TokenStream, Token};
 use value::Value;
 
 
 fn error_page<'a>()
-    -> impl Parser<Output=Item, Input=TokenStream<'a>>
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
 {
     use ast::ErrorPageResponse;
     use value::Item::*;
 
-    fn lit<'a, 'x>(val: &'a Value) -> Result<&'a str, Error<Token<'x>, Token<'x>>> {
+    fn lit<'a, 'x>(val: &'a Value<'a>) -> Result<&'a str, Error<Token<'x>, Token<'x>>> {
         if val.data.is_empty() {
             return Err(Error::unexpected_message(
                 "empty error codes are not supported"));
@@ -94,7 +94,7 @@ enum ListenParts {
 }
 
 fn listen<'a>()
-    -> impl Parser<Output=Item, Input=TokenStream<'a>>
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
 {
     use ast::{Address, Listen, HttpExt};
     use self::ListenParts::*;
@@ -157,10 +157,10 @@ fn listen<'a>()
 }
 
 fn limit_except<'a>()
-    -> impl Parser<Output=Item, Input=TokenStream<'a>>
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
 {
     ident("limit_except")
-    .with(many1(string().map(|x| x.value.to_string())))
+    .with(many1(string().map(|x| x.value.into())))
     .and(block())
     .map(|(methods, (position, directives))| {
         Item::LimitExcept(ast::LimitExcept { methods, position, directives })
@@ -168,7 +168,7 @@ fn limit_except<'a>()
 }
 
 pub fn directives<'a>()
-    -> impl Parser<Output=Item, Input=TokenStream<'a>>
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
 {
     choice((
         error_page(),
@@ -182,13 +182,18 @@ pub fn directives<'a>()
         ident("etag").with(bool()).skip(semi()).map(Item::Etag),
         ident("server_tokens").with(value()).skip(semi())
             .map(Item::ServerTokens),
+        ident("http2").with(bool()).skip(semi()).map(Item::Http2),
+        ident("limit_rate").with(value()).skip(semi())
+            .map(Item::LimitRate),
+        ident("limit_rate_after").with(value()).skip(semi())
+            .map(Item::LimitRateAfter),
         ident("recursive_error_pages").with(bool()).skip(semi())
             .map(Item::RecursiveErrorPages),
         ident("chunked_transfer_encoding").with(bool()).skip(semi())
             .map(Item::ChunkedTransferEncoding),
         ident("keepalive_timeout")
-            .with(value())
-            .and(optional(value()))
+            .with(duration())
+            .and(optional(duration()))
             .map(|(timeo, htimeo)| Item::KeepaliveTimeout(timeo, htimeo))
             .skip(semi()),
         ident("error_log").with(value())
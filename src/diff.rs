@@ -0,0 +1,264 @@
+//! Semantic diff of two configs
+//!
+//! Directives are compared by meaning rather than by text, so formatting
+//! and comments don't produce changes. Servers are matched by `server_name`
+//! and `listen`, locations by their pattern and other blocks by their
+//! arguments. Directives of the same name are compared as a set, so moving
+//! a directive within the block isn't reported. The exception is
+//! directives whose order matters to nginx: `allow` and `deny`, `rewrite`,
+//! `if` and regex locations are compared by position.
+use std::fmt;
+
+use ast::{Directive, Item, Main};
+use display::single_line as text;
+
+
+/// Kind of the change
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// Directive or block exists only in the new config
+    Added,
+    /// Directive or block exists only in the old config
+    Removed,
+    /// Directive exists in both configs but has different arguments
+    Changed,
+}
+
+/// A single difference between two configs
+#[derive(Debug, Clone)]
+pub struct Change<'a> {
+    /// Kind of the change
+    pub kind: ChangeKind,
+    /// Enclosing blocks and the directive itself, e.g.
+    /// `["http", "server[api.example.com:443]", "location[/v1]",
+    /// "proxy_pass"]`
+    pub path: Vec<String>,
+    /// Directive in the old config (`None` if added)
+    pub old: Option<&'a Directive<'a>>,
+    /// Directive in the new config (`None` if removed)
+    pub new: Option<&'a Directive<'a>>,
+}
+
+impl<'a> Change<'a> {
+    /// Path formatted as `http > server[api.example.com:443] > proxy_pass`
+    pub fn path_string(&self) -> String {
+        self.path.join(" > ")
+    }
+}
+
+impl<'a> fmt::Display for Change<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = match self.kind {
+            ChangeKind::Added => "+",
+            ChangeKind::Removed => "-",
+            ChangeKind::Changed => "~",
+        };
+        write!(f, "{} {}", sign, self.path_string())?;
+        // blocks are fully described by the path
+        let leaf = |dir: &&Directive| dir.item.children().is_none();
+        match (self.old.filter(leaf), self.new.filter(leaf)) {
+            (Some(old), Some(new)) => {
+                write!(f, ": {} => {}", text(old), text(new))
+            }
+            (Some(dir), None) | (None, Some(dir)) => {
+                write!(f, ": {}", text(dir))
+            }
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+/// Returns changes needed to turn `old` config into `new` one
+///
+/// Within each block, changes follow the order of the old config, and
+/// directives which exist only in the new config go last. Added or removed
+/// block is reported once, without its contents.
+pub fn diff<'a>(old: &'a Main<'a>, new: &'a Main<'a>) -> Vec<Change<'a>> {
+    let mut changes = Vec::new();
+    diff_block(&old.directives, &new.directives, &mut Vec::new(),
+        &mut changes);
+    changes
+}
+
+/// Key used to match blocks and directives between configs
+fn label(dir: &Directive) -> String {
+    let name = dir.item.directive_name();
+    match dir.item {
+        Item::Server(ref srv) => {
+            let mut names = Vec::new();
+            let mut listens = Vec::new();
+            for sub in &srv.directives {
+                match sub.item {
+                    Item::ServerName(ref items) => {
+                        names.extend(items.iter().map(|n| n.to_string()));
+                    }
+                    Item::Listen(ref lst) => {
+                        listens.push(lst.address.to_string());
+                    }
+                    _ => {}
+                }
+            }
+            let mut key = names.join(" ");
+            if !listens.is_empty() {
+                key.push(':');
+                key.push_str(&listens.join(","));
+            }
+            if key.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}[{}]", name, key)
+            }
+        }
+        _ if dir.item.children().is_some() => {
+            let header = text(dir);
+            let args = header[name.len()..].trim();
+            if args.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}[{}]", name, args)
+            }
+        }
+        _ => name.to_string(),
+    }
+}
+
+/// Sequence of the directives which nginx checks in order
+fn sequence(dir: &Directive) -> Option<&'static str> {
+    use ast::LocationPattern::*;
+    match dir.item {
+        Item::Allow(..) | Item::Deny(..) => Some("allow/deny"),
+        Item::Rewrite(..) => Some("rewrite"),
+        Item::If(..) => Some("if"),
+        Item::Location(ref loc) => match loc.pattern {
+            Regex(..) | RegexInsensitive(..) => Some("location ~"),
+            Prefix(..) | Exact(..) | FinalPrefix(..) | Named(..) => None,
+        },
+        _ => None,
+    }
+}
+
+/// Groups directives by label, keeping the order of first occurrence
+///
+/// Directives of the same [`sequence`](fn.sequence.html) are put in a
+/// single group.
+fn group<'a>(old: &'a [Directive<'a>], new: &'a [Directive<'a>])
+    -> Vec<(String, Vec<&'a Directive<'a>>, Vec<&'a Directive<'a>>)>
+{
+    let mut groups: Vec<(String, Vec<_>, Vec<_>)> = Vec::new();
+    for (is_new, dirs) in [(false, old), (true, new)] {
+        for dir in dirs {
+            let key = sequence(dir).map_or_else(|| label(dir), String::from);
+            let idx = match groups.iter().position(|g| g.0 == key) {
+                Some(idx) => idx,
+                None => {
+                    groups.push((key, Vec::new(), Vec::new()));
+                    groups.len() - 1
+                }
+            };
+            if is_new {
+                groups[idx].2.push(dir);
+            } else {
+                groups[idx].1.push(dir);
+            }
+        }
+    }
+    groups
+}
+
+fn change<'a>(kind: ChangeKind, path: &[String], key: &str,
+    old: Option<&'a Directive<'a>>, new: Option<&'a Directive<'a>>)
+    -> Change<'a>
+{
+    let mut path = path.to_vec();
+    path.push(key.to_string());
+    Change { kind, path, old, new }
+}
+
+fn diff_block<'a>(old: &'a [Directive<'a>], new: &'a [Directive<'a>],
+    path: &mut Vec<String>, changes: &mut Vec<Change<'a>>)
+{
+    use self::ChangeKind::*;
+
+    for (key, mut old, mut new) in group(old, new) {
+        if old.iter().chain(&new).any(|d| sequence(d).is_some()) {
+            diff_sequence(&old, &new, path, changes);
+            continue;
+        }
+        let is_block = old.iter().chain(&new)
+            .any(|d| d.item.children().is_some());
+        if !is_block {
+            // drop directives which are unchanged
+            let new_text = new.iter().map(|d| text(d)).collect::<Vec<_>>();
+            let mut used = vec![false; new.len()];
+            old.retain(|d| {
+                let old_text = text(d);
+                let same = (0..new.len())
+                    .find(|&i| !used[i] && new_text[i] == old_text);
+                match same {
+                    Some(i) => {
+                        used[i] = true;
+                        false
+                    }
+                    None => true,
+                }
+            });
+            let mut used = used.into_iter();
+            new.retain(|_| !used.next().unwrap());
+        }
+        let common = old.len().min(new.len());
+        for (o, n) in old.iter().zip(&new) {
+            if is_block {
+                path.push(key.clone());
+                diff_block(o.item.children().unwrap_or(&[]),
+                           n.item.children().unwrap_or(&[]),
+                           path, changes);
+                path.pop();
+            } else {
+                changes.push(change(Changed, path, &key, Some(o), Some(n)));
+            }
+        }
+        for o in &old[common..] {
+            changes.push(change(Removed, path, &key, Some(o), None));
+        }
+        for n in &new[common..] {
+            changes.push(change(Added, path, &key, None, Some(n)));
+        }
+    }
+}
+
+/// Compares directives whose order matters by position
+fn diff_sequence<'a>(old: &[&'a Directive<'a>], new: &[&'a Directive<'a>],
+    path: &mut Vec<String>, changes: &mut Vec<Change<'a>>)
+{
+    use self::ChangeKind::*;
+
+    for idx in 0..old.len().max(new.len()) {
+        match (old.get(idx), new.get(idx)) {
+            (Some(&o), Some(&n)) => {
+                let (old_key, new_key) = (label(o), label(n));
+                if old_key != new_key {
+                    changes.push(change(Removed, path, &old_key,
+                                        Some(o), None));
+                    changes.push(change(Added, path, &new_key,
+                                        None, Some(n)));
+                } else if o.item.children().is_some() {
+                    path.push(old_key);
+                    diff_block(o.item.children().unwrap_or(&[]),
+                               n.item.children().unwrap_or(&[]),
+                               path, changes);
+                    path.pop();
+                } else if text(o) != text(n) {
+                    changes.push(change(Changed, path, &old_key,
+                                        Some(o), Some(n)));
+                }
+            }
+            (Some(&o), None) => {
+                changes.push(change(Removed, path, &label(o), Some(o), None));
+            }
+            (None, Some(&n)) => {
+                changes.push(change(Added, path, &label(n), None, Some(n)));
+            }
+            (None, None) => unreachable!(),
+        }
+    }
+}
@@ -1,10 +1,12 @@
-use ast;
+use std::borrow::Cow;
 use std::fmt;
+
+use ast;
 use format::{Displayable, Formatter, Style};
 
 use value;
 
-impl Displayable for ast::Main {
+impl<'a> Displayable for ast::Main<'a> {
     fn display(&self, f: &mut Formatter) {
         for item in &self.directives {
             item.display(f);
@@ -12,7 +14,7 @@ impl Displayable for ast::Main {
     }
 }
 
-impl Displayable for ast::Directive {
+impl<'a> Displayable for ast::Directive<'a> {
     fn display(&self, f: &mut Formatter) {
         self.item.display(f)
     }
@@ -39,21 +41,42 @@ fn one_arg_dir(name: &str, val: &value::Value, f: &mut Formatter) {
     f.end();
 }
 
-impl Displayable for ast::Item {
+impl<'a> Displayable for ast::Item<'a> {
     fn display(&self, f: &mut Formatter) {
         use ast::Item::*;
         match *self {
             | Daemon(opt)
             | MasterProcess(opt)
+            | Ssl(opt)
+            | Http2(opt)
             | ProxyPassRequestHeaders(opt)
             | ProxyPassRequestBody(opt)
             | ProxyInterceptErrors(opt)
             | ProxyBuffering(opt)
+            | ProxyRequestBuffering(opt)
+            | ProxySslServerName(opt)
+            | ProxySslVerify(opt)
+            | ProxyCacheLock(opt)
             | Gzip(opt)
             | Etag(opt)
             | RecursiveErrorPages(opt)
             | ChunkedTransferEncoding(opt)
             | RealIpRecursive(opt)
+            | SslPreferServerCiphers(opt)
+            | SslSessionTickets(opt)
+            | SslStapling(opt)
+            | SslStaplingVerify(opt)
+            | FastcgiInterceptErrors(opt)
+            | FastcgiCacheLock(opt)
+            | FastcgiCacheRevalidate(opt)
+            | FastcgiCacheBackgroundUpdate(opt)
+            | UwsgiBuffering(opt)
+            | UwsgiInterceptErrors(opt)
+            | ScgiBuffering(opt)
+            | ScgiInterceptErrors(opt)
+            | GrpcInterceptErrors(opt)
+            | GrpcSslVerify(opt)
+            | GrpcSslServerName(opt)
             => {
                 f.indent();
                 f.write(self.directive_name());
@@ -93,9 +116,12 @@ impl Displayable for ast::Item {
                 f.indent();
                 lst.display(f);
             }
-            ProxySetHeader { ref field, ref value } => {
+            | ProxySetHeader { ref field, ref value }
+            | GrpcSetHeader { ref field, ref value }
+            => {
                 f.indent();
-                f.write("proxy_set_header ");
+                f.write(self.directive_name());
+                f.write(" ");
                 field.display(f);
                 f.write(" ");
                 value.display(f);
@@ -128,22 +154,10 @@ impl Displayable for ast::Item {
                 f.end();
             }
             ServerName(ref items) => {
-                use ast::ServerName::*;
                 f.indent();
                 f.write("server_name");
                 for item in items {
-                    match *item {
-                        Exact(ref v)
-                        => f.fmt(&format_args!(" {}", escape(&v))),
-                        Suffix(ref v)
-                        => f.fmt(&format_args!(" .{}", escape(&v))),
-                        StarSuffix(ref v)
-                        => f.fmt(&format_args!(" *.{}", escape(&v))),
-                        StarPrefix(ref v)
-                        => f.fmt(&format_args!(" {}.*", escape(&v))),
-                        Regex(ref v)
-                        => f.fmt(&format_args!(" ~{}", escape(&v))),
-                    }
+                    f.fmt(&format_args!(" {}", item));
                 }
                 f.end();
             }
@@ -184,29 +198,46 @@ impl Displayable for ast::Item {
                 for inc in &m.includes {
                     f.indent();
                     f.write("include ");
-                    f.write(escape(inc));
+                    f.write(&escape(inc));
                     f.end();
                 }
-                for &(ref pat, ref value) in &m.patterns {
+                let mut patterns = m.patterns.iter()
+                    .map(|(pat, value)| {
+                        let text = match *pat {
+                            Exact(ref v) if matches!(&v[..],
+                                | "volatile"
+                                | "hostnames"
+                                | "default"
+                                | "include"
+                            ) => format!("\\{}", v),
+                            // literal `~` or `\` must not be read as prefix
+                            Exact(ref v) if v.starts_with(['~', '\\'])
+                            => format!("\\{}", v),
+                            Exact(ref v) => v.to_string(),
+                            Suffix(ref v) => format!(".{}", v),
+                            StarSuffix(ref v) => format!("*.{}", v),
+                            StarPrefix(ref v) => format!("{}.*", v),
+                            Regex(ref v, quote) => {
+                                let text = format!("~{}", v);
+                                return (true,
+                                    value::quote_as(&text, quote).into_owned(),
+                                    value);
+                            }
+                        };
+                        let text = escape(&text).into_owned();
+                        (false, text, value)
+                    })
+                    .collect::<Vec<_>>();
+                if f.style().sort_map_entries {
+                    // regexes are matched in order so they are kept last
+                    patterns.sort_by(|a, b| match (a.0, b.0) {
+                        (false, false) => a.1.cmp(&b.1),
+                        (a_regex, b_regex) => a_regex.cmp(&b_regex),
+                    });
+                }
+                for (_, text, value) in patterns {
                     f.indent();
-                    match *pat {
-                        Exact(ref v) if matches!(&v[..],
-                            | "volatile"
-                            | "hostnames"
-                            | "default"
-                            | "include"
-                        ) => f.fmt(&format_args!("\\{}", escape(&v))),
-                        Exact(ref v)
-                        => f.fmt(&format_args!("{}", escape(&v))),
-                        Suffix(ref v)
-                        => f.fmt(&format_args!(".{}", escape(&v))),
-                        StarSuffix(ref v)
-                        => f.fmt(&format_args!("*.{}", escape(&v))),
-                        StarPrefix(ref v)
-                        => f.fmt(&format_args!("{}.*", escape(&v))),
-                        Regex(ref v)
-                        => f.fmt(&format_args!("~{}", escape(&v))),
-                    }
+                    f.write(&text);
                     f.write(" ");
                     value.display(f);
                     f.end();
@@ -217,7 +248,7 @@ impl Displayable for ast::Item {
                 use ast::RewriteFlag::*;
                 f.indent();
                 f.write("rewrite ");
-                f.write(escape(&rw.regex));
+                f.write(&escape(&rw.regex));
                 f.write(" ");
                 rw.replacement.display(f);
                 f.write(match rw.flag {
@@ -248,7 +279,9 @@ impl Displayable for ast::Item {
             | Root(ref val)
             | Alias(ref val)
             | DefaultType(ref val)
-            | ClientMaxBodySize(ref val)
+            | LimitRate(ref val)
+            | LimitRateAfter(ref val)
+            | AuthBasicUserFile(ref val)
             | Include(ref val)
             | RewriteByLuaFile(ref val)
             | BalancerByLuaFile(ref val)
@@ -263,17 +296,34 @@ impl Displayable for ast::Item {
             | SslSessionStoreByLuaFile(ref val)
             | SslCertificate(ref val)
             | SslCertificateKey(ref val)
+            | SslTrustedCertificate(ref val)
+            | SslDhparam(ref val)
+            | SslClientCertificate(ref val)
             | ProxyPass(ref val)
             | ProxyCache(ref val)
             | ProxyCacheKey(ref val)
             | ProxyMethod(ref val)
-            | ProxyReadTimeout(ref val)
-            | ProxyConnectTimeout(ref val)
             | ProxyHideHeader(ref val)
             | ProxyPassHeader(ref val)
             | ProxyNextUpstreamTries(ref val)
-            | ProxyNextUpstreamTimeout(ref val)
+            | ProxySslName(ref val)
+            | FastcgiPass(ref val)
+            | FastcgiIndex(ref val)
+            | FastcgiCache(ref val)
+            | FastcgiCacheKey(ref val)
+            | UwsgiPass(ref val)
+            | UwsgiCache(ref val)
+            | UwsgiCacheKey(ref val)
+            | ScgiPass(ref val)
+            | ScgiCache(ref val)
+            | ScgiCacheKey(ref val)
+            | GrpcPass(ref val)
+            | GrpcSslCertificate(ref val)
+            | GrpcSslCertificateKey(ref val)
+            | GrpcSslTrustedCertificate(ref val)
+            | GrpcSslName(ref val)
             | ServerTokens(ref val)
+            | LoadModule(ref val)
             | RealIpHeader(ref val)
             => {
                 one_arg_dir(self.directive_name(), val, f);
@@ -349,12 +399,21 @@ impl Displayable for ast::Item {
                 f.end();
             }
             Expires(::ast::Expires { modified, ref value }) => {
+                use ast::ExpiresTime::*;
                 f.indent();
                 f.write("expires ");
                 if modified {
                     f.write("modified ");
                 }
-                value.display(f);
+                match *value {
+                    Off => f.write("off"),
+                    Epoch => f.write("epoch"),
+                    Max => f.write("max"),
+                    After(ref time) => f.fmt(time),
+                    Before(ref time) => f.fmt(&format_args!("-{}", time)),
+                    TimeOfDay(ref time) => f.fmt(&format_args!("@{}", time)),
+                    Variable(ref val) => val.display(f),
+                }
                 f.end();
             }
             If(ast::If { ref condition, ref directives, position: _ }) => {
@@ -373,23 +432,23 @@ impl Displayable for ast::Item {
                         f.write(" != ");
                         f.write(&escape(s));
                     }
-                    RegEq(ref v, ref r, case) => {
+                    RegEq(ref v, ref r, case, quote) => {
                         v.display(f);
                         if *case {
                             f.write(" ~ ");
                         } else {
                             f.write(" ~* ");
                         }
-                        f.write(&escape(r));
+                        f.write(&value::quote_as(r, *quote));
                     }
-                    RegNeq(ref v, ref r, case) => {
+                    RegNeq(ref v, ref r, case, quote) => {
                         v.display(f);
                         if *case {
                             f.write(" !~ ");
                         } else {
                             f.write(" !~* ");
                         }
-                        f.write(&escape(r));
+                        f.write(&value::quote_as(r, *quote));
                     }
                     Exists(ref v) => {
                         f.write("-e ");
@@ -431,6 +490,49 @@ impl Displayable for ast::Item {
                 }
                 f.end_block();
             }
+            AuthBasic(ast::AuthBasic::Off)
+            | AuthRequest(ast::AuthRequest::Off)
+            | AuthJwt(ast::AuthJwt::Off)
+            => {
+                f.indent();
+                f.write(self.directive_name());
+                f.write(" off");
+                f.end();
+            }
+            | AuthBasic(ast::AuthBasic::Realm(ref val))
+            | AuthRequest(ast::AuthRequest::Uri(ref val))
+            => {
+                one_arg_dir(self.directive_name(), val, f);
+            }
+            AuthJwt(ast::AuthJwt::Realm { ref realm, ref token }) => {
+                f.indent();
+                f.write(self.directive_name());
+                f.write(" ");
+                realm.display(f);
+                if let Some(ref token) = token {
+                    f.write(" token=");
+                    token.display(f);
+                }
+                f.end();
+            }
+            AuthRequestSet { ref variable, ref value } => {
+                f.indent();
+                f.write(self.directive_name());
+                f.write(" $");
+                f.write(variable);
+                f.write(" ");
+                value.display(f);
+                f.end();
+            }
+            Satisfy(satisfy) => {
+                f.indent();
+                f.write(self.directive_name());
+                f.write(match satisfy {
+                    ast::Satisfy::All => " all",
+                    ast::Satisfy::Any => " any",
+                });
+                f.end();
+            }
             Allow(ref source) | Deny(ref source) => {
                 use ast::Source::*;
                 f.indent();
@@ -466,7 +568,63 @@ impl Displayable for ast::Item {
                 }
                 f.end();
             }
-            ProxyCacheValid(ref val) => {
+            ProxyRedirect(ref redirect) => {
+                use ast::ProxyRedirect::*;
+                f.indent();
+                f.write(self.directive_name());
+                match *redirect {
+                    Default => f.write(" default"),
+                    Off => f.write(" off"),
+                    Replace { ref redirect, ref replacement } => {
+                        f.write(" ");
+                        redirect.display(f);
+                        f.write(" ");
+                        replacement.display(f);
+                    }
+                }
+                f.end();
+            }
+            ProxyCookieDomain(ref rw) | ProxyCookiePath(ref rw) => {
+                use ast::ProxyCookieRewrite::*;
+                f.indent();
+                f.write(self.directive_name());
+                match *rw {
+                    Off => f.write(" off"),
+                    Replace { ref from, ref to } => {
+                        f.write(" ");
+                        from.display(f);
+                        f.write(" ");
+                        to.display(f);
+                    }
+                }
+                f.end();
+            }
+            ProxyCachePath(ref p) | FastcgiCachePath(ref p) => {
+                f.indent();
+                f.write(self.directive_name());
+                f.write(" ");
+                p.path.display(f);
+                p.display_options(f);
+                f.end();
+            }
+            ProxyStore(ref store) => {
+                f.indent();
+                f.write(self.directive_name());
+                match *store {
+                    ast::ProxyStore::On => f.write(" on"),
+                    ast::ProxyStore::Off => f.write(" off"),
+                    ast::ProxyStore::Path(ref path) => {
+                        f.write(" ");
+                        path.display(f);
+                    }
+                }
+                f.end();
+            }
+            | ProxyCacheValid(ref val)
+            | FastcgiCacheValid(ref val)
+            | UwsgiCacheValid(ref val)
+            | ScgiCacheValid(ref val)
+            => {
                 use ast::ProxyCacheValid::*;
                 f.indent();
                 f.write(self.directive_name());
@@ -494,14 +652,129 @@ impl Displayable for ast::Item {
                 f.indent();
                 f.write(self.directive_name());
                 f.write(" ");
-                timeo.display(f);
+                f.fmt(timeo);
                 if let Some(header_timeo) = header_timeo {
                     f.write(" ");
-                    header_timeo.display(f);
+                    f.fmt(header_timeo);
                 }
                 f.end();
             }
-            ProxyNextUpstream(ref items) => {
+            | ClientMaxBodySize(ref val)
+            | ProxyBufferSize(ref val)
+            | ProxyBusyBuffersSize(ref val)
+            => {
+                f.indent();
+                f.write(self.directive_name());
+                f.write(" ");
+                f.fmt(val);
+                f.end();
+            }
+            | ProxyReadTimeout(ref val)
+            | ProxyConnectTimeout(ref val)
+            | ProxySendTimeout(ref val)
+            | ProxyNextUpstreamTimeout(ref val)
+            | FastcgiReadTimeout(ref val)
+            | FastcgiCacheLockAge(ref val)
+            | FastcgiCacheLockTimeout(ref val)
+            | UwsgiReadTimeout(ref val)
+            | UwsgiConnectTimeout(ref val)
+            | UwsgiSendTimeout(ref val)
+            | ScgiReadTimeout(ref val)
+            | ScgiConnectTimeout(ref val)
+            | ScgiSendTimeout(ref val)
+            | GrpcReadTimeout(ref val)
+            | GrpcConnectTimeout(ref val)
+            | GrpcSendTimeout(ref val)
+            | SslSessionTimeout(ref val)
+            => {
+                f.indent();
+                f.write(self.directive_name());
+                f.write(" ");
+                f.fmt(val);
+                f.end();
+            }
+            | FastcgiParam(ref p)
+            | UwsgiParam(ref p)
+            | ScgiParam(ref p)
+            => {
+                f.indent();
+                f.write(self.directive_name());
+                f.write(" ");
+                p.name.display(f);
+                f.write(" ");
+                p.value.display(f);
+                if p.if_not_empty {
+                    f.write(" if_not_empty");
+                }
+                f.end();
+            }
+            FastcgiSplitPathInfo(ref regex) => {
+                f.indent();
+                f.write(self.directive_name());
+                f.write(" ");
+                f.write(&escape(regex));
+                f.end();
+            }
+            | ProxyBuffers(ref buf)
+            | FastcgiBuffers(ref buf)
+            | UwsgiBuffers(ref buf)
+            | ScgiBuffers(ref buf)
+            => {
+                f.indent();
+                f.write(self.directive_name());
+                f.fmt(&format_args!(" {} {}", buf.number, buf.size));
+                f.end();
+            }
+            | ProxyCacheBypass(ref items)
+            | ProxyNoCache(ref items)
+            | FastcgiCacheBypass(ref items)
+            | FastcgiNoCache(ref items)
+            => {
+                f.indent();
+                f.write(self.directive_name());
+                for item in items {
+                    f.write(" ");
+                    item.display(f);
+                }
+                f.end();
+            }
+            FastcgiCacheMethods(ref items) => {
+                f.indent();
+                f.write(self.directive_name());
+                for item in items {
+                    f.write(" ");
+                    f.write(&escape(item));
+                }
+                f.end();
+            }
+            FastcgiCacheMinUses(num) => {
+                f.indent();
+                f.write(self.directive_name());
+                f.write(" ");
+                f.fmt(&num);
+                f.end();
+            }
+            FastcgiCacheMaxRangeOffset(num) => {
+                f.indent();
+                f.write(self.directive_name());
+                f.write(" ");
+                f.fmt(&num);
+                f.end();
+            }
+            ProxyCacheUseStale(ref items) | FastcgiCacheUseStale(ref items) => {
+                f.indent();
+                f.write(self.directive_name());
+                for item in items {
+                    f.write(" ");
+                    f.write(item.as_str());
+                }
+                f.end();
+            }
+            | ProxyNextUpstream(ref items)
+            | UwsgiNextUpstream(ref items)
+            | ScgiNextUpstream(ref items)
+            | GrpcNextUpstream(ref items)
+            => {
                 use ast::ProxyNextUpstreamFlag::*;
                 f.indent();
                 f.write(self.directive_name());
@@ -581,21 +854,123 @@ impl Displayable for ast::Item {
                 f.write(" ");
                 file.display(f);
                 if let Some(level) = level {
-                    use ast::ErrorLevel::*;
                     f.write(" ");
-                    f.write(match level {
-                        Debug => "debug",
-                        Info => "info",
-                        Notice => "notice",
-                        Warn => "warn",
-                        Error => "error",
-                        Crit => "crit",
-                        Alert => "alert",
-                        Emerg => "emerg",
-                    });
+                    f.write(level.as_str());
+                }
+                f.end();
+            }
+            LimitReqZone(ref zone) => {
+                f.indent();
+                f.write(self.directive_name());
+                f.write(" ");
+                zone.key.display(f);
+                zone.display_options(f);
+                f.end();
+            }
+            LimitReq(ref lim) => {
+                f.indent();
+                f.write(self.directive_name());
+                f.write(" zone=");
+                f.write(&escape(&lim.zone));
+                if let Some(burst) = lim.burst {
+                    f.fmt(&format_args!(" burst={}", burst));
+                }
+                match lim.delay {
+                    Some(ast::LimitReqDelay::NoDelay) => f.write(" nodelay"),
+                    Some(ast::LimitReqDelay::Delay(n)) => {
+                        f.fmt(&format_args!(" delay={}", n));
+                    }
+                    None => {}
+                }
+                f.end();
+            }
+            LimitReqStatus(code) | LimitConnStatus(code) => {
+                f.indent();
+                f.write(self.directive_name());
+                f.write(" ");
+                f.fmt(&code);
+                f.end();
+            }
+            LimitReqLogLevel(level) => {
+                f.indent();
+                f.write(self.directive_name());
+                f.write(" ");
+                f.write(level.as_str());
+                f.end();
+            }
+            LimitConnZone(ref zone) => {
+                f.indent();
+                f.write(self.directive_name());
+                f.write(" ");
+                zone.key.display(f);
+                f.fmt(&format_args!(" zone={}:{}",
+                    escape(&zone.name), zone.size));
+                f.end();
+            }
+            LimitConn { ref zone, number } => {
+                f.indent();
+                f.write(self.directive_name());
+                f.write(" ");
+                f.write(&escape(zone));
+                f.write(" ");
+                f.fmt(&number);
+                f.end();
+            }
+            SslProtocols(ref items) | GrpcSslProtocols(ref items) => {
+                f.indent();
+                f.write(self.directive_name());
+                for item in items {
+                    f.write(" ");
+                    f.write(item.as_str());
+                }
+                f.end();
+            }
+            | SslCiphers(ref val)
+            | SslEcdhCurve(ref val)
+            | GrpcSslCiphers(ref val)
+            => {
+                f.indent();
+                f.write(self.directive_name());
+                f.write(" ");
+                f.write(&escape(val));
+                f.end();
+            }
+            SslSessionCache(ref cache) => {
+                f.indent();
+                f.write(self.directive_name());
+                match *cache {
+                    ast::SslSessionCache::Off => f.write(" off"),
+                    ast::SslSessionCache::None => f.write(" none"),
+                    ast::SslSessionCache::Store { builtin, ref shared } => {
+                        match builtin {
+                            Some(Some(size)) => {
+                                f.fmt(&format_args!(" builtin:{}", size));
+                            }
+                            Some(None) => f.write(" builtin"),
+                            None => {}
+                        }
+                        if let Some((ref name, ref size)) = *shared {
+                            f.fmt(&format_args!(" shared:{}:{}",
+                                escape(name), size));
+                        }
+                    }
                 }
                 f.end();
             }
+            SslVerifyClient(val) => {
+                f.indent();
+                f.write(self.directive_name());
+                f.write(" ");
+                f.write(val.as_str());
+                f.end();
+            }
+            SslVerifyDepth(depth) => {
+                f.indent();
+                f.write(self.directive_name());
+                f.write(" ");
+                f.fmt(&depth);
+                f.end();
+            }
             Index(ref items) => {
                 f.indent();
                 f.write("index");
@@ -605,6 +980,29 @@ impl Displayable for ast::Item {
                 }
                 f.end();
             }
+            Extension(ast::Extension { ref directive, ref directives, .. })
+            => {
+                if directives.is_some() {
+                    f.margin();
+                }
+                f.indent();
+                f.write(directive.name());
+                for arg in directive.arguments() {
+                    f.write(" ");
+                    arg.display(f);
+                }
+                match *directives {
+                    Some(ref directives) => {
+                        f.write(" ");
+                        f.start_block();
+                        for dir in directives {
+                            dir.display(f);
+                        }
+                        f.end_block();
+                    }
+                    None => f.end(),
+                }
+            }
         }
     }
 }
@@ -660,13 +1058,52 @@ impl Displayable for ast::Address {
     }
 }
 
-fn to_string<T: Displayable>(v: &T) -> String {
-    let style = Style::default();
-    let mut formatter = Formatter::new(&style);
+/// Directive as a single line
+///
+/// Blocks are returned without children and `{`. Multi-line directives
+/// (like `map`) are joined into a single line.
+pub(crate) fn single_line(dir: &ast::Directive) -> String {
+    let text = dir.to_string();
+    let mut lines = text.lines().map(|l| l.trim()).filter(|l| !l.is_empty());
+    if dir.item.children().is_some() {
+        let header = lines.next().unwrap_or("");
+        header.trim_end_matches('{').trim_end().to_string()
+    } else {
+        lines.collect::<Vec<_>>().join(" ")
+    }
+}
+
+/// Arguments of the directive as a single line, without the name
+pub(crate) fn arguments(dir: &ast::Directive) -> String {
+    let line = single_line(dir);
+    line[dir.item.directive_name().len()..]
+        .trim_end_matches(';').trim().to_string()
+}
+
+impl<'a> ast::Main<'a> {
+    /// Format config using the given style
+    pub fn format(&self, style: &Style) -> String {
+        to_string_with(self, style)
+    }
+}
+
+impl<'a> ast::Directive<'a> {
+    /// Format directive using the given style
+    pub fn format(&self, style: &Style) -> String {
+        to_string_with(self, style)
+    }
+}
+
+fn to_string_with<T: Displayable>(v: &T, style: &Style) -> String {
+    let mut formatter = Formatter::new(style);
     v.display(&mut formatter);
     formatter.into_string()
 }
 
+fn to_string<T: Displayable>(v: &T) -> String {
+    to_string_with(v, &Style::default())
+}
+
 macro_rules! impl_display {
     ($( $typ: ty, )+) => {
         $(
@@ -680,28 +1117,107 @@ macro_rules! impl_display {
 }
 
 impl_display!(
-    ast::Main,
+    ast::Main<'_>,
     ast::Listen,
     ast::Address,
-    ast::Directive,
-    ast::Item,
-    value::Value,
+    ast::Directive<'_>,
+    ast::Item<'_>,
+    value::Value<'_>,
 );
 
-fn escape(s: &str) -> &str {
-    // TODO(tailhook) escape raw value
-    return s
+fn escape(s: &str) -> Cow<'_, str> {
+    value::quote(s)
+}
+
+impl<'a> ast::LimitReqZone<'a> {
+    /// Prints options in the order they were written, options missing
+    /// from `order` are printed after the others
+    fn display_options(&self, f: &mut Formatter) {
+        use ast::LimitReqZoneKey::*;
+        let fields = [Zone, Rate, Sync];
+        let mut printed = Vec::new();
+        for &key in self.order.iter().chain(&fields) {
+            if printed.contains(&key) {
+                continue;
+            }
+            printed.push(key);
+            match key {
+                Zone => f.fmt(&format_args!(" zone={}:{}",
+                    escape(&self.name), self.size)),
+                Rate => f.fmt(&format_args!(" rate={}", self.rate)),
+                Sync => if self.sync {
+                    f.write(" sync");
+                },
+            }
+        }
+    }
+}
+
+impl<'a> ast::ProxyCachePath<'a> {
+    /// Prints options in the order they were written, options missing
+    /// from `order` are printed after the others
+    fn display_options(&self, f: &mut Formatter) {
+        use ast::CachePathKey::*;
+        let fields = [Levels, UseTempPath, KeysZone, Inactive, MaxSize];
+        let mut printed = Vec::new();
+        let mut done = vec![false; self.options.len()];
+        for key in self.order.iter().chain(&fields) {
+            if printed.contains(&key) {
+                continue;
+            }
+            printed.push(key);
+            match *key {
+                Levels => if let Some(ref levels) = self.levels {
+                    f.write(" levels=");
+                    f.write(&escape(levels));
+                },
+                UseTempPath => if let Some(opt) = self.use_temp_path {
+                    f.write(" use_temp_path=");
+                    f.write(if opt { "on" } else { "off" });
+                },
+                KeysZone => {
+                    let (ref zone, ref size) = self.keys_zone;
+                    f.fmt(&format_args!(" keys_zone={}:{}",
+                        escape(zone), size));
+                }
+                Inactive => if let Some(ref inactive) = self.inactive {
+                    f.fmt(&format_args!(" inactive={}", inactive));
+                },
+                MaxSize => if let Some(ref max_size) = self.max_size {
+                    f.fmt(&format_args!(" max_size={}", max_size));
+                },
+                // options with the same name are printed in turn, so they
+                // aren't marked as printed
+                Other(ref name) => {
+                    printed.pop();
+                    let found = self.options.iter().enumerate()
+                        .find(|&(i, (k, _))| k == name && !done[i]);
+                    if let Some((i, (k, v))) = found {
+                        done[i] = true;
+                        f.fmt(&format_args!(" {}={}", escape(k), escape(v)));
+                    }
+                }
+            }
+        }
+        for (i, (key, val)) in self.options.iter().enumerate() {
+            if !done[i] {
+                f.fmt(&format_args!(" {}={}", escape(key), escape(val)));
+            }
+        }
+    }
 }
 
-impl fmt::Display for ast::LocationPattern {
+impl<'a> fmt::Display for ast::LocationPattern<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         use ast::LocationPattern::*;
         match *self {
-            Prefix(ref p) => f.write_str(escape(p)),
+            Prefix(ref p) => f.write_str(&escape(p)),
             Exact(ref p) => write!(f, "= {}", escape(p)),
             FinalPrefix(ref p) => write!(f, "^~ {}", escape(p)),
-            Regex(ref p) => write!(f, "~ {}", escape(p)),
-            RegexInsensitive(ref p) => write!(f, "~* {}", escape(p)),
+            Regex(ref p, quote) => write!(f, "~ {}", value::quote_as(p, quote)),
+            RegexInsensitive(ref p, quote) => {
+                write!(f, "~* {}", value::quote_as(p, quote))
+            }
             Named(ref name) => {
                 write!(f, "{}", escape(&(String::from("@") + name)))
             }
@@ -737,6 +1253,81 @@ impl ast::GzipProxied {
     }
 }
 
+impl ast::ErrorLevel {
+    fn as_str(&self) -> &str {
+        use ast::ErrorLevel::*;
+        match *self {
+            Debug => "debug",
+            Info => "info",
+            Notice => "notice",
+            Warn => "warn",
+            Error => "error",
+            Crit => "crit",
+            Alert => "alert",
+            Emerg => "emerg",
+        }
+    }
+}
+
+impl ast::CacheUseStale {
+    fn as_str(&self) -> &str {
+        use ast::CacheUseStale::*;
+        match *self {
+            Error => "error",
+            Timeout => "timeout",
+            InvalidHeader => "invalid_header",
+            Updating => "updating",
+            Http500 => "http_500",
+            Http502 => "http_502",
+            Http503 => "http_503",
+            Http504 => "http_504",
+            Http403 => "http_403",
+            Http404 => "http_404",
+            Http429 => "http_429",
+            Off => "off",
+        }
+    }
+}
+
+impl ast::SslProtocol {
+    fn as_str(&self) -> &str {
+        use ast::SslProtocol::*;
+        match *self {
+            SslV2 => "SSLv2",
+            SslV3 => "SSLv3",
+            TlsV1 => "TLSv1",
+            TlsV1_1 => "TLSv1.1",
+            TlsV1_2 => "TLSv1.2",
+            TlsV1_3 => "TLSv1.3",
+        }
+    }
+}
+
+impl ast::SslVerifyClient {
+    fn as_str(&self) -> &str {
+        use ast::SslVerifyClient::*;
+        match *self {
+            On => "on",
+            Off => "off",
+            Optional => "optional",
+            OptionalNoCa => "optional_no_ca",
+        }
+    }
+}
+
+impl<'a> fmt::Display for ast::ServerName<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use ast::ServerName::*;
+        let text = match *self {
+            Exact(ref v) => return f.write_str(&escape(v)),
+            Suffix(ref v) => format!(".{}", v),
+            StarSuffix(ref v) => format!("*.{}", v),
+            StarPrefix(ref v) => format!("{}.*", v),
+            Regex(ref v) => format!("~{}", v),
+        };
+        f.write_str(&escape(&text))
+    }
+}
 
 impl fmt::Display for ast::GzipStatic {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -749,3 +1340,9 @@ impl fmt::Display for ast::GzipProxied {
         self.as_str().fmt(f)
     }
 }
+
+impl fmt::Display for ast::SslProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
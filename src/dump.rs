@@ -0,0 +1,107 @@
+//! Parsing of the `nginx -T` output
+//!
+//! `nginx -T` prints every file it has loaded, each preceded by a
+//! `# configuration file /path:` line. The dump is split into virtual files
+//! and `include` directives are resolved against them instead of the disk.
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use glob::{Pattern, PatternError, MatchOptions};
+
+use ast::{Directive, Main};
+use error::ConfigError;
+use grammar::{Files, parse_directives, expand_includes, set_file};
+
+
+const HEADER: &str = "# configuration file ";
+
+struct Dump<'a> {
+    files: Vec<(PathBuf, &'a str)>,
+}
+
+impl<'a> Dump<'a> {
+    fn split(text: &'a str) -> Dump<'a> {
+        let mut files = Vec::new();
+        let mut current: Option<(PathBuf, usize)> = None;
+        let mut offset = 0;
+        for line in text.split_inclusive('\n') {
+            let header = line.trim_end_matches(['\r', '\n'])
+                .strip_prefix(HEADER)
+                .and_then(|x| x.strip_suffix(':'));
+            if let Some(path) = header {
+                if let Some((path, start)) = current.take() {
+                    files.push((path, &text[start..offset]));
+                }
+                current = Some((PathBuf::from(path), offset + line.len()));
+            }
+            offset += line.len();
+        }
+        if let Some((path, start)) = current {
+            files.push((path, &text[start..]));
+        }
+        Dump { files }
+    }
+}
+
+impl<'a> Files<'a> for Dump<'a> {
+    fn matching(&self, pattern: &str) -> Result<Vec<PathBuf>, PatternError> {
+        let pattern = Pattern::new(pattern)?;
+        let options = MatchOptions {
+            require_literal_separator: true,
+            .. MatchOptions::new()
+        };
+        let mut paths = self.files.iter()
+            .map(|(path, _)| path)
+            .filter(|path| pattern.matches_path_with(path, options))
+            .cloned()
+            .collect::<Vec<_>>();
+        // same order as glob on disk
+        paths.sort();
+        paths.dedup();
+        Ok(paths)
+    }
+    fn same_file(&self, a: &Path, b: &Path) -> Result<bool, io::Error> {
+        Ok(a == b)
+    }
+    fn load(&self, path: &Path, include_chain: &[PathBuf])
+        -> Result<Vec<Directive<'a>>, ConfigError>
+    {
+        let &(_, text) = self.files.iter()
+            .find(|(name, _)| name == path)
+            .expect("only matching files are loaded");
+        let mut dirs = parse_directives(text)
+            .map_err(|error| ConfigError::Parse {
+                path: Some(path.to_path_buf()),
+                include_chain: include_chain.to_vec(),
+                error,
+            })?;
+        set_file(&mut dirs, path);
+        Ok(dirs)
+    }
+}
+
+/// Parses the output of `nginx -T` as a multi-file configuration
+///
+/// The first file in the dump is the main one, `include` directives are
+/// resolved against the other files of the dump the same way
+/// [`parse_main_from_file`] resolves them on disk. Every directive has its
+/// `file` set, positions and spans are relative to that file.
+///
+/// Lines before the first file, like `nginx: configuration file ... test is
+/// successful`, are ignored.
+///
+/// [`parse_main_from_file`]: fn.parse_main_from_file.html
+pub fn parse_nginx_t_dump(text: &str) -> Result<Main<'_>, ConfigError> {
+    let dump = Dump::split(text);
+    let main = match dump.files.first() {
+        Some((path, _)) => path.clone(),
+        None => return Err(ConfigError::EmptyDump),
+    };
+    let base = main.parent().unwrap_or_else(|| Path::new("."));
+    let mut directives = dump.load(&main, &[])?;
+    let mut vars = HashMap::new();
+    let chain = [main.clone()];
+    expand_includes(&mut directives, &dump, base, &chain, &mut vars)?;
+    Ok(Main { directives })
+}
@@ -0,0 +1,160 @@
+//! Structural editing of directive lists
+//!
+//! [`Block`](trait.Block.html) is implemented for `Vec<Directive>`, so the
+//! helpers work on `Main::directives`, on the result of
+//! `item.children_mut()` and inside
+//! [`visit_mutable`](../visitors/fn.visit_mutable.html) to update many
+//! blocks at once. Directives which aren't touched keep their positions.
+//!
+//! All helpers look only at the directives of the block itself, not at
+//! nested blocks.
+use std::mem;
+
+use ast::{Directive, Item, Location, LocationPattern};
+use position::Span;
+
+
+/// Mutation helpers for the list of directives of a block
+pub trait Block<'a> {
+    /// Remove all directives matching the predicate
+    ///
+    /// Returns removed directives in their original order.
+    fn remove_where<F>(&mut self, f: F) -> Vec<Directive<'a>>
+        where F: FnMut(&Directive) -> bool;
+
+    /// Insert directive after the last one matching the predicate
+    ///
+    /// Returns `false` and leaves the block untouched if nothing matches.
+    fn insert_after<F>(&mut self, f: F, dir: Directive<'a>) -> bool
+        where F: FnMut(&Directive) -> bool;
+
+    /// Replace the directive of the same name or append a new one
+    ///
+    /// This is meant for directives which may be specified once per block
+    /// (like `root` or `client_max_body_size`). The first directive with
+    /// the same name is replaced and returned, other ones are removed.
+    fn upsert(&mut self, dir: Directive<'a>) -> Option<Directive<'a>>;
+
+    /// Move directives matching the predicate into a new location
+    ///
+    /// The location is inserted in place of the first matching directive.
+    /// Returns `false` and leaves the block untouched if nothing matches.
+    fn wrap_in_location<F>(&mut self, pattern: LocationPattern<'a>, f: F)
+        -> bool
+        where F: FnMut(&Directive) -> bool;
+
+    /// Move directives matching `f` to the end of the child block matching
+    /// `target`
+    ///
+    /// The first block matching `target` is used and it's never moved
+    /// itself. Returns the number of moved directives, which is zero if
+    /// there is no such block. To move directives between sibling blocks
+    /// use [`remove_where`](#tymethod.remove_where) and `extend`.
+    fn move_to<F, G>(&mut self, f: F, target: G) -> usize
+        where F: FnMut(&Directive) -> bool,
+              G: FnMut(&Directive) -> bool;
+}
+
+impl<'a> Block<'a> for Vec<Directive<'a>> {
+    fn remove_where<F>(&mut self, mut f: F) -> Vec<Directive<'a>>
+        where F: FnMut(&Directive) -> bool
+    {
+        let (removed, kept) = mem::take(self).into_iter()
+            .partition(|d| f(d));
+        *self = kept;
+        removed
+    }
+
+    fn insert_after<F>(&mut self, f: F, dir: Directive<'a>) -> bool
+        where F: FnMut(&Directive) -> bool
+    {
+        match self.iter().rposition(f) {
+            Some(idx) => {
+                self.insert(idx + 1, dir);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn upsert(&mut self, dir: Directive<'a>) -> Option<Directive<'a>> {
+        let name = dir.item.directive_name();
+        let idx = match self.iter()
+            .position(|d| d.item.directive_name() == name)
+        {
+            Some(idx) => idx,
+            None => {
+                self.push(dir);
+                return None;
+            }
+        };
+        let old = mem::replace(&mut self[idx], dir);
+        let mut cur = 0;
+        self.retain(|d| {
+            let keep = cur <= idx || d.item.directive_name() != name;
+            cur += 1;
+            keep
+        });
+        Some(old)
+    }
+
+    fn wrap_in_location<F>(&mut self, pattern: LocationPattern<'a>,
+        mut f: F)
+        -> bool
+        where F: FnMut(&Directive) -> bool
+    {
+        let indices = self.iter().enumerate()
+            .filter(|&(_, d)| f(d))
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>();
+        let idx = match indices.first() {
+            Some(&idx) => idx,
+            None => return false,
+        };
+        let mut wrapped = indices.iter().rev()
+            .map(|&idx| self.remove(idx))
+            .collect::<Vec<_>>();
+        wrapped.reverse();
+        let start = wrapped[0].position;
+        let span = Span {
+            start: wrapped[0].span.start,
+            end: wrapped[wrapped.len() - 1].span.end,
+        };
+        let file = wrapped[0].file.clone();
+        self.insert(idx, Directive {
+            position: start,
+            span,
+            file,
+            item: Item::Location(Location {
+                position: (start, span.end),
+                pattern,
+                directives: wrapped,
+            }),
+        });
+        true
+    }
+
+    fn move_to<F, G>(&mut self, mut f: F, mut target: G) -> usize
+        where F: FnMut(&Directive) -> bool,
+              G: FnMut(&Directive) -> bool
+    {
+        let idx = match self.iter()
+            .position(|d| d.item.children().is_some() && target(d))
+        {
+            Some(idx) => idx,
+            None => return 0,
+        };
+        let matched = self.iter().enumerate()
+            .map(|(i, d)| i != idx && f(d))
+            .collect::<Vec<_>>();
+        // target shifts left by the number of directives moved before it
+        let idx = idx - matched[..idx].iter().filter(|&&m| m).count();
+        let mut matched = matched.into_iter();
+        let moved = self.remove_where(|_| matched.next().unwrap());
+        let count = moved.len();
+        self[idx].item.children_mut()
+            .expect("target is a block")
+            .extend(moved);
+        count
+    }
+}
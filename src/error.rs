@@ -1,7 +1,14 @@
+use std::error::Error as StdError;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
 use combine::easy::{Errors, Error};
+use glob::PatternError;
 
 use tokenizer::Token;
 use position::Pos;
+use validate;
 
 pub type InternalError<'a> = Errors<Token<'a>, Token<'a>, Pos>;
 
@@ -10,10 +17,158 @@ pub type InternalError<'a> = Errors<Token<'a>, Token<'a>, Pos>;
 ///
 /// This structure is opaque for forward compatibility. We are exploring a
 /// way to improve both error message and API.
-#[derive(Fail, Debug)]
-#[fail(display="parse error: {}", _0)]
+#[derive(Debug)]
 pub struct ParseError(Errors<String, String, Pos>);
 
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "parse error: {}", self.0)
+    }
+}
+
+impl StdError for ParseError {}
+
+/// Error loading config along with the files it includes
+///
+/// Every variant has the `path` of the file with the problem (`None` if
+/// it's the text passed to `parse_main`) and the `include_chain`: files
+/// which included it, starting from the main one.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// Error reading file
+    Io {
+        /// File which couldn't be read
+        path: PathBuf,
+        /// Files which included `path`, outermost first
+        include_chain: Vec<PathBuf>,
+        /// Underlying error
+        error: io::Error,
+    },
+    /// Invalid glob pattern in `include`
+    Pattern {
+        /// File containing the `include`
+        path: Option<PathBuf>,
+        /// Files which included `path`, outermost first
+        include_chain: Vec<PathBuf>,
+        /// Position of the `include` directive
+        position: Pos,
+        /// Pattern, relative paths are joined with the directory of the
+        /// including file
+        pattern: String,
+        /// Underlying error
+        error: PatternError,
+    },
+    /// Syntax error in the main config or in an included file
+    Parse {
+        /// File with the error
+        path: Option<PathBuf>,
+        /// Files which included `path`, outermost first
+        include_chain: Vec<PathBuf>,
+        /// Underlying error
+        error: ParseError,
+    },
+    /// File includes itself through other files
+    ///
+    /// A file matching its own `include` pattern (like `include *.conf`)
+    /// is skipped instead.
+    IncludeCycle {
+        /// File included again
+        path: PathBuf,
+        /// Files which included `path`, outermost first, `path` is
+        /// among them
+        include_chain: Vec<PathBuf>,
+    },
+    /// Output of `nginx -T` has no `# configuration file` lines
+    EmptyDump,
+    /// Config is parsed but some directives are not accepted, e.g. they
+    /// aren't available in the target build or aren't allowed in the
+    /// context
+    Invalid {
+        /// Every problem found, in the order of directives
+        errors: Vec<validate::Error>,
+    },
+}
+
+impl ConfigError {
+    /// File with the problem, `None` if it's the text passed to
+    /// `parse_main`
+    pub fn path(&self) -> Option<&Path> {
+        use self::ConfigError::*;
+        match *self {
+            Io { ref path, .. } | IncludeCycle { ref path, .. } => Some(path),
+            Pattern { ref path, .. } | Parse { ref path, .. } => {
+                path.as_deref()
+            }
+            EmptyDump | Invalid { .. } => None,
+        }
+    }
+    /// Files which included the file with the problem, outermost first
+    pub fn include_chain(&self) -> &[PathBuf] {
+        use self::ConfigError::*;
+        match *self {
+            | Io { ref include_chain, .. }
+            | Pattern { ref include_chain, .. }
+            | Parse { ref include_chain, .. }
+            | IncludeCycle { ref include_chain, .. }
+            => include_chain,
+            EmptyDump | Invalid { .. } => &[],
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::ConfigError::*;
+        if let Some(path) = self.path() {
+            write!(f, "{}: ", path.display())?;
+        }
+        match *self {
+            Io { ref error, .. } => write!(f, "{}", error)?,
+            Pattern { position, ref pattern, ref error, .. } => {
+                write!(f, "{}: invalid include pattern {:?}: {}",
+                    position, pattern, error)?;
+            }
+            Parse { ref error, .. } => write!(f, "{}", error)?,
+            IncludeCycle { .. } => f.write_str("include cycle")?,
+            EmptyDump => {
+                f.write_str("no configuration files in nginx -T output")?;
+            }
+            Invalid { ref errors } => {
+                for (i, error) in errors.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str("; ")?;
+                    }
+                    write!(f, "{}", error)?;
+                }
+            }
+        }
+        let chain = self.include_chain();
+        if !chain.is_empty() {
+            f.write_str(" (included from ")?;
+            for (i, path) in chain.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(" -> ")?;
+                }
+                write!(f, "{}", path.display())?;
+            }
+            f.write_str(")")?;
+        }
+        Ok(())
+    }
+}
+
+impl StdError for ConfigError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        use self::ConfigError::*;
+        match *self {
+            Io { ref error, .. } => Some(error),
+            Pattern { ref error, .. } => Some(error),
+            Parse { ref error, .. } => Some(error),
+            IncludeCycle { .. } | EmptyDump | Invalid { .. } => None,
+        }
+    }
+}
+
 #[cfg(not(feature="fuzzy_errors"))]
 impl<'a> From<InternalError<'a>> for ParseError {
     fn from(e: InternalError<'a>) -> ParseError {
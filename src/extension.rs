@@ -0,0 +1,258 @@
+//! Directives implemented outside of this crate
+//!
+//! The parser knows only directives of the standard nginx modules (and some
+//! popular third-party ones). Directives of other modules can be supported
+//! by registering a parser for them in a set of [`Extensions`] and parsing
+//! with that set:
+//!
+//! ```rust
+//! # use clia_nginx_config::ast::{Item, Value};
+//! # use clia_nginx_config::extension::{Extensions, ExtensionDirective};
+//! #[derive(Debug, Clone, PartialEq)]
+//! struct Geo2Path(Value<'static>);
+//!
+//! impl ExtensionDirective for Geo2Path {
+//!     fn name(&self) -> &'static str { "geo2_path" }
+//!     fn arguments(&self) -> Vec<Value<'static>> { vec![self.0.clone()] }
+//!     fn visit_values<'a>(&'a self,
+//!         f: &mut dyn FnMut(&'a Value<'static>))
+//!     {
+//!         f(&self.0)
+//!     }
+//!     fn visit_values_mut(&mut self, f: &mut dyn FnMut(&mut Value<'static>))
+//!     {
+//!         f(&mut self.0)
+//!     }
+//! }
+//!
+//! let mut extensions = Extensions::new();
+//! extensions.register("geo2_path", false, |mut args| {
+//!     match args.len() {
+//!         1 => Ok(Box::new(Geo2Path(args.remove(0)))),
+//!         _ => Err("geo2_path takes exactly one argument".into()),
+//!     }
+//! });
+//! let dirs = extensions
+//!     .parse_directives("geo2_path /var/lib/GeoIP2.mmdb;").unwrap();
+//! match dirs[0].item {
+//!     Item::Extension(ref ext) => {
+//!         assert!(ext.directive.downcast_ref::<Geo2Path>().is_some());
+//!     }
+//!     _ => unreachable!(),
+//! }
+//! ```
+//!
+//! Registered parsers are used only by the parsing methods of the set
+//! (including files loaded by `include`), the plain parsing functions know
+//! only built-in directives. Built-in directives take precedence over the
+//! registered ones.
+//!
+//! [`Extensions`]: struct.Extensions.html
+use std::any::Any;
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+use ast::{Main, Directive};
+use error::{ConfigError, ParseError};
+use grammar;
+use registry::Context;
+use value::Value;
+
+
+type ParseFn = dyn Fn(Vec<Value<'static>>)
+    -> Result<Box<dyn ExtensionDirective>, String> + Send + Sync;
+
+#[derive(Clone)]
+pub(crate) struct Registered {
+    name: &'static str,
+    pub(crate) block: bool,
+    pub(crate) parse: Arc<ParseFn>,
+}
+
+/// A set of parsers for directives implemented outside of this crate
+///
+/// See the [module documentation](index.html) for an example.
+#[derive(Clone, Default)]
+pub struct Extensions {
+    registered: Vec<Registered>,
+}
+
+
+/// Arguments of a directive implemented outside of this crate
+///
+/// Values are owned (`'static`) because the directive outlives the text
+/// it's parsed from. Directives of the block, if the directive is
+/// registered as a block, are stored in [`ast::Extension`] rather than
+/// here.
+///
+/// Only [`name`](#tymethod.name) and [`arguments`](#tymethod.arguments)
+/// are required. Implement [`visit_values`](#method.visit_values) and
+/// [`visit_values_mut`](#method.visit_values_mut) to make values of the
+/// directive visible to visitors and variable substitution.
+///
+/// [`ast::Extension`]: ../ast/struct.Extension.html
+pub trait ExtensionDirective: ExtensionBase + fmt::Debug + Send + Sync {
+    /// Name of the directive, same as it was registered with
+    fn name(&self) -> &'static str;
+    /// Arguments to print after the name
+    ///
+    /// These are displayed by the formatter, so values created in code are
+    /// quoted according to the [`Style`](../struct.Style.html).
+    fn arguments(&self) -> Vec<Value<'static>>;
+    /// Executes function on all the values of the directive
+    fn visit_values<'a>(&'a self, f: &mut dyn FnMut(&'a Value<'static>)) {
+        let _ = f;
+    }
+    /// Executes function on all the values of the directive (mutably)
+    fn visit_values_mut(&mut self, f: &mut dyn FnMut(&mut Value<'static>)) {
+        let _ = f;
+    }
+    /// Checks the directive, returns a message for each problem found
+    ///
+    /// Called by [`validate::validate`](../validate/fn.validate.html).
+    fn validate(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// Object-safe `Clone`, `PartialEq` and downcasting for
+/// [`ExtensionDirective`]
+///
+/// Implemented automatically for all types that are `Clone + PartialEq`.
+///
+/// [`ExtensionDirective`]: trait.ExtensionDirective.html
+pub trait ExtensionBase {
+    /// Clones the directive into a new box
+    fn clone_box(&self) -> Box<dyn ExtensionDirective>;
+    /// Whether `other` is of the same type and equal to this directive
+    fn eq_box(&self, other: &dyn ExtensionDirective) -> bool;
+    /// Upcast to `Any`
+    fn as_any(&self) -> &dyn Any;
+    /// Upcast to `Any` (mutably)
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T> ExtensionBase for T
+    where T: ExtensionDirective + Clone + PartialEq + 'static
+{
+    fn clone_box(&self) -> Box<dyn ExtensionDirective> {
+        Box::new(self.clone())
+    }
+    fn eq_box(&self, other: &dyn ExtensionDirective) -> bool {
+        other.as_any().downcast_ref::<T>() == Some(self)
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl dyn ExtensionDirective {
+    /// Returns the directive if it is of type `T`
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.as_any().downcast_ref()
+    }
+    /// Returns the directive if it is of type `T` (mutably)
+    pub fn downcast_mut<T: Any>(&mut self) -> Option<&mut T> {
+        self.as_any_mut().downcast_mut()
+    }
+}
+
+impl Clone for Box<dyn ExtensionDirective> {
+    fn clone(&self) -> Box<dyn ExtensionDirective> {
+        self.clone_box()
+    }
+}
+
+impl PartialEq for dyn ExtensionDirective {
+    fn eq(&self, other: &dyn ExtensionDirective) -> bool {
+        self.eq_box(other)
+    }
+}
+
+impl Eq for dyn ExtensionDirective {}
+
+impl Extensions {
+    /// Creates an empty set, which parses only built-in directives
+    pub fn new() -> Extensions {
+        Extensions::default()
+    }
+
+    /// Registers a parser for the directive `name`
+    ///
+    /// The `parse` function gets the arguments of the directive. If
+    /// `block` is true the directive must be followed by a `{ block }` of
+    /// directives, otherwise by a semicolon. An error returned by `parse`
+    /// becomes a parse error of the config.
+    ///
+    /// Registering the same name again replaces the parser. Names of
+    /// built-in directives can't be overridden.
+    pub fn register<F>(&mut self, name: &'static str, block: bool, parse: F)
+        -> &mut Self
+        where F: Fn(Vec<Value<'static>>)
+                    -> Result<Box<dyn ExtensionDirective>, String>
+                 + Send + Sync + 'static,
+    {
+        self.registered.retain(|r| r.name != name);
+        self.registered.push(Registered {
+            name, block, parse: Arc::new(parse),
+        });
+        self
+    }
+
+    /// Same as [`parse_main`](../fn.parse_main.html) but also parses
+    /// the registered directives
+    pub fn parse_main<'a>(&self, s: &'a str)
+        -> Result<Main<'a>, ConfigError>
+    {
+        grammar::parse_main_with(s, self)
+    }
+
+    /// Same as [`parse_directives`](../fn.parse_directives.html) but also
+    /// parses the registered directives
+    pub fn parse_directives<'a>(&self, s: &'a str)
+        -> Result<Vec<Directive<'a>>, ParseError>
+    {
+        grammar::parse_directives_with(s, self)
+    }
+
+    /// Same as [`parse_in_context`](../fn.parse_in_context.html) but also
+    /// parses the registered directives
+    pub fn parse_in_context<'a>(&self, context: Context, s: &'a str)
+        -> Result<Vec<Directive<'a>>, ConfigError>
+    {
+        grammar::parse_in_context_with(context, s, self)
+    }
+
+    /// Same as [`parse_main_from_file`](../fn.parse_main_from_file.html)
+    /// but also parses the registered directives
+    pub fn parse_main_from_file<P: AsRef<Path>>(&self, path: P)
+        -> Result<Main<'static>, ConfigError>
+    {
+        grammar::parse_main_from_file_with(path.as_ref(), self)
+    }
+
+    /// Same as
+    /// [`parse_directives_from_file`](../fn.parse_directives_from_file.html)
+    /// but also parses the registered directives
+    pub fn parse_directives_from_file<P: AsRef<Path>>(&self, path: P)
+        -> Result<Vec<Directive<'static>>, ConfigError>
+    {
+        grammar::parse_directives_from_file_with(path.as_ref(), self)
+    }
+
+    pub(crate) fn lookup(&self, name: &str) -> Option<Registered> {
+        self.registered.iter().find(|r| r.name == name).cloned()
+    }
+}
+
+impl fmt::Debug for Extensions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list()
+            .entries(self.registered.iter().map(|r| r.name))
+            .finish()
+    }
+}
@@ -0,0 +1,66 @@
+use combine::{many1, Parser};
+use combine::{choice};
+
+use ast::Item;
+use grammar::{value, bool, duration, raw};
+use helpers::{semi, ident, string};
+use proxy::{param, cache_valid, cache_use_stale, cache_path, buffers};
+use tokenizer::TokenStream;
+
+
+pub fn directives<'a>()
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
+{
+    choice((
+        ident("fastcgi_pass").with(value()).skip(semi())
+            .map(Item::FastcgiPass),
+        param("fastcgi_param").map(Item::FastcgiParam),
+        ident("fastcgi_index").with(value()).skip(semi())
+            .map(Item::FastcgiIndex),
+        ident("fastcgi_split_path_info")
+            .with(raw())
+            .skip(semi())
+            .map(Item::FastcgiSplitPathInfo),
+        ident("fastcgi_read_timeout").with(duration()).skip(semi())
+            .map(Item::FastcgiReadTimeout),
+        ident("fastcgi_buffers").with(buffers()).skip(semi())
+            .map(Item::FastcgiBuffers),
+        ident("fastcgi_intercept_errors").with(bool()).skip(semi())
+            .map(Item::FastcgiInterceptErrors),
+        ident("fastcgi_cache").with(value()).skip(semi())
+            .map(Item::FastcgiCache),
+        ident("fastcgi_cache_key").with(value()).skip(semi())
+            .map(Item::FastcgiCacheKey),
+        ident("fastcgi_cache_valid").with(cache_valid()).skip(semi())
+            .map(Item::FastcgiCacheValid),
+        ident("fastcgi_cache_bypass").with(many1(value())).skip(semi())
+            .map(Item::FastcgiCacheBypass),
+        ident("fastcgi_cache_lock").with(bool()).skip(semi())
+            .map(Item::FastcgiCacheLock),
+        ident("fastcgi_cache_methods")
+            .with(many1(raw()))
+            .skip(semi())
+            .map(Item::FastcgiCacheMethods),
+        ident("fastcgi_cache_min_uses")
+            .with(string().and_then(|t| t.value.parse::<u32>()))
+            .skip(semi())
+            .map(Item::FastcgiCacheMinUses),
+        ident("fastcgi_cache_use_stale").with(cache_use_stale()).skip(semi())
+            .map(Item::FastcgiCacheUseStale),
+        cache_path("fastcgi_cache_path").map(Item::FastcgiCachePath),
+        ident("fastcgi_no_cache").with(many1(value())).skip(semi())
+            .map(Item::FastcgiNoCache),
+        ident("fastcgi_cache_lock_age").with(duration()).skip(semi())
+            .map(Item::FastcgiCacheLockAge),
+        ident("fastcgi_cache_lock_timeout").with(duration()).skip(semi())
+            .map(Item::FastcgiCacheLockTimeout),
+        ident("fastcgi_cache_revalidate").with(bool()).skip(semi())
+            .map(Item::FastcgiCacheRevalidate),
+        ident("fastcgi_cache_background_update").with(bool()).skip(semi())
+            .map(Item::FastcgiCacheBackgroundUpdate),
+        ident("fastcgi_cache_max_range_offset")
+            .with(string().and_then(|t| t.value.parse::<u64>()))
+            .skip(semi())
+            .map(Item::FastcgiCacheMaxRangeOffset),
+    ))
+}
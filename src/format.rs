@@ -6,32 +6,132 @@ use std::default::Default;
 pub(crate) struct Formatter<'a> {
     buf: String,
     style: &'a Style,
-    indent: u32,
+    level: u32,
+    /// Simple directive being written, if alignment or wrapping is enabled
+    line: Option<Line>,
+    /// Simple directives written so far, in the order of the buffer
+    lines: Vec<Line>,
+}
+
+/// Simple directive (one not followed by a block) in the output
+#[derive(Debug, PartialEq)]
+struct Line {
+    /// Byte range of the line in the buffer, including the newline
+    start: usize,
+    end: usize,
+    level: u32,
+    /// Name and arguments as written, quoted
+    args: Vec<String>,
+    /// Next character starts a new argument
+    split: bool,
 }
 
 /// A configuration of formatting style
 ///
-/// Currently we only have indentation configured, other things might be
-/// added later.
+/// Use `Main::format` or `Directive::format` to display config with
+/// non-default style.
 #[derive(Debug, PartialEq, Clone)]
 pub struct Style {
     indent: u32,
+    tabs: bool,
+    block_margin: u32,
+    align_arguments: bool,
+    max_width: Option<usize>,
+    pub(crate) quotes: Quotes,
+    pub(crate) preserve_quotes: bool,
+    pub(crate) sort_map_entries: bool,
+}
+
+/// Quoting of values which may contain variables
+///
+/// Unless [`Style::preserve_quotes`](struct.Style.html#method.preserve_quotes)
+/// is disabled, this applies only to values created in code, values parsed
+/// from the config keep their original quotes.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Quotes {
+    /// Quote only values which need it, using double quotes (default)
+    Minimal,
+    /// Always use double quotes
+    Double,
+    /// Always use single quotes
+    Single,
 }
 
 impl Default for Style {
     fn default() -> Style {
         Style {
             indent: 4,
+            tabs: false,
+            block_margin: 1,
+            align_arguments: false,
+            max_width: None,
+            quotes: Quotes::Minimal,
+            preserve_quotes: true,
+            sort_map_entries: false,
         }
     }
 }
 
 impl Style {
     /// Change the number of spaces used for indentation
+    ///
+    /// When tabs are enabled, this is the width of a tab used to compute
+    /// line width.
     pub fn indent(&mut self, indent: u32) -> &mut Self {
         self.indent = indent;
         self
     }
+    /// Indent with tabs instead of spaces
+    pub fn tabs(&mut self, tabs: bool) -> &mut Self {
+        self.tabs = tabs;
+        self
+    }
+    /// Change the number of blank lines put before a block (default `1`)
+    ///
+    /// No blank lines are put before the first directive of the file or
+    /// of the block.
+    pub fn block_margin(&mut self, lines: u32) -> &mut Self {
+        self.block_margin = lines;
+        self
+    }
+    /// Align arguments of consecutive directives with the same name
+    ///
+    /// For example, values of consecutive `proxy_set_header` directives
+    /// are put into the same column.
+    pub fn align_arguments(&mut self, align: bool) -> &mut Self {
+        self.align_arguments = align;
+        self
+    }
+    /// Wrap arguments of directives which are longer than `width`
+    ///
+    /// Continuation lines are indented one level deeper. Directive with a
+    /// single argument is never wrapped.
+    pub fn max_width(&mut self, width: Option<usize>) -> &mut Self {
+        self.max_width = width;
+        self
+    }
+    /// Change quoting of values
+    pub fn quotes(&mut self, quotes: Quotes) -> &mut Self {
+        self.quotes = quotes;
+        self
+    }
+    /// Keep quotes of the values as written in the config (default `true`)
+    ///
+    /// Disable to requote all values according to
+    /// [`quotes`](#method.quotes).
+    pub fn preserve_quotes(&mut self, preserve: bool) -> &mut Self {
+        self.preserve_quotes = preserve;
+        self
+    }
+    /// Sort entries of `map` blocks
+    ///
+    /// Only exact and wildcard entries are sorted. Regular expressions are
+    /// matched in order, so they are kept in the original order after
+    /// the other entries.
+    pub fn sort_map_entries(&mut self, sort: bool) -> &mut Self {
+        self.sort_map_entries = sort;
+        self
+    }
 }
 
 pub(crate) trait Displayable {
@@ -43,32 +143,60 @@ impl<'a> Formatter<'a> {
         Formatter {
             buf: String::with_capacity(1024),
             style,
-            indent: 0,
+            level: 0,
+            line: None,
+            lines: Vec::new(),
         }
     }
 
+    pub fn style(&self) -> &Style {
+        self.style
+    }
+
+    fn tracks_lines(&self) -> bool {
+        self.style.align_arguments || self.style.max_width.is_some()
+    }
+
     pub fn indent(&mut self) {
-        for _ in 0..self.indent {
-            self.buf.push(' ');
+        self.line = if self.tracks_lines() {
+            Some(Line {
+                start: self.buf.len(),
+                end: self.buf.len(),
+                level: self.level,
+                args: Vec::new(),
+                split: true,
+            })
+        } else {
+            None
+        };
+        for _ in 0..self.level {
+            push_indent(&mut self.buf, self.style);
         }
     }
 
     pub fn end(&mut self) {
         self.buf.push(';');
         self.buf.push('\n');
+        if let Some(mut line) = self.line.take() {
+            if !line.args.is_empty() {
+                line.end = self.buf.len();
+                self.lines.push(line);
+            }
+        }
     }
     pub fn endline(&mut self) {
+        self.line = None;
         self.buf.push('\n');
     }
 
     pub fn start_block(&mut self) {
         self.buf.push('{');
         self.endline();
-        self.indent += self.style.indent;
+        self.level += 1;
     }
 
     pub fn end_block(&mut self) {
-        self.indent = self.indent.checked_sub(self.style.indent)
+        self.level = self.level.checked_sub(1)
             .expect("negative indent");
         self.indent();
         self.buf.push('}');
@@ -77,19 +205,176 @@ impl<'a> Formatter<'a> {
 
     pub fn margin(&mut self) {
         if !self.buf.is_empty() && !self.buf.ends_with("{\n") {
-            self.buf.push('\n');
+            for _ in 0..self.style.block_margin {
+                self.buf.push('\n');
+            }
         }
     }
 
+    /// Writes text of the directive
+    ///
+    /// Quotes in `s` must be balanced, i.e. values are written as a
+    /// whole. Unquoted spaces separate arguments for alignment and
+    /// wrapping.
     pub fn write(&mut self, s: &str) {
         self.buf.push_str(s);
+        if let Some(ref mut line) = self.line {
+            line.push_text(s);
+        }
     }
     pub fn fmt<D: fmt::Display>(&mut self, s: &D) {
         use std::fmt::Write;
-        write!(&mut self.buf, "{}", s).expect("write never fails");
+        if self.line.is_some() {
+            self.write(&s.to_string());
+        } else {
+            write!(&mut self.buf, "{}", s).expect("write never fails");
+        }
     }
 
     pub fn into_string(self) -> String {
-        self.buf
+        if self.lines.is_empty() {
+            return self.buf;
+        }
+        let lines = &self.lines;
+        let mut buf = String::with_capacity(self.buf.len());
+        let mut pos = 0;
+        let mut start = 0;
+        while start < lines.len() {
+            let first = &lines[start];
+            let mut end = start + 1;
+            if self.style.align_arguments {
+                while end < lines.len() &&
+                    lines[end].start == lines[end - 1].end &&
+                    lines[end].level == first.level &&
+                    lines[end].args[0] == first.args[0]
+                {
+                    end += 1;
+                }
+            }
+            let group = &lines[start..end];
+            let widths = if group.len() > 1 {
+                column_widths(group)
+            } else {
+                Vec::new()
+            };
+            buf.push_str(&self.buf[pos..first.start]);
+            for line in group {
+                line.write(&mut buf, &widths, self.style);
+            }
+            pos = group[group.len() - 1].end;
+            start = end;
+        }
+        buf.push_str(&self.buf[pos..]);
+        buf
+    }
+}
+
+impl Line {
+    fn push_text(&mut self, text: &str) {
+        let mut quote = None;
+        let mut escaped = false;
+        for c in text.chars() {
+            match (quote, c) {
+                _ if escaped => escaped = false,
+                (_, '\\') => escaped = true,
+                (Some(q), c) if c == q => quote = None,
+                (None, '"') | (None, '\'') => quote = Some(c),
+                (None, ' ') => {
+                    self.split = true;
+                    continue;
+                }
+                _ => {}
+            }
+            if self.split {
+                self.args.push(String::new());
+                self.split = false;
+            }
+            self.args.last_mut().expect("argument started").push(c);
+        }
+    }
+
+    /// Writes the line padding arguments to `widths`, moving arguments
+    /// which don't fit `max_width` to the next lines
+    fn write(&self, buf: &mut String, widths: &[usize], style: &Style) {
+        let mut indent = String::new();
+        for _ in 0..self.level {
+            push_indent(&mut indent, style);
+        }
+        let mut line = indent.clone();
+        for (col, arg) in self.args.iter().enumerate() {
+            line.push_str(arg);
+            if col + 1 < self.args.len() {
+                let pad = widths.get(col)
+                    .map_or(1, |w| w - arg.chars().count() + 1);
+                line.extend((0..pad).map(|_| ' '));
+            }
+        }
+        line.push(';');
+        match style.max_width {
+            Some(width) if self.args.len() > 2 &&
+                line_width(&line, style) > width
+            => wrap(buf, &indent, &self.args, width, style),
+            _ => buf.push_str(&line),
+        }
+        buf.push('\n');
+    }
+}
+
+fn push_indent(buf: &mut String, style: &Style) {
+    if style.tabs {
+        buf.push('\t');
+    } else {
+        for _ in 0..style.indent {
+            buf.push(' ');
+        }
+    }
+}
+
+/// Widths of columns of consecutive directives with the same name
+fn column_widths(lines: &[Line]) -> Vec<usize> {
+    let mut widths: Vec<usize> = Vec::new();
+    for line in lines {
+        // the last argument isn't padded
+        for (col, arg) in line.args[..line.args.len()-1].iter().enumerate() {
+            let width = arg.chars().count();
+            if col < widths.len() {
+                widths[col] = widths[col].max(width);
+            } else {
+                widths.push(width);
+            }
+        }
+    }
+    widths
+}
+
+fn line_width(line: &str, style: &Style) -> usize {
+    line.chars()
+        .map(|c| if c == '\t' { style.indent as usize } else { 1 })
+        .sum()
+}
+
+/// Writes the directive, moving arguments which don't fit to the next lines
+fn wrap(buf: &mut String, indent: &str, args: &[String], width: usize,
+    style: &Style)
+{
+    let mut cur = String::from(indent);
+    cur.push_str(&args[0]);
+    cur.push(' ');
+    cur.push_str(&args[1]);
+    for (idx, arg) in args.iter().enumerate().skip(2) {
+        // the last argument is followed by a semicolon
+        let tail = if idx + 1 == args.len() { 1 } else { 0 };
+        if line_width(&cur, style) + 1 + arg.chars().count() + tail > width {
+            buf.push_str(&cur);
+            buf.push('\n');
+            cur.clear();
+            cur.push_str(indent);
+            push_indent(&mut cur, style);
+        } else {
+            cur.push(' ');
+        }
+        cur.push_str(arg);
     }
+    buf.push_str(&cur);
+    buf.push(';');
 }
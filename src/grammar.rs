@@ -1,24 +1,35 @@
+use std::borrow::Cow;
+
 use combine::{eof, many, many1, Parser};
-use combine::{choice, position};
+use combine::{choice, position, satisfy_map, parser};
 use combine::combinator::{opaque, no_partial, FnOpaque};
-use combine::error::StreamError;
+use combine::error::{StreamError, Consumed};
 use combine::easy::Error;
 
-use ast::{self, Main, Directive, Item};
-use error::ParseError;
-use helpers::{semi, ident, text, string, kind};
-use position::Pos;
+use ast::{self, Main, Directive, Item, Size, Duration};
+use error::{ParseError, ConfigError};
+use extension::Extensions;
+use helpers::{semi, ident, text, string, kind, spanned, end_position};
+use position::{Pos, Span};
+use registry::Context;
 use tokenizer::{TokenStream, Token, Kind};
-use value::Value;
+use validate::check_context;
+use value::{Value, QuoteStyle, unquote};
 
 use access;
 use core;
+use fastcgi;
+use grpc;
 use gzip;
 use headers;
+use limit;
 use proxy;
 use rewrite;
+use scgi;
 use log;
 use real_ip;
+use ssl;
+use uwsgi;
 
 
 pub enum Code {
@@ -33,13 +44,22 @@ pub fn bool<'a>() -> impl Parser<Output=bool, Input=TokenStream<'a>> {
     ))
 }
 
-pub fn value<'a>() -> impl Parser<Output=Value, Input=TokenStream<'a>> {
-    (position(), string())
-    .and_then(|(p, v)| Value::parse(p, v))
+pub fn value<'a>() -> impl Parser<Output=Value<'a>, Input=TokenStream<'a>> {
+    spanned(string())
+    .and_then(|(span, v)| Value::parse(span, v))
+}
+
+pub fn size<'a>() -> impl Parser<Output=Size, Input=TokenStream<'a>> {
+    string().and_then(|t| t.value.parse::<Size>())
+}
+
+pub fn duration<'a>() -> impl Parser<Output=Duration, Input=TokenStream<'a>>
+{
+    string().and_then(|t| t.value.parse::<Duration>())
 }
 
 pub fn worker_processes<'a>()
-    -> impl Parser<Output=Item, Input=TokenStream<'a>>
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
 {
     use ast::WorkerProcesses;
     ident("worker_processes")
@@ -51,21 +71,23 @@ pub fn worker_processes<'a>()
     .map(Item::WorkerProcesses)
 }
 
-pub fn server_name<'a>() -> impl Parser<Output=Item, Input=TokenStream<'a>> {
+pub fn server_name<'a>()
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
+{
     use ast::ServerName::*;
     ident("server_name")
     .with(many1(
-        string().map(|t| {
-            if t.value.starts_with("~") {
-                Regex(t.value[1..].to_string())
-            } else if t.value.starts_with("*.") {
-                StarSuffix(t.value[2..].to_string())
-            } else if t.value.ends_with(".*") {
-                StarPrefix(t.value[..t.value.len()-2].to_string())
-            } else if t.value.starts_with(".") {
-                Suffix(t.value[1..].to_string())
+        raw().map(|v| {
+            if v.starts_with('~') {
+                Regex(strip(v, 1, 0))
+            } else if v.starts_with("*.") {
+                StarSuffix(strip(v, 2, 0))
+            } else if v.ends_with(".*") {
+                StarPrefix(strip(v, 0, 2))
+            } else if v.starts_with('.') {
+                Suffix(strip(v, 1, 0))
             } else {
-                Exact(t.value.to_string())
+                Exact(v)
             }
         })
     ))
@@ -74,15 +96,15 @@ pub fn server_name<'a>() -> impl Parser<Output=Item, Input=TokenStream<'a>> {
 }
 
 
-pub fn map<'a>() -> impl Parser<Output=Item, Input=TokenStream<'a>> {
+pub fn map<'a>() -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>> {
     use tokenizer::Kind::{BlockStart, BlockEnd};
     use helpers::kind;
-    enum Tok {
+    enum Tok<'a> {
         Hostnames,
         Volatile,
-        Pattern(String, Value),
-        Default(Value),
-        Include(String),
+        Pattern((Cow<'a, str>, QuoteStyle), Value<'a>),
+        Default(Value<'a>),
+        Include(Cow<'a, str>),
     }
     ident("map")
     .with(value())
@@ -93,7 +115,7 @@ pub fn map<'a>() -> impl Parser<Output=Item, Input=TokenStream<'a>> {
             t.value[2..].chars()
             .all(|x| matches!(x, 'a'...'z' | 'A'...'Z' | '0'...'9' | '_'))
         {
-            Ok(t.value[1..].to_string())
+            Ok(Cow::Borrowed(&t.value[1..]))
         } else {
             Err(Error::unexpected_message("invalid variable"))
         }
@@ -104,7 +126,7 @@ pub fn map<'a>() -> impl Parser<Output=Item, Input=TokenStream<'a>> {
         ident("volatile").map(|_| Tok::Volatile),
         ident("default").with(value()).map(|v| Tok::Default(v)),
         ident("include").with(raw()).map(|v| Tok::Include(v)),
-        raw().and(value()).map(|(s, v)| Tok::Pattern(s, v)),
+        raw_quoted().and(value()).map(|(s, v)| Tok::Pattern(s, v)),
     )).skip(semi())))
     .skip(kind(BlockEnd))
     .map(|((expression, variable), vec): ((_, _), Vec<Tok>)| {
@@ -122,27 +144,27 @@ pub fn map<'a>() -> impl Parser<Output=Item, Input=TokenStream<'a>> {
                 Tok::Volatile => res.volatile = true,
                 Tok::Default(v) => res.default = Some(v),
                 Tok::Include(path) => res.includes.push(path),
-                Tok::Pattern(x, targ) => {
+                Tok::Pattern((x, quote), targ) => {
                     use ast::MapPattern::*;
-                    let mut s = &x[..];
-                    if s.starts_with('~') {
-                        res.patterns.push((Regex(s[1..].to_string()), targ));
-                        continue;
-                    } else if s.starts_with('\\') {
-                        s = &s[1..];
-                    }
-                    let pat = if res.hostnames {
-                        if s.starts_with("*.") {
-                            StarSuffix(s[2..].to_string())
-                        } else if s.ends_with(".*") {
-                            StarPrefix(s[..s.len()-2].to_string())
-                        } else if s.starts_with(".") {
-                            Suffix(s[1..].to_string())
+                    let pat = if x.starts_with('~') {
+                        Regex(strip(x, 1, 0), quote)
+                    } else {
+                        let x = if x.starts_with('\\') {
+                            strip(x, 1, 0)
                         } else {
-                            Exact(s.to_string())
+                            x
+                        };
+                        if !res.hostnames {
+                            Exact(x)
+                        } else if x.starts_with("*.") {
+                            StarSuffix(strip(x, 2, 0))
+                        } else if x.ends_with(".*") {
+                            StarPrefix(strip(x, 0, 2))
+                        } else if x.starts_with('.') {
+                            Suffix(strip(x, 1, 0))
+                        } else {
+                            Exact(x)
                         }
-                    } else {
-                        Exact(s.to_string())
                     };
                     res.patterns.push((pat, targ));
                 }
@@ -153,7 +175,7 @@ pub fn map<'a>() -> impl Parser<Output=Item, Input=TokenStream<'a>> {
 }
 
 pub fn block<'a>()
-    -> FnOpaque<TokenStream<'a>, ((Pos, Pos), Vec<Directive>)>
+    -> FnOpaque<TokenStream<'a>, ((Pos, Pos), Vec<Directive<'a>>)>
 {
     use tokenizer::Kind::{BlockStart, BlockEnd};
     use helpers::kind;
@@ -163,29 +185,44 @@ pub fn block<'a>()
                 kind(BlockStart)
                     .with(many(directive()))
                     .skip(kind(BlockEnd)),
-                position(),
+                end_position(),
         ))
         .map(|(s, dirs, e)| ((s, e), dirs)))
     })
 }
 
+/// Cuts `start` bytes from the start and `end` bytes from the end of the
+/// string without copying borrowed string
+fn strip(s: Cow<str>, start: usize, end: usize) -> Cow<str> {
+    match s {
+        Cow::Borrowed(s) => Cow::Borrowed(&s[start..s.len()-end]),
+        Cow::Owned(s) => Cow::Owned(s[start..s.len()-end].to_string()),
+    }
+}
+
 // A string that forbids variables
-pub fn raw<'a>() -> impl Parser<Output=String, Input=TokenStream<'a>> {
-    // TODO(tailhook) unquote single and double quotes
-    // error on variables?
-    string().and_then(|t| Ok::<_, Error<_, _>>(t.value.to_string()))
+/// String without variables, unquoted and unescaped
+pub fn raw<'a>() -> impl Parser<Output=Cow<'a, str>, Input=TokenStream<'a>> {
+    string().map(|t| unquote(t.value))
+}
+
+/// Same as `raw` but also returns quotes the string is written with
+pub fn raw_quoted<'a>()
+    -> impl Parser<Output=(Cow<'a, str>, QuoteStyle), Input=TokenStream<'a>>
+{
+    string().map(|t| (unquote(t.value), QuoteStyle::of(t.value)))
 }
 
-pub fn location<'a>() -> impl Parser<Output=Item, Input=TokenStream<'a>> {
+pub fn location<'a>() -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>> {
     use ast::LocationPattern::*;
     ident("location").with(choice((
         text("=").with(raw().map(Exact)),
         text("^~").with(raw().map(FinalPrefix)),
-        text("~").with(raw().map(Regex)),
-        text("~*").with(raw().map(RegexInsensitive)),
+        text("~").with(raw_quoted().map(|(v, q)| Regex(v, q))),
+        text("~*").with(raw_quoted().map(|(v, q)| RegexInsensitive(v, q))),
         raw()
-            .map(|v| if v.starts_with('*') {
-                Named(v)
+            .map(|v| if v.starts_with('@') {
+                Named(strip(v, 1, 0))
             } else {
                 Prefix(v)
             }),
@@ -216,7 +253,7 @@ impl Code {
 }
 
 
-pub fn try_files<'a>() -> impl Parser<Output=Item, Input=TokenStream<'a>> {
+pub fn try_files<'a>() -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>> {
     use ast::TryFilesLastOption::*;
     use ast::Item::TryFiles;
     use value::Item::*;
@@ -231,7 +268,7 @@ pub fn try_files<'a>() -> impl Parser<Output=Item, Input=TokenStream<'a>> {
                 Code(self::Code::parse(&x[1..])?.as_code())
             }
             [Literal(x)] if x.starts_with("@") => {
-                NamedLocation(x[1..].to_string())
+                NamedLocation(strip(x.clone(), 1, 0))
             }
             _ => Uri(last.clone()),
         };
@@ -243,7 +280,7 @@ pub fn try_files<'a>() -> impl Parser<Output=Item, Input=TokenStream<'a>> {
 }
 
 
-pub fn openresty<'a>() -> impl Parser<Output=Item, Input=TokenStream<'a>> {
+pub fn openresty<'a>() -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>> {
     use ast::Item::*;
     choice((
         ident("rewrite_by_lua_file").with(value()).skip(semi())
@@ -271,15 +308,62 @@ pub fn openresty<'a>() -> impl Parser<Output=Item, Input=TokenStream<'a>> {
     ))
 }
 
-pub fn directive<'a>() -> impl Parser<Output=Directive, Input=TokenStream<'a>>
+/// Directive parsed by a parser registered in the `Extensions` of the
+/// token stream
+pub fn extension<'a>() -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
+{
+    parser(|input: &mut TokenStream<'a>| {
+        Ok((input.extensions().clone(), Consumed::Empty(())))
+    })
+    .then(|extensions: Extensions| {
+        satisfy_map(move |t: Token<'a>| match t.kind {
+            Kind::String => extensions.lookup(t.value),
+            _ => None,
+        })
+    })
+    .and(many(value()))
+    .and(choice((
+        semi().map(|_| None),
+        block().map(Some),
+    )))
+    .and_then(|((ext, args), block): ((_, Vec<Value>), Option<_>)| {
+        if ext.block != block.is_some() {
+            return Err(Error::unexpected_message(if ext.block {
+                "expected block"
+            } else {
+                "unexpected block"
+            }));
+        }
+        let args = args.into_iter().map(Value::into_owned).collect();
+        let directive = (ext.parse)(args)
+            .map_err(Error::unexpected_message)?;
+        let (position, directives) = match block {
+            Some((position, directives)) => {
+                (Some(position), Some(directives))
+            }
+            None => (None, None),
+        };
+        Ok(Item::Extension(ast::Extension {
+            directive, position, directives,
+        }))
+    })
+}
+
+pub fn directive<'a>()
+    -> impl Parser<Output=Directive<'a>, Input=TokenStream<'a>>
 {
     position()
     .and(choice((
-        ident("daemon").with(bool()).skip(semi())
-            .map(Item::Daemon),
-        ident("master_process").with(bool()).skip(semi())
-            .map(Item::MasterProcess),
-        worker_processes(),
+        // main context
+        choice((
+            ident("daemon").with(bool()).skip(semi())
+                .map(Item::Daemon),
+            ident("master_process").with(bool()).skip(semi())
+                .map(Item::MasterProcess),
+            ident("load_module").with(value()).skip(semi())
+                .map(Item::LoadModule),
+            worker_processes(),
+        )),
         ident("http").with(block())
             .map(|(position, directives)| ast::Http { position, directives })
             .map(Item::Http),
@@ -301,20 +385,25 @@ pub fn directive<'a>() -> impl Parser<Output=Directive, Input=TokenStream<'a>>
             Item::Types(t)
         }),
         ident("include").with(value()).skip(semi()).map(Item::Include),
-        ident("ssl_certificate").with(value()).skip(semi())
-            .map(Item::SslCertificate),
-        ident("ssl_certificate_key").with(value()).skip(semi())
-            .map(Item::SslCertificateKey),
         location(),
         headers::directives(),
         server_name(),
         map(),
-        ident("client_max_body_size").with(value()).skip(semi())
+        ident("client_max_body_size").with(size()).skip(semi())
             .map(Item::ClientMaxBodySize),
-        proxy::directives(),
+        // upstream modules
+        choice((
+            proxy::directives(),
+            fastcgi::directives(),
+            uwsgi::directives(),
+            scgi::directives(),
+            grpc::directives(),
+        )),
+        ssl::directives(),
         gzip::directives(),
         core::directives(),
         access::directives(),
+        limit::directives(),
         log::directives(),
         real_ip::directives(),
         openresty(),
@@ -322,9 +411,13 @@ pub fn directive<'a>() -> impl Parser<Output=Directive, Input=TokenStream<'a>>
         ident("empty_gif").skip(semi()).map(|_| Item::EmptyGif),
         ident("index").with(many(value())).skip(semi())
             .map(Item::Index),
+        extension(),
     )))
-    .map(|(pos, dir)| Directive {
-        position: pos,
+    .and(end_position())
+    .map(|((start, dir), end)| Directive {
+        position: start,
+        span: Span { start, end },
+        file: None,
         item: dir,
     })
 }
@@ -336,21 +429,39 @@ pub fn directive<'a>() -> impl Parser<Output=Directive, Input=TokenStream<'a>>
 /// using the current working directory as the base for relative paths.
 /// If you want includes resolved relative to a file, use
 /// `parse_main_from_file(path)` instead.
-pub fn parse_main(s: &str) -> Result<Main, ::failure::Error> {
+pub fn parse_main(s: &str) -> Result<Main<'_>, ConfigError> {
+    parse_main_with(s, &Extensions::new())
+}
+
+pub(crate) fn parse_main_with<'a>(s: &'a str, extensions: &Extensions)
+    -> Result<Main<'a>, ConfigError>
+{
     // Parse into directives (possibly returning a parse error)
-    let mut directives = parse_directives(s).map_err(|e| ::failure::Error::from(e))?;
+    let mut directives = parse_directives_with(s, extensions)
+        .map_err(|error| ConfigError::Parse {
+            path: None,
+            include_chain: Vec::new(),
+            error,
+        })?;
     // Expand includes using current working directory as base
     let base = Path::new(".");
     let mut vars: HashMap<String, String> = HashMap::new();
-    expand_includes(&mut directives, base, None, &mut vars)?;
+    expand_includes(&mut directives, &Disk { extensions }, base, &[],
+                    &mut vars)?;
     Ok(Main { directives })
-} 
+}
 
 /// Parses a piece of config from arbitrary context
 ///
 /// This implies no validation of what context directives belong to.
-pub fn parse_directives(s: &str) -> Result<Vec<Directive>, ParseError> {
-    let mut tokens = TokenStream::new(s);
+pub fn parse_directives(s: &str) -> Result<Vec<Directive<'_>>, ParseError> {
+    parse_directives_with(s, &Extensions::new())
+}
+
+pub(crate) fn parse_directives_with<'a>(s: &'a str, extensions: &Extensions)
+    -> Result<Vec<Directive<'a>>, ParseError>
+{
+    let mut tokens = TokenStream::with_extensions(s, extensions.clone());
     let (doc, _) = many1(directive())
         .skip(eof())
         .parse_stream(&mut tokens)
@@ -358,36 +469,145 @@ pub fn parse_directives(s: &str) -> Result<Vec<Directive>, ParseError> {
     Ok(doc)
 }
 
+/// Parses a piece of config as if it were inside of a `context` block
+///
+/// Unlike [`parse_directives`] this checks that each directive, including
+/// directives of the nested blocks, is allowed where it is (see
+/// [`validate::check_context`]). This is useful for validating snippets,
+/// like the contents of a `location`, kept apart from the main config.
+///
+/// Every misplaced directive is reported in `ConfigError::Invalid`.
+/// Positions of directives and errors are relative to the snippet.
+/// Includes are not expanded.
+///
+/// [`parse_directives`]: fn.parse_directives.html
+/// [`validate::check_context`]: validate/fn.check_context.html
+pub fn parse_in_context(context: Context, s: &str)
+    -> Result<Vec<Directive<'_>>, ConfigError>
+{
+    parse_in_context_with(context, s, &Extensions::new())
+}
+
+pub(crate) fn parse_in_context_with<'a>(context: Context, s: &'a str,
+    extensions: &Extensions)
+    -> Result<Vec<Directive<'a>>, ConfigError>
+{
+    let directives = parse_directives_with(s, extensions)
+        .map_err(|error| ConfigError::Parse {
+            path: None,
+            include_chain: Vec::new(),
+            error,
+        })?;
+    let errors = check_context(context, &directives);
+    if !errors.is_empty() {
+        return Err(ConfigError::Invalid { errors });
+    }
+    Ok(directives)
+}
+
 use std::path::{Path, PathBuf};
 use std::fs;
-use glob::glob;
+use std::io;
+use std::sync::Arc;
+use glob::{glob, PatternError};
 use std::collections::HashMap;
 
+use visitors::visit_mutable;
+
+/// Source of the files loaded by `include`
+pub(crate) trait Files<'a> {
+    /// Files matching the glob pattern, in the order they are included
+    fn matching(&self, pattern: &str) -> Result<Vec<PathBuf>, PatternError>;
+    /// Whether both paths refer to the same file
+    fn same_file(&self, a: &Path, b: &Path) -> Result<bool, io::Error>;
+    /// Parses the file, without expanding its includes
+    ///
+    /// `include_chain` is only used for errors.
+    fn load(&self, path: &Path, include_chain: &[PathBuf])
+        -> Result<Vec<Directive<'a>>, ConfigError>;
+}
+
+/// Files on disk
+struct Disk<'e> {
+    extensions: &'e Extensions,
+}
+
+impl<'a, 'e> Files<'a> for Disk<'e> {
+    fn matching(&self, pattern: &str) -> Result<Vec<PathBuf>, PatternError> {
+        Ok(glob(pattern)?
+            .filter_map(Result::ok)
+            .filter(|path| path.is_file())
+            .collect())
+    }
+    fn same_file(&self, a: &Path, b: &Path) -> Result<bool, io::Error> {
+        Ok(fs::canonicalize(a)? == fs::canonicalize(b)?)
+    }
+    fn load(&self, path: &Path, include_chain: &[PathBuf])
+        -> Result<Vec<Directive<'a>>, ConfigError>
+    {
+        let data = fs::read_to_string(path)
+            .map_err(|error| ConfigError::Io {
+                path: path.to_path_buf(),
+                include_chain: include_chain.to_vec(),
+                error,
+            })?;
+        let mut dirs = parse_directives_with(&data, self.extensions)
+            .map_err(|error| ConfigError::Parse {
+                path: Some(path.to_path_buf()),
+                include_chain: include_chain.to_vec(),
+                error,
+            })?
+            .into_iter().map(Directive::into_owned)
+            .collect();
+        set_file(&mut dirs, path);
+        Ok(dirs)
+    }
+}
+
+/// Marks directives and their children as read from `path`
+pub(crate) fn set_file(dirs: &mut Vec<Directive>, path: &Path) {
+    let file: Arc<Path> = Arc::from(path);
+    visit_mutable(dirs, |dir| dir.file = Some(file.clone()));
+}
+
 /// Parse a file on disk and also expand `include` directives using globbing.
 ///
 /// Includes with variable references are left untouched. Included files are
 /// processed recursively using their directory as a base for relative paths.
 pub fn parse_directives_from_file<P: AsRef<Path>>(path: P)
-    -> Result<Vec<Directive>, ::failure::Error>
+    -> Result<Vec<Directive<'static>>, ConfigError>
+{
+    parse_directives_from_file_with(path.as_ref(), &Extensions::new())
+}
+
+pub(crate) fn parse_directives_from_file_with(path: &Path,
+    extensions: &Extensions)
+    -> Result<Vec<Directive<'static>>, ConfigError>
 {
-    let path = path.as_ref();
     let base = path.parent().unwrap_or_else(|| Path::new("."));
-    let data = fs::read_to_string(path)?;
-    let mut directives = parse_directives(&data)?;
+    let disk = Disk { extensions };
+    let mut directives = disk.load(path, &[])?;
     let mut vars: HashMap<String, String> = HashMap::new();
-    expand_includes(&mut directives, base, Some(path), &mut vars)?;
+    let chain = [path.to_path_buf()];
+    expand_includes(&mut directives, &disk, base, &chain, &mut vars)?;
     Ok(directives)
 }
 
 /// Convenience helper to parse a main config file and expand includes.
 pub fn parse_main_from_file<P: AsRef<Path>>(path: P)
-    -> Result<Main, ::failure::Error>
+    -> Result<Main<'static>, ConfigError>
 {
-    let dirs = parse_directives_from_file(path)?;
+    parse_main_from_file_with(path.as_ref(), &Extensions::new())
+}
+
+pub(crate) fn parse_main_from_file_with(path: &Path, extensions: &Extensions)
+    -> Result<Main<'static>, ConfigError>
+{
+    let dirs = parse_directives_from_file_with(path, extensions)?;
     Ok(Main { directives: dirs })
 }
 
-fn value_to_path(v: &Value) -> Option<String> {
+pub(crate) fn value_to_path(v: &Value) -> Option<String> {
     use value::Item;
     let mut s = String::new();
     for item in &v.data {
@@ -406,7 +626,7 @@ fn resolve_value_with_vars(v: &Value, vars: &HashMap<String, String>) -> Option<
         match item {
             Item::Literal(x) => s.push_str(x),
             Item::Variable(name) => {
-                if let Some(val) = vars.get(name) {
+                if let Some(val) = vars.get(&name[..]) {
                     s.push_str(val);
                 } else {
                     return None;
@@ -417,16 +637,22 @@ fn resolve_value_with_vars(v: &Value, vars: &HashMap<String, String>) -> Option<
     Some(s)
 }
 
-fn expand_includes(dirs: &mut Vec<Directive>, base: &Path, current_file: Option<&Path>, vars: &mut HashMap<String, String>)
-    -> Result<(), ::failure::Error>
+/// Replaces `include` directives with directives of the included files
+///
+/// `chain` is the list of files from the main one to the one containing
+/// `dirs`, it's empty if `dirs` aren't read from a file.
+pub(crate) fn expand_includes<'a>(dirs: &mut Vec<Directive<'a>>, files: &dyn Files<'a>, base: &Path, chain: &[PathBuf], vars: &mut HashMap<String, String>)
+    -> Result<(), ConfigError>
 {
+    let current_file = chain.last();
+    let (outer, _) = chain.split_at(chain.len().saturating_sub(1));
     let mut i = 0;
     while i < dirs.len() {
         // Update variable map if this directive is a `set` in the current scope.
         match dirs[i].item {
             ast::Item::Set { ref variable, ref value } => {
                 if let Some(resolved) = resolve_value_with_vars(value, vars) {
-                    vars.insert(variable.clone(), resolved);
+                    vars.insert(variable.to_string(), resolved);
                 }
             }
             _ => {}
@@ -436,11 +662,12 @@ fn expand_includes(dirs: &mut Vec<Directive>, base: &Path, current_file: Option<
         {
             use ast::Item::*;
             match dirs[i].item {
-                Http(ref mut h) => { let mut subvars = vars.clone(); expand_includes(&mut h.directives, base, current_file, &mut subvars)?; }
-                Server(ref mut s) => { let mut subvars = vars.clone(); expand_includes(&mut s.directives, base, current_file, &mut subvars)?; }
-                Location(ref mut l) => { let mut subvars = vars.clone(); expand_includes(&mut l.directives, base, current_file, &mut subvars)?; }
-                If(ref mut iff) => { let mut subvars = vars.clone(); expand_includes(&mut iff.directives, base, current_file, &mut subvars)?; }
-                LimitExcept(ref mut le) => { let mut subvars = vars.clone(); expand_includes(&mut le.directives, base, current_file, &mut subvars)?; }
+                Http(ref mut h) => { let mut subvars = vars.clone(); expand_includes(&mut h.directives, files, base, chain, &mut subvars)?; }
+                Server(ref mut s) => { let mut subvars = vars.clone(); expand_includes(&mut s.directives, files, base, chain, &mut subvars)?; }
+                Location(ref mut l) => { let mut subvars = vars.clone(); expand_includes(&mut l.directives, files, base, chain, &mut subvars)?; }
+                If(ref mut iff) => { let mut subvars = vars.clone(); expand_includes(&mut iff.directives, files, base, chain, &mut subvars)?; }
+                LimitExcept(ref mut le) => { let mut subvars = vars.clone(); expand_includes(&mut le.directives, files, base, chain, &mut subvars)?; }
+                Extension(ast::Extension { directives: Some(ref mut d), .. }) => { let mut subvars = vars.clone(); expand_includes(d, files, base, chain, &mut subvars)?; }
                 _ => {}
             }
         }
@@ -452,25 +679,46 @@ fn expand_includes(dirs: &mut Vec<Directive>, base: &Path, current_file: Option<
                     // Interpret pattern relative to base
                     let full_pat = base.join(&pat).to_string_lossy().into_owned();
                     let mut inserted = Vec::new();
-                    for entry in glob(&full_pat)? {
-                        if let Ok(path) = entry {
-                            // don't include the file that contains the include
-                            if let Some(cur) = current_file {
-                                if fs::canonicalize(&path)? == fs::canonicalize(cur)? {
-                                    continue;
-                                }
+                    let matching = files.matching(&full_pat)
+                        .map_err(|error| ConfigError::Pattern {
+                            path: current_file.cloned(),
+                            include_chain: outer.to_vec(),
+                            position: dirs[i].position,
+                            pattern: full_pat.clone(),
+                            error,
+                        })?;
+                    for path in matching {
+                        let same_file = |other: &PathBuf| {
+                            files.same_file(&path, other)
+                            .map_err(|error| ConfigError::Io {
+                                path: path.clone(),
+                                include_chain: chain.to_vec(),
+                                error,
+                            })
+                        };
+                        // don't include the file that contains the include
+                        if let Some(cur) = current_file {
+                            if same_file(cur)? {
+                                continue;
                             }
-                            if path.is_file() {
-                                let data = fs::read_to_string(&path)?;
-                                let mut inc_dirs = parse_directives(&data)?;
-                                // recursively expand includes within included file
-                                if let Some(dirp) = path.parent() {
-                                    // included file shares current variable scope (included content acts as if inserted here)
-                                    expand_includes(&mut inc_dirs, dirp, Some(&path), vars)?;
-                                }
-                                inserted.append(&mut inc_dirs);
+                        }
+                        for other in outer {
+                            if same_file(other)? {
+                                return Err(ConfigError::IncludeCycle {
+                                    path,
+                                    include_chain: chain.to_vec(),
+                                });
                             }
                         }
+                        let mut inc_dirs = files.load(&path, chain)?;
+                        // recursively expand includes within included file
+                        if let Some(dirp) = path.parent() {
+                            let mut inner = chain.to_vec();
+                            inner.push(path.clone());
+                            // included file shares current variable scope (included content acts as if inserted here)
+                            expand_includes(&mut inc_dirs, files, dirp, &inner, vars)?;
+                        }
+                        inserted.append(&mut inc_dirs);
                     }
                     if !inserted.is_empty() {
                         // replace the include directive with inserted ones
@@ -0,0 +1,57 @@
+use combine::{Parser};
+use combine::{choice};
+
+use ast::{Item};
+use grammar::{value, bool, duration, raw};
+use helpers::{semi, ident};
+use proxy::next_upstream;
+use ssl::protocols;
+use tokenizer::TokenStream;
+
+
+fn ssl_directives<'a>()
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
+{
+    choice((
+        ident("grpc_ssl_certificate").with(value()).skip(semi())
+            .map(Item::GrpcSslCertificate),
+        ident("grpc_ssl_certificate_key").with(value()).skip(semi())
+            .map(Item::GrpcSslCertificateKey),
+        ident("grpc_ssl_protocols").with(protocols()).skip(semi())
+            .map(Item::GrpcSslProtocols),
+        ident("grpc_ssl_ciphers").with(raw())
+            .skip(semi())
+            .map(Item::GrpcSslCiphers),
+        ident("grpc_ssl_verify").with(bool()).skip(semi())
+            .map(Item::GrpcSslVerify),
+        ident("grpc_ssl_trusted_certificate").with(value()).skip(semi())
+            .map(Item::GrpcSslTrustedCertificate),
+        ident("grpc_ssl_server_name").with(bool()).skip(semi())
+            .map(Item::GrpcSslServerName),
+        ident("grpc_ssl_name").with(value()).skip(semi())
+            .map(Item::GrpcSslName),
+    ))
+}
+
+pub fn directives<'a>()
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
+{
+    choice((
+        ident("grpc_pass").with(value()).skip(semi())
+            .map(Item::GrpcPass),
+        ident("grpc_set_header").with(value()).and(value())
+            .skip(semi())
+            .map(|(field, value)| Item::GrpcSetHeader { field, value }),
+        ident("grpc_read_timeout").with(duration()).skip(semi())
+            .map(Item::GrpcReadTimeout),
+        ident("grpc_connect_timeout").with(duration()).skip(semi())
+            .map(Item::GrpcConnectTimeout),
+        ident("grpc_send_timeout").with(duration()).skip(semi())
+            .map(Item::GrpcSendTimeout),
+        ident("grpc_intercept_errors").with(bool()).skip(semi())
+            .map(Item::GrpcInterceptErrors),
+        ident("grpc_next_upstream").with(next_upstream()).skip(semi())
+            .map(Item::GrpcNextUpstream),
+        ssl_directives(),
+    ))
+}
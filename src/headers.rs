@@ -1,13 +1,15 @@
 use combine::{Parser, optional};
-use combine::{choice};
+use combine::choice;
+use combine::easy::Error;
 
-use ast::{self, Item};
+use ast::{self, Item, Duration};
 use grammar::{value};
-use helpers::{semi, ident};
-use tokenizer::{TokenStream};
+use helpers::{semi, ident, string, spanned};
+use tokenizer::{TokenStream, Token};
+use value::Value;
 
 fn add_header<'a>()
-    -> impl Parser<Output=Item, Input=TokenStream<'a>>
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
 {
     ident("add_header")
     .with((
@@ -21,19 +23,46 @@ fn add_header<'a>()
     .map(Item::AddHeader)
 }
 
+fn expires_time<'a>()
+    -> impl Parser<Output=ast::ExpiresTime<'a>, Input=TokenStream<'a>>
+{
+    use ast::ExpiresTime::*;
+    choice((
+        ident("off").map(|_| Off),
+        ident("epoch").map(|_| Epoch),
+        ident("max").map(|_| Max),
+        spanned(string()).and_then(|(span, tok)|
+            -> Result<_, Error<Token<'a>, Token<'a>>>
+        {
+            if tok.value.contains('$') {
+                Ok(Variable(Value::parse(span, tok)?))
+            } else if let Some(time) = tok.value.strip_prefix('@') {
+                Ok(TimeOfDay(time.parse::<Duration>()?))
+            } else if let Some(time) = tok.value.strip_prefix('-') {
+                Ok(Before(time.parse::<Duration>()?))
+            } else if let Some(time) = tok.value.strip_prefix('+') {
+                // the sign is kept for display
+                Ok(After(time.parse::<Duration>()?.spelled(tok.value)))
+            } else {
+                Ok(After(tok.value.parse::<Duration>()?))
+            }
+        }),
+    ))
+}
+
 fn expires<'a>()
-    -> impl Parser<Output=Item, Input=TokenStream<'a>>
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
 {
     ident("expires")
     .with(optional(ident("modified"))).map(|x| x.is_some())
-    .and(value())
+    .and(expires_time())
     .map(|(modified, value)| {
         Item::Expires(ast::Expires { modified, value })
     }).skip(semi())
 }
 
 pub fn directives<'a>()
-    -> impl Parser<Output=Item, Input=TokenStream<'a>>
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
 {
     choice((
         add_header(),
@@ -1,11 +1,12 @@
 use std::marker::PhantomData;
 
-use combine::{Parser, ConsumedResult, satisfy, StreamOnce};
+use combine::{Parser, ConsumedResult, satisfy, parser, position, StreamOnce};
+use combine::error::Consumed;
 use combine::error::{Tracked};
 use combine::stream::easy::{Error, Errors, Info};
 
 use tokenizer::{TokenStream, Kind, Token};
-use position::Pos;
+use position::{Pos, Span};
 
 
 #[derive(Debug, Clone)]
@@ -65,6 +66,25 @@ pub fn string<'x>() -> TokenMatch<'x> {
     kind(Kind::String)
 }
 
+/// Position right after the last parsed token
+///
+/// This is an end counterpart of `combine::position` which points to the
+/// start of the next token.
+pub fn end_position<'x>() -> impl Parser<Output=Pos, Input=TokenStream<'x>> {
+    parser(|input: &mut TokenStream<'x>| {
+        Ok((input.end_position(), Consumed::Empty(())))
+    })
+}
+
+/// Wraps parser to also return the span of the tokens it consumed
+pub fn spanned<'x, P>(p: P)
+    -> impl Parser<Output=(Span, P::Output), Input=TokenStream<'x>>
+    where P: Parser<Input=TokenStream<'x>>
+{
+    (position(), p, end_position())
+    .map(|(start, out, end)| (Span { start, end }, out))
+}
+
 impl<'a> Parser for TokenMatch<'a> {
     type Input = TokenStream<'a>;
     type Output = Token<'a>;
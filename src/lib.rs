@@ -17,28 +17,49 @@ extern crate combine;
 #[macro_use] extern crate matches;
 #[cfg(test)] #[macro_use] extern crate pretty_assertions;
 extern crate glob;
+#[cfg(feature="regex")] extern crate fancy_regex;
 
 pub mod ast;
+pub mod diff;
+pub mod edit;
 mod display;
+mod dump;
 mod error;
+pub mod extension;
 mod format;
 mod grammar;
 mod helpers;
+pub mod lint;
+mod owned;
 mod position;
+pub mod profile;
+pub mod query;
+pub mod registry;
 mod tokenizer;
+mod units;
 mod value;
+pub mod validate;
 pub mod visitors;
+#[cfg(feature="regex")] pub mod regex;
 
 mod access;
 mod core;
+mod fastcgi;
+mod grpc;
 mod gzip;
 mod headers;
+mod limit;
 mod proxy;
 mod rewrite;
+mod scgi;
 mod log;
 mod real_ip;
+mod ssl;
+mod uwsgi;
 
 pub use grammar::{parse_main, parse_directives, parse_main_from_file, parse_directives_from_file};
-pub use format::Style;
-pub use position::Pos;
-pub use error::ParseError;
+pub use grammar::parse_in_context;
+pub use format::{Style, Quotes};
+pub use position::{Pos, Span};
+pub use error::{ParseError, ConfigError};
+pub use dump::parse_nginx_t_dump;
@@ -0,0 +1,152 @@
+use std::borrow::Cow;
+
+use combine::{many1, Parser};
+use combine::{choice};
+use combine::error::StreamError;
+use combine::easy::Error;
+
+use ast::{self, Item, ErrorLevel, Rate, Size};
+use grammar::{value, raw};
+use helpers::{semi, ident, string};
+use tokenizer::{TokenStream, Token};
+
+
+/// Parses `zone=name:size` into name and size
+fn zone<'a>(value: &'a str)
+    -> Result<(Cow<'a, str>, Size), Error<Token<'a>, Token<'a>>>
+{
+    let mut pair = value.splitn(2, ':');
+    let name = pair.next().unwrap();
+    let size = pair.next().ok_or_else(|| {
+        Error::unexpected_message("zone must be in form `zone=name:size`")
+    })?;
+    Ok((name.into(), size.parse()?))
+}
+
+fn status<'a>() -> impl Parser<Output=u32, Input=TokenStream<'a>> {
+    string().and_then(|t| -> Result<_, Error<_, _>> {
+        let code = t.value.parse::<u32>()?;
+        match code {
+            400..=599 => Ok(code),
+            _ => Err(Error::unexpected_message(
+                format!("status code must be between 400 and 599, \
+                         got {}", code))),
+        }
+    })
+}
+
+fn limit_req_zone<'a>()
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
+{
+    ident("limit_req_zone")
+    .with(value())
+    .and(many1(string()))
+    .and_then(|(key, items): (_, Vec<Token<'a>>)| {
+        let mut name_size = None;
+        let mut rate = None;
+        let mut sync = false;
+        let mut order = Vec::new();
+        for tok in items {
+            if let Some(val) = tok.value.strip_prefix("zone=") {
+                name_size = Some(zone(val)?);
+                order.push(ast::LimitReqZoneKey::Zone);
+            } else if let Some(val) = tok.value.strip_prefix("rate=") {
+                rate = Some(val.parse::<Rate>()?);
+                order.push(ast::LimitReqZoneKey::Rate);
+            } else if tok.value == "sync" {
+                sync = true;
+                order.push(ast::LimitReqZoneKey::Sync);
+            } else {
+                return Err(Error::unexpected_message(
+                    format!("invalid limit_req_zone option {:?}",
+                            tok.value)));
+            }
+        }
+        let (name, size) = name_size.ok_or_else(|| {
+            Error::unexpected_message("limit_req_zone requires zone")
+        })?;
+        let rate = rate.ok_or_else(|| {
+            Error::unexpected_message("limit_req_zone requires rate")
+        })?;
+        Ok(ast::LimitReqZone { key, name, size, rate, sync, order })
+    })
+    .skip(semi())
+    .map(Item::LimitReqZone)
+}
+
+fn limit_req<'a>() -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>> {
+    use ast::LimitReqDelay::*;
+    ident("limit_req")
+    .with(many1(string()))
+    .and_then(|items: Vec<Token<'a>>| {
+        let mut zone = None;
+        let mut burst = None;
+        let mut delay = None;
+        for tok in items {
+            if let Some(val) = tok.value.strip_prefix("zone=") {
+                zone = Some(Cow::Borrowed(val));
+            } else if let Some(val) = tok.value.strip_prefix("burst=") {
+                burst = Some(val.parse()?);
+            } else if let Some(val) = tok.value.strip_prefix("delay=") {
+                delay = Some(Delay(val.parse()?));
+            } else if tok.value == "nodelay" {
+                delay = Some(NoDelay);
+            } else {
+                return Err(Error::unexpected_message(
+                    format!("invalid limit_req option {:?}", tok.value)));
+            }
+        }
+        let zone = zone.ok_or_else(|| {
+            Error::unexpected_message("limit_req requires zone")
+        })?;
+        Ok(ast::LimitReq { zone, burst, delay })
+    })
+    .skip(semi())
+    .map(Item::LimitReq)
+}
+
+fn log_level<'a>() -> impl Parser<Output=ErrorLevel, Input=TokenStream<'a>> {
+    use ast::ErrorLevel::*;
+    choice((
+        ident("info").map(|_| Info),
+        ident("notice").map(|_| Notice),
+        ident("warn").map(|_| Warn),
+        ident("error").map(|_| Error),
+    ))
+}
+
+fn limit_conn_zone<'a>()
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
+{
+    ident("limit_conn_zone")
+    .with(value())
+    .and(string().and_then(|t| match t.value.strip_prefix("zone=") {
+        Some(val) => zone(val),
+        None => Err(Error::unexpected_message(
+            "limit_conn_zone requires zone")),
+    }))
+    .skip(semi())
+    .map(|(key, (name, size))| ast::LimitConnZone { key, name, size })
+    .map(Item::LimitConnZone)
+}
+
+pub fn directives<'a>()
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
+{
+    choice((
+        limit_req_zone(),
+        limit_req(),
+        ident("limit_req_status").with(status()).skip(semi())
+            .map(Item::LimitReqStatus),
+        ident("limit_req_log_level").with(log_level()).skip(semi())
+            .map(Item::LimitReqLogLevel),
+        limit_conn_zone(),
+        ident("limit_conn")
+            .with(raw())
+            .and(string().and_then(|t| t.value.parse::<u32>()))
+            .skip(semi())
+            .map(|(zone, number)| Item::LimitConn { zone, number }),
+        ident("limit_conn_status").with(status()).skip(semi())
+            .map(Item::LimitConnStatus),
+    ))
+}
@@ -0,0 +1,352 @@
+//! Lints for nginx config
+//!
+//! Unlike [`validate`](../validate/index.html) these checks report
+//! configurations which are valid for nginx but are probably not what
+//! the user wants (e.g. insecure). Each warning has a rule id, so any
+//! rule can be suppressed.
+//!
+//! [`public_locations`](fn.public_locations.html) helps to review which
+//! parts of the site are reachable without authentication.
+use std::collections::HashSet;
+use std::fmt;
+
+use ast::{Directive, Item, Location, LocationPattern, Source, SslProtocol};
+use position::Pos;
+
+
+/// Deprecated protocol (SSLv2, SSLv3, TLSv1, TLSv1.1) in `ssl_protocols`
+pub const TLS_DEPRECATED_PROTOCOL: &str = "tls-deprecated-protocol";
+/// Weak cipher (RC4, 3DES, NULL, EXPORT) enabled in `ssl_ciphers`
+pub const TLS_WEAK_CIPHER: &str = "tls-weak-cipher";
+/// Server has `listen ... ssl` but no `ssl_certificate`
+pub const TLS_MISSING_CERTIFICATE: &str = "tls-missing-certificate";
+/// HTTPS server doesn't send `Strict-Transport-Security` header
+pub const TLS_MISSING_HSTS: &str = "tls-missing-hsts";
+/// Directive or parameter is deprecated in the target nginx version
+///
+/// Reported by [`ParserProfile::lint`] rather than by the `Linter`.
+///
+/// [`ParserProfile::lint`]: ../profile/struct.ParserProfile.html#method.lint
+pub const DEPRECATED_DIRECTIVE: &str = "deprecated-directive";
+
+const TLS_RULES: &[&str] = &[
+    TLS_DEPRECATED_PROTOCOL,
+    TLS_WEAK_CIPHER,
+    TLS_MISSING_CERTIFICATE,
+    TLS_MISSING_HSTS,
+];
+
+/// OpenSSL cipher names and aliases which enable weak ciphers
+const WEAK_CIPHERS: &[&str] = &[
+    // aliases
+    "3DES", "DES", "RC4", "RC2", "EXP", "EXPORT", "EXPORT40", "EXPORT56",
+    "LOW", "NULL", "eNULL", "aNULL", "ADH", "AECDH",
+    // RC4 suites
+    "RC4-MD5", "RC4-SHA", "ADH-RC4-MD5", "ECDH-RSA-RC4-SHA",
+    "ECDH-ECDSA-RC4-SHA", "ECDHE-RSA-RC4-SHA", "ECDHE-ECDSA-RC4-SHA",
+    "AECDH-RC4-SHA", "PSK-RC4-SHA",
+    // DES and triple DES suites
+    "DES-CBC-SHA", "DES-CBC3-SHA", "DES-CBC-MD5", "DES-CBC3-MD5",
+    "EDH-RSA-DES-CBC-SHA", "EDH-DSS-DES-CBC-SHA",
+    "EDH-RSA-DES-CBC3-SHA", "EDH-DSS-DES-CBC3-SHA",
+    "ADH-DES-CBC-SHA", "ADH-DES-CBC3-SHA",
+    "ECDH-RSA-DES-CBC3-SHA", "ECDH-ECDSA-DES-CBC3-SHA",
+    "ECDHE-RSA-DES-CBC3-SHA", "ECDHE-ECDSA-DES-CBC3-SHA",
+    "AECDH-DES-CBC3-SHA", "PSK-3DES-EDE-CBC-SHA",
+    // export suites
+    "EXP-RC4-MD5", "EXP-RC2-CBC-MD5", "EXP-DES-CBC-SHA",
+    "EXP-EDH-RSA-DES-CBC-SHA", "EXP-EDH-DSS-DES-CBC-SHA",
+    "EXP-ADH-RC4-MD5", "EXP-ADH-DES-CBC-SHA",
+    // suites without encryption
+    "NULL-MD5", "NULL-SHA", "NULL-SHA256", "ECDHE-RSA-NULL-SHA",
+    "ECDHE-ECDSA-NULL-SHA", "AECDH-NULL-SHA",
+];
+
+
+/// A warning reported by the linter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    /// Position of the directive which triggered the warning
+    pub position: Pos,
+    /// Rule id, can be used to suppress the warning
+    pub rule: &'static str,
+    /// Human-readable description of the problem
+    pub message: String,
+}
+
+/// Set of enabled lint rules
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Linter {
+    rules: Vec<&'static str>,
+    suppressed: HashSet<String>,
+}
+
+impl Linter {
+    /// Profile which checks TLS security policy
+    pub fn tls() -> Linter {
+        Linter {
+            rules: TLS_RULES.to_vec(),
+            suppressed: HashSet::new(),
+        }
+    }
+
+    /// Don't report warnings of the specified rule
+    pub fn suppress(&mut self, rule: &str) -> &mut Self {
+        self.suppressed.insert(rule.to_string());
+        self
+    }
+
+    /// Returns true if rule is in the profile and is not suppressed
+    pub fn is_enabled(&self, rule: &str) -> bool {
+        self.rules.contains(&rule) && !self.suppressed.contains(rule)
+    }
+
+    /// Run all enabled rules over the config
+    ///
+    /// Warnings are returned in the order of directives in the config.
+    pub fn check(&self, directives: &[Directive]) -> Vec<Warning> {
+        let mut warnings = Vec::new();
+        tls_block(directives, &Inherited::default(), &mut warnings);
+        warnings.retain(|w| self.is_enabled(w.rule));
+        warnings.sort_by_key(|w| w.position);
+        warnings
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {} [{}]", self.position, self.message, self.rule)
+    }
+}
+
+/// Settings which are inherited from the `http` block into servers
+#[derive(Debug, Default, Clone)]
+struct Inherited {
+    certificate: bool,
+    hsts: bool,
+}
+
+fn is_hsts(item: &Item) -> bool {
+    match *item {
+        Item::AddHeader(ref h) => {
+            h.field.to_string().eq_ignore_ascii_case(
+                "Strict-Transport-Security")
+        }
+        _ => false,
+    }
+}
+
+fn protocol_warnings(position: Pos, protocols: &[SslProtocol],
+    warnings: &mut Vec<Warning>)
+{
+    use ast::SslProtocol::*;
+    for proto in protocols {
+        match *proto {
+            SslV2 | SslV3 | TlsV1 | TlsV1_1 => warnings.push(Warning {
+                position,
+                rule: TLS_DEPRECATED_PROTOCOL,
+                message: format!("protocol {} is deprecated", proto),
+            }),
+            TlsV1_2 | TlsV1_3 => {}
+        }
+    }
+}
+
+fn cipher_warnings(position: Pos, ciphers: &str,
+    warnings: &mut Vec<Warning>)
+{
+    let names = ciphers.split(&[':', ' ', ','][..])
+        .filter(|name| !name.is_empty());
+    for cipher in names {
+        // `!X` and `-X` remove ciphers from the list
+        if cipher.starts_with('!') || cipher.starts_with('-') {
+            continue;
+        }
+        // `+X` moves ciphers to the end of the list
+        let name = cipher.trim_start_matches('+');
+        if WEAK_CIPHERS.contains(&name) {
+            warnings.push(Warning {
+                position,
+                rule: TLS_WEAK_CIPHER,
+                message: format!("weak cipher {:?} is enabled", cipher),
+            });
+        }
+    }
+}
+
+fn tls_block(directives: &[Directive], parent: &Inherited,
+    warnings: &mut Vec<Warning>)
+{
+    let mut current = parent.clone();
+    // add_header directives are inherited only if there are none on
+    // the current level
+    if directives.iter().any(|d| matches!(d.item, Item::AddHeader(..))) {
+        current.hsts = directives.iter().any(|d| is_hsts(&d.item));
+    }
+    current.certificate |= directives.iter()
+        .any(|d| matches!(d.item, Item::SslCertificate(..)));
+    for dir in directives {
+        match dir.item {
+            Item::SslProtocols(ref protocols) => {
+                protocol_warnings(dir.position, protocols, warnings);
+            }
+            Item::SslCiphers(ref ciphers) => {
+                cipher_warnings(dir.position, ciphers, warnings);
+            }
+            Item::Server(ref srv) => {
+                let ssl = srv.directives.iter().any(|d| match d.item {
+                    Item::Listen(ref lst) => lst.ssl,
+                    _ => false,
+                });
+                if ssl {
+                    tls_server(dir.position, &srv.directives, &current,
+                        warnings);
+                } else {
+                    tls_block(&srv.directives, &current, warnings);
+                }
+            }
+            _ => {
+                if let Some(children) = dir.item.children() {
+                    tls_block(children, &current, warnings);
+                }
+            }
+        }
+    }
+}
+
+fn tls_server(position: Pos, directives: &[Directive], http: &Inherited,
+    warnings: &mut Vec<Warning>)
+{
+    let has_headers = directives.iter()
+        .any(|d| matches!(d.item, Item::AddHeader(..)));
+    let hsts = if has_headers {
+        directives.iter().any(|d| is_hsts(&d.item))
+    } else {
+        http.hsts
+    };
+    let certificate = http.certificate || directives.iter()
+        .any(|d| matches!(d.item, Item::SslCertificate(..)));
+    if !certificate {
+        warnings.push(Warning {
+            position,
+            rule: TLS_MISSING_CERTIFICATE,
+            message: "server listens with ssl but has no ssl_certificate"
+                .into(),
+        });
+    }
+    if !hsts {
+        warnings.push(Warning {
+            position,
+            rule: TLS_MISSING_HSTS,
+            message: "https server has no \
+                `add_header Strict-Transport-Security`".into(),
+        });
+    }
+    tls_block(directives, http, warnings);
+}
+
+/// Access settings effective in a block after inheritance
+#[derive(Debug, Default, Clone)]
+struct Access<'a> {
+    auth_basic: bool,
+    auth_request: bool,
+    auth_jwt: bool,
+    satisfy_any: bool,
+    rules: Vec<&'a Item<'a>>,
+}
+
+impl<'a> Access<'a> {
+    fn merge(&self, directives: &'a [Directive<'a>]) -> Access<'a> {
+        use ast::{AuthBasic, AuthRequest, AuthJwt, Satisfy};
+
+        let mut access = self.clone();
+        let mut rules = Vec::new();
+        for dir in directives {
+            match dir.item {
+                Item::AuthBasic(ref a) => {
+                    access.auth_basic = matches!(*a, AuthBasic::Realm(..));
+                }
+                Item::AuthRequest(ref a) => {
+                    access.auth_request = matches!(*a, AuthRequest::Uri(..));
+                }
+                Item::AuthJwt(ref a) => {
+                    access.auth_jwt = matches!(*a, AuthJwt::Realm {..});
+                }
+                Item::Satisfy(s) => access.satisfy_any = s == Satisfy::Any,
+                Item::Allow(..) | Item::Deny(..) => rules.push(&dir.item),
+                _ => {}
+            }
+        }
+        // allow/deny rules are inherited only if there are none on
+        // the current level
+        if !rules.is_empty() {
+            access.rules = rules;
+        }
+        access
+    }
+
+    /// Returns the action of the first rule which matches any client
+    ///
+    /// Rules for specific addresses before it can't make location
+    /// public or private as a whole, so they are ignored.
+    fn all_clients(&self) -> Option<bool> {
+        self.rules.iter().filter_map(|item| match **item {
+            Item::Allow(Source::All) => Some(true),
+            Item::Deny(Source::All) => Some(false),
+            _ => None,
+        }).next()
+    }
+
+    fn is_public(&self) -> bool {
+        let auth = self.auth_basic || self.auth_request || self.auth_jwt;
+        let all_clients = self.all_clients();
+        if self.satisfy_any && all_clients == Some(true) {
+            // `allow all` is enough when any of the checks may pass
+            return true;
+        }
+        !auth && all_clients != Some(false)
+    }
+}
+
+fn public_in_block<'a>(directives: &'a [Directive<'a>], parent: &Access<'a>,
+    result: &mut Vec<&'a Location<'a>>)
+{
+    let access = parent.merge(directives);
+    for dir in directives {
+        match dir.item {
+            Item::Location(ref loc) => {
+                let nested = access.merge(&loc.directives);
+                let internal =
+                    matches!(loc.pattern, LocationPattern::Named(..)) ||
+                    loc.directives.iter()
+                        .any(|d| matches!(d.item, Item::Internal));
+                if !internal && nested.is_public() {
+                    result.push(loc);
+                }
+                public_in_block(&loc.directives, &access, result);
+            }
+            // access settings in `if` and `limit_except` are conditional
+            Item::If(..) | Item::LimitExcept(..) => {}
+            _ => {
+                if let Some(children) = dir.item.children() {
+                    public_in_block(children, &access, result);
+                }
+            }
+        }
+    }
+}
+
+/// Returns locations which are accessible by anyone
+///
+/// A location is public if after inheritance it has neither
+/// authentication (`auth_basic`, `auth_request`, `auth_jwt`) nor `deny all`.
+/// With `satisfy any` a location is also public if `allow all` is reached
+/// before `deny all`, regardless of authentication. Named and `internal`
+/// locations can't be requested by clients, so they are never reported.
+pub fn public_locations<'a>(directives: &'a [Directive<'a>])
+    -> Vec<&'a Location<'a>>
+{
+    let mut result = Vec::new();
+    public_in_block(directives, &Access::default(), &mut result);
+    result
+}
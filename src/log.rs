@@ -1,23 +1,23 @@
 use combine::{many, Parser};
-use combine::{choice, optional, position};
+use combine::{choice, optional};
 use combine::error::StreamError;
 use combine::easy::Error;
 
 use ast::{self, Item};
 use grammar::{value};
-use helpers::{semi, ident, string};
+use helpers::{semi, ident, string, spanned};
 use tokenizer::{TokenStream};
-use value::Value;
+use value::{Value, unquote};
 
 
 fn access_log<'a>()
-    -> impl Parser<Output=Item, Input=TokenStream<'a>>
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
 {
-    enum I {
-        If(Value),
+    enum I<'a> {
+        If(Value<'a>),
         Gzip(Option<u8>),
-        Flush(String),
-        Buffer(String),
+        Flush(&'a str),
+        Buffer(&'a str),
     }
 
     ident("access_log")
@@ -26,17 +26,17 @@ fn access_log<'a>()
         value().and(optional(
             string()
             .and(many::<Vec<_>, _>(
-                (position(), string()).and_then(|(pos, s)| {
-                    if s.value.starts_with("if=") {
-                        Ok(I::If(Value::parse_str(pos, &s.value[3..])?))
+                spanned(string()).and_then(|(span, s)| {
+                    if let Some(cond) = s.value.strip_prefix("if=") {
+                        Ok(I::If(Value::parse_str(span.tail(cond), cond)?))
                     } else if s.value == "gzip" {
                         Ok(I::Gzip(None))
                     } else if s.value.starts_with("gzip=") {
                         Ok(I::Gzip(Some(s.value[5..].parse()?)))
                     } else if s.value.starts_with("buffer=") {
-                        Ok(I::Buffer(s.value[7..].to_string()))
+                        Ok(I::Buffer(&s.value[7..]))
                     } else if s.value.starts_with("flush=") {
-                        Ok(I::Flush(s.value[6..].to_string()))
+                        Ok(I::Flush(&s.value[6..]))
                     } else {
                         Err(Error::unexpected_message(
                             format!("bad access_log param {:?}", s.value)))
@@ -52,13 +52,13 @@ fn access_log<'a>()
                 condition: None,
             };
             if let Some((format, params)) = params {
-                res.format = Some(format.value.to_string());
+                res.format = Some(unquote(format.value));
                 for item in params {
                     match item {
                         I::If(val) => res.condition = Some(val),
-                        I::Buffer(buf) => res.buffer = Some(buf),
+                        I::Buffer(buf) => res.buffer = Some(buf.into()),
                         I::Gzip(gzip) => res.gzip = Some(gzip),
-                        I::Flush(flush) => res.flush = Some(flush),
+                        I::Flush(flush) => res.flush = Some(flush.into()),
                     }
                 }
             }
@@ -70,7 +70,7 @@ fn access_log<'a>()
 }
 
 pub fn directives<'a>()
-    -> impl Parser<Output=Item, Input=TokenStream<'a>>
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
 {
     choice((
         access_log(),
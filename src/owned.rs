@@ -0,0 +1,254 @@
+//! Conversion of the AST borrowing the source text into an owned one
+use std::borrow::Cow;
+use std::path::Path;
+use std::sync::Arc;
+
+use ast::*;
+use extension::ExtensionDirective;
+use position::{Pos, Span};
+
+/// Converts node into one with `'static` lifetime, copying borrowed strings
+pub(crate) trait IntoStatic {
+    type Static;
+    fn into_static(self) -> Self::Static;
+}
+
+impl<'a> IntoStatic for Cow<'a, str> {
+    type Static = Cow<'static, str>;
+    fn into_static(self) -> Cow<'static, str> {
+        Cow::Owned(self.into_owned())
+    }
+}
+
+impl<'a> IntoStatic for Value<'a> {
+    type Static = Value<'static>;
+    fn into_static(self) -> Value<'static> {
+        self.into_owned()
+    }
+}
+
+impl<T: IntoStatic> IntoStatic for Vec<T> {
+    type Static = Vec<T::Static>;
+    fn into_static(self) -> Vec<T::Static> {
+        self.into_iter().map(IntoStatic::into_static).collect()
+    }
+}
+
+impl<T: IntoStatic> IntoStatic for Option<T> {
+    type Static = Option<T::Static>;
+    fn into_static(self) -> Option<T::Static> {
+        self.map(IntoStatic::into_static)
+    }
+}
+
+impl<A: IntoStatic, B: IntoStatic> IntoStatic for (A, B) {
+    type Static = (A::Static, B::Static);
+    fn into_static(self) -> (A::Static, B::Static) {
+        (self.0.into_static(), self.1.into_static())
+    }
+}
+
+/// Implements `IntoStatic` for types which don't borrow anything
+macro_rules! unchanged {
+    ($($ty:ty),* $(,)*) => {$(
+        impl IntoStatic for $ty {
+            type Static = $ty;
+            fn into_static(self) -> $ty {
+                self
+            }
+        }
+    )*};
+}
+
+/// Implements `IntoStatic` for AST nodes by converting each field
+///
+/// Fields of structs and struct variants are listed by name, fields of
+/// tuple variants by a binding name of choice.
+macro_rules! into_static {
+    () => {};
+    (struct $name:ident { $($field:ident),* $(,)* } $($rest:tt)*) => {
+        impl<'a> IntoStatic for $name<'a> {
+            type Static = $name<'static>;
+            fn into_static(self) -> $name<'static> {
+                $name { $($field: self.$field.into_static()),* }
+            }
+        }
+        into_static! { $($rest)* }
+    };
+    (enum $name:ident {
+        $($variant:ident
+            $(($($arg:ident),*))?
+            $({ $($field:ident),* })?
+        ),* $(,)*
+    } $($rest:tt)*) => {
+        impl<'a> IntoStatic for $name<'a> {
+            type Static = $name<'static>;
+            fn into_static(self) -> $name<'static> {
+                match self {
+                    $($name::$variant
+                        $(($($arg),*))?
+                        $({ $($field),* })?
+                    => $name::$variant
+                        $(($($arg.into_static()),*))?
+                        $({ $($field: $field.into_static()),* })?,
+                    )*
+                }
+            }
+        }
+        into_static! { $($rest)* }
+    };
+}
+
+impl<'a> Main<'a> {
+    /// Converts config into one which doesn't borrow the source text
+    ///
+    /// Parsing borrows strings from the source where possible, use this to
+    /// keep the config after the source text is dropped.
+    pub fn into_owned(self) -> Main<'static> {
+        self.into_static()
+    }
+}
+
+impl<'a> Directive<'a> {
+    /// Converts directive into one which doesn't borrow the source text
+    pub fn into_owned(self) -> Directive<'static> {
+        self.into_static()
+    }
+}
+
+impl<'a> Item<'a> {
+    /// Converts item into one which doesn't borrow the source text
+    pub fn into_owned(self) -> Item<'static> {
+        self.into_static()
+    }
+}
+
+unchanged! {
+    bool, u8, u32, u64, Pos, Span, Arc<Path>, Box<dyn ExtensionDirective>,
+    Size, Duration, Rate, Buffers, QuoteStyle, Listen, Source, RealIpFrom,
+    WorkerProcesses, ErrorLevel, ErrorPageResponse, RewriteFlag, Satisfy,
+    GzipStatic, GzipProxied, ProxyHttpVersion, ProxyNextUpstreamFlag,
+    CacheUseStale, LimitReqDelay, LimitReqZoneKey, SslProtocol, SslVerifyClient,
+}
+
+into_static! {
+    struct Main { directives }
+    struct Directive { position, span, file, item }
+    struct Http { position, directives }
+    struct Server { position, directives }
+    enum IfCondition {
+        NonEmpty(a), Eq(a, b), Neq(a, b), RegEq(a, b, c, d),
+        RegNeq(a, b, c, d), Exists(a), NotExists(a), FileExists(a),
+        FileNotExists(a), DirExists(a), DirNotExists(a), Executable(a),
+        NotExecutable(a),
+    }
+    struct If { position, condition, directives }
+    enum LocationPattern {
+        Prefix(a), Exact(a), FinalPrefix(a), Regex(a, b),
+        RegexInsensitive(a, b), Named(a),
+    }
+    struct Location { position, pattern, directives }
+    struct AddHeader { field, value, always }
+    enum ServerName {
+        Exact(a), Suffix(a), StarSuffix(a), StarPrefix(a), Regex(a),
+    }
+    enum MapPattern {
+        Exact(a), Suffix(a), StarSuffix(a), StarPrefix(a), Regex(a, b),
+    }
+    struct Map {
+        variable, expression, default, hostnames, volatile, includes, patterns,
+    }
+    struct Types { types }
+    struct ErrorPage { codes, response_code, uri }
+    enum Return { Redirect { code, url }, Text { code, text } }
+    struct Rewrite { regex, replacement, flag }
+    enum TryFilesLastOption { Uri(a), NamedLocation(a), Code(a) }
+    struct TryFiles { options, last_option }
+    struct Expires { modified, value }
+    enum ExpiresTime {
+        Off, Epoch, Max, After(a), Before(a), TimeOfDay(a), Variable(a),
+    }
+    enum AuthBasic { Off, Realm(a) }
+    enum AuthRequest { Off, Uri(a) }
+    enum AuthJwt { Off, Realm { realm, token } }
+    enum ProxyCacheValid { Normal(a), Specific(a, b), Any(a) }
+    struct Param { name, value, if_not_empty }
+    enum ProxyRedirect { Default, Off, Replace { redirect, replacement } }
+    enum ProxyCookieRewrite { Off, Replace { from, to } }
+    struct ProxyCachePath {
+        path, levels, use_temp_path, keys_zone, inactive, max_size, options,
+        order,
+    }
+    enum CachePathKey {
+        Levels, UseTempPath, KeysZone, Inactive, MaxSize, Other(a),
+    }
+    struct LimitReqZone { key, name, size, rate, sync, order }
+    struct LimitReq { zone, burst, delay }
+    struct LimitConnZone { key, name, size }
+    enum ProxyStore { On, Off, Path(a) }
+    enum AccessLog { Off, On(a) }
+    struct AccessLogOptions { path, format, buffer, gzip, flush, condition }
+    struct Extension { directive, position, directives }
+    struct LimitExcept { position, methods, directives }
+    enum SslSessionCache { Off, None, Store { builtin, shared } }
+    enum Item {
+        Daemon(a), LoadModule(a), MasterProcess(a), WorkerProcesses(a),
+        Http(a), Server(a), Location(a), Listen(a), ProxyPass(a),
+        ProxySetHeader { field, value }, ProxyMethod(a), ProxyReadTimeout(a),
+        ProxyConnectTimeout(a), ProxyHideHeader(a), ProxyPassHeader(a),
+        ProxyPassRequestBody(a), ProxyPassRequestHeaders(a),
+        ProxyHttpVersion(a), ProxyIgnoreHeaders(a), ProxyInterceptErrors(a),
+        ProxyBuffering(a), ProxyCache(a), ProxyCacheKey(a), ProxyCacheValid(a),
+        ProxyNextUpstreamTries(a), ProxyNextUpstreamTimeout(a),
+        ProxyNextUpstream(a), ProxyRedirect(a), ProxyBuffers(a),
+        ProxyBufferSize(a), ProxyBusyBuffersSize(a), ProxySendTimeout(a),
+        ProxyRequestBuffering(a), ProxySslServerName(a), ProxySslVerify(a),
+        ProxySslName(a), ProxyCookieDomain(a), ProxyCookiePath(a),
+        ProxyCachePath(a), ProxyCacheBypass(a), ProxyNoCache(a),
+        ProxyCacheLock(a), ProxyCacheUseStale(a), ProxyStore(a),
+        FastcgiPass(a), FastcgiParam(a), FastcgiIndex(a),
+        FastcgiSplitPathInfo(a), FastcgiReadTimeout(a), FastcgiBuffers(a),
+        FastcgiInterceptErrors(a), FastcgiCache(a), FastcgiCacheKey(a),
+        FastcgiCacheValid(a), FastcgiCacheBypass(a), FastcgiCacheLock(a),
+        FastcgiCacheMethods(a), FastcgiCacheMinUses(a),
+        FastcgiCacheUseStale(a), FastcgiCachePath(a), FastcgiNoCache(a),
+        FastcgiCacheLockAge(a), FastcgiCacheLockTimeout(a),
+        FastcgiCacheRevalidate(a), FastcgiCacheBackgroundUpdate(a),
+        FastcgiCacheMaxRangeOffset(a), UwsgiPass(a), UwsgiParam(a),
+        UwsgiReadTimeout(a), UwsgiConnectTimeout(a), UwsgiSendTimeout(a),
+        UwsgiBuffering(a), UwsgiBuffers(a), UwsgiInterceptErrors(a),
+        UwsgiNextUpstream(a), UwsgiCache(a), UwsgiCacheKey(a),
+        UwsgiCacheValid(a), ScgiPass(a), ScgiParam(a), ScgiReadTimeout(a),
+        ScgiConnectTimeout(a), ScgiSendTimeout(a), ScgiBuffering(a),
+        ScgiBuffers(a), ScgiInterceptErrors(a), ScgiNextUpstream(a),
+        ScgiCache(a), ScgiCacheKey(a), ScgiCacheValid(a), GrpcPass(a),
+        GrpcSetHeader { field, value }, GrpcReadTimeout(a),
+        GrpcConnectTimeout(a), GrpcSendTimeout(a), GrpcInterceptErrors(a),
+        GrpcNextUpstream(a), GrpcSslCertificate(a), GrpcSslCertificateKey(a),
+        GrpcSslProtocols(a), GrpcSslCiphers(a), GrpcSslVerify(a),
+        GrpcSslTrustedCertificate(a), GrpcSslServerName(a), GrpcSslName(a),
+        LimitReqZone(a), LimitReq(a), LimitReqStatus(a), LimitReqLogLevel(a),
+        LimitConnZone(a), LimitConn { zone, number }, LimitConnStatus(a),
+        LimitRate(a), LimitRateAfter(a), Gzip(a), GzipStatic(a),
+        GzipProxied(a), AddHeader(a), Expires(a), Root(a), Alias(a),
+        ErrorPage(a), DefaultType(a), Types(a), ErrorLog { file, level },
+        Rewrite(a), Return(a), If(a), TryFiles(a), ServerName(a),
+        Set { variable, value }, Map(a), ClientMaxBodySize(a), Include(a),
+        EmptyGif, Internal, LimitExcept(a), Etag(a), RecursiveErrorPages(a),
+        ChunkedTransferEncoding(a), KeepaliveTimeout(a, b), ServerTokens(a),
+        SslCertificate(a), SslCertificateKey(a), SslProtocols(a),
+        SslCiphers(a), SslPreferServerCiphers(a), SslSessionCache(a),
+        SslSessionTimeout(a), SslSessionTickets(a), SslStapling(a),
+        SslStaplingVerify(a), SslTrustedCertificate(a), SslDhparam(a),
+        SslEcdhCurve(a), SslClientCertificate(a), SslVerifyClient(a),
+        SslVerifyDepth(a), Ssl(a), Http2(a), RewriteByLuaFile(a),
+        BalancerByLuaFile(a), AccessByLuaFile(a), HeaderFilterByLuaFile(a),
+        ContentByLuaFile(a), BodyFilterByLuaFile(a), LogByLuaFile(a),
+        LuaNeedRequestBody(a), SslCertificateByLuaFile(a),
+        SslSessionFetchByLuaFile(a), SslSessionStoreByLuaFile(a), Allow(a),
+        Deny(a), AuthBasic(a), AuthBasicUserFile(a), AuthRequest(a),
+        AuthRequestSet { variable, value }, Satisfy(a), AuthJwt(a),
+        AccessLog(a), RealIpHeader(a), RealIpRecursive(a), SetRealIpFrom(a),
+        Index(a), Extension(a),
+    }
+}
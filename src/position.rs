@@ -1,4 +1,5 @@
 use std::fmt;
+use std::ops::Range;
 
 /// Original position of element in source code
 #[derive(PartialOrd, Ord, PartialEq, Eq, Clone, Copy, Default)]
@@ -6,7 +7,57 @@ pub struct Pos {
     /// One-based line number
     pub line: usize,
     /// One-based column number
+    ///
+    /// Columns are counted in characters, tab is a single character.
     pub column: usize,
+    /// Zero-based byte offset from the start of the source text
+    pub offset: usize,
+}
+
+/// Range of the source code occupied by an element
+///
+/// `end` points right after the last character of the element, so
+/// `&text[span.range()]` is exactly the text of the element.
+#[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Span {
+    /// Position of the first character
+    pub start: Pos,
+    /// Position right after the last character
+    pub end: Pos,
+}
+
+impl Span {
+    /// Byte range of the element in the source text
+    pub fn range(&self) -> Range<usize> {
+        self.start.offset..self.end.offset
+    }
+
+    /// Span of `part`, which must be a substring of single-line `text`
+    /// located at this span
+    pub(crate) fn sub(&self, text: &str, part: &str) -> Span {
+        let skip = part.as_ptr() as usize - text.as_ptr() as usize;
+        let start = shift(self.start, &text[..skip]);
+        Span { start, end: shift(start, part) }
+    }
+
+    /// Span of `part`, which must be a suffix of the single-line text
+    /// located at this span
+    pub(crate) fn tail(&self, part: &str) -> Span {
+        let start = Pos {
+            line: self.end.line,
+            column: self.end.column - part.chars().count(),
+            offset: self.end.offset - part.len(),
+        };
+        Span { start, end: self.end }
+    }
+}
+
+fn shift(pos: Pos, text: &str) -> Pos {
+    Pos {
+        line: pos.line,
+        column: pos.column + text.chars().count(),
+        offset: pos.offset + text.len(),
+    }
 }
 
 impl fmt::Debug for Pos {
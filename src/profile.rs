@@ -0,0 +1,417 @@
+//! Version- and module-aware checks
+//!
+//! Directives appear and disappear across nginx releases and most of them
+//! are implemented by modules which may be left out of the build.
+//! [`ParserProfile`] describes the target build, so the config can be
+//! checked against it:
+//!
+//! ```rust
+//! # use clia_nginx_config::profile::ParserProfile;
+//! let profile = ParserProfile::from_nginx_v("\
+//!     nginx version: nginx/1.26.0
+//!     configure arguments: --with-http_ssl_module --with-http_v2_module
+//! ").unwrap();
+//! assert!(profile.parse_main("http2 on;").is_ok());
+//! assert!(profile.parse_main("ssl on;").is_err());
+//! ```
+//!
+//! [`ParserProfile`]: struct.ParserProfile.html
+use std::collections::HashSet;
+use std::path::Path;
+
+use ast::{Directive, Item, HttpExt, Main};
+use error::ConfigError;
+use grammar::{parse_main, value_to_path};
+use lint::{Warning, DEPRECATED_DIRECTIVE};
+use registry::{self, Version};
+use units::ParseUnitError;
+use validate::Error;
+use visitors::DirectiveIter;
+
+
+/// Modules built by `./configure` without options
+const STANDARD_MODULES: &[&str] = &[
+    "ngx_core_module",
+    "ngx_conf_module",
+    "ngx_events_module",
+    "ngx_event_core_module",
+    "ngx_http_module",
+    "ngx_http_core_module",
+    "ngx_http_log_module",
+    "ngx_http_upstream_module",
+    "ngx_http_index_module",
+    "ngx_http_headers_module",
+    "ngx_http_rewrite_module",
+    "ngx_http_gzip_module",
+    "ngx_http_proxy_module",
+    "ngx_http_fastcgi_module",
+    "ngx_http_uwsgi_module",
+    "ngx_http_scgi_module",
+    "ngx_http_grpc_module",
+    "ngx_http_limit_req_module",
+    "ngx_http_limit_conn_module",
+    "ngx_http_access_module",
+    "ngx_http_auth_basic_module",
+    "ngx_http_map_module",
+    "ngx_http_empty_gif_module",
+    "ngx_http_upstream_hash_module",
+    "ngx_http_upstream_ip_hash_module",
+    "ngx_http_upstream_least_conn_module",
+    "ngx_http_upstream_keepalive_module",
+];
+
+/// Directory name prefixes of well-known third-party modules
+const THIRD_PARTY: &[(&str, &str)] = &[
+    ("lua-nginx-module", "ngx_http_lua_module"),
+    ("ngx_lua-", "ngx_http_lua_module"),
+];
+
+const CONFIGURE_ARGS: &str = "configure arguments:";
+const V2_MODULE: &str = "ngx_http_v2_module";
+const SPDY_MODULE: &str = "ngx_http_spdy_module";
+const SSL_MODULE: &str = "ngx_http_ssl_module";
+/// `listen ... http2` and `ngx_http_v2_module` are added
+const HTTP2_ADDED: Version = Version::new(1, 9, 5);
+/// `http2 on;` replaces `listen ... http2`
+const HTTP2_DIRECTIVE: Version = Version::new(1, 25, 1);
+/// `ssl on;` is deprecated in favor of `listen ... ssl`
+const SSL_DEPRECATED: Version = Version::new(1, 15, 0);
+
+
+/// Target nginx build: version and enabled modules
+///
+/// By default any version and all modules are accepted. Modules loaded
+/// by `load_module` directives of the checked config are added to the
+/// enabled ones.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParserProfile {
+    version: Option<Version>,
+    modules: Option<HashSet<String>>,
+}
+
+impl ParserProfile {
+    /// Profile which accepts everything the parser supports
+    pub fn new() -> ParserProfile {
+        ParserProfile::default()
+    }
+
+    /// Profile of the build described by `nginx -V` output
+    ///
+    /// Version is taken from the `nginx version:` line, for OpenResty,
+    /// Tengine and Angie it's the version of the underlying nginx. Output of
+    /// other forks is rejected, as their version says nothing about the
+    /// nginx one. Modules are the
+    /// standard ones adjusted by `--with-*` and `--without-*` options of
+    /// the `configure arguments:` line. Dynamic modules are enabled only
+    /// when loaded by `load_module`. Of the `--add-module` ones only
+    /// well-known modules (like lua) are recognized.
+    pub fn from_nginx_v(output: &str) -> Result<ParserProfile, ParseUnitError>
+    {
+        let mut profile = ParserProfile::new();
+        profile.standard_modules();
+        let mut has_version = false;
+        for line in output.lines().map(|l| l.trim()) {
+            if line.contains(" version: ") {
+                profile.version = Some(nginx_version(line)?);
+                has_version = true;
+            } else if let Some(args) = line.strip_prefix(CONFIGURE_ARGS) {
+                for arg in args.split_whitespace() {
+                    profile.configure_option(arg);
+                }
+            }
+        }
+        if !has_version {
+            return Err(ParseUnitError::new("version",
+                output.lines().next().unwrap_or("")));
+        }
+        Ok(profile)
+    }
+
+    /// Set target version of nginx
+    pub fn version(&mut self, version: Version) -> &mut Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// Enable only the listed modules, e.g. `ngx_http_proxy_module`
+    pub fn modules<I, S>(&mut self, modules: I) -> &mut Self
+        where I: IntoIterator<Item=S>,
+              S: Into<String>,
+    {
+        self.modules = Some(modules.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Enable only modules built by `./configure` without options
+    pub fn standard_modules(&mut self) -> &mut Self {
+        self.modules(STANDARD_MODULES.iter().cloned())
+    }
+
+    /// Enable a module in addition to the enabled ones
+    pub fn enable_module(&mut self, name: &str) -> &mut Self {
+        if let Some(ref mut modules) = self.modules {
+            modules.insert(name.to_string());
+        }
+        self
+    }
+
+    /// Disable a module
+    pub fn disable_module(&mut self, name: &str) -> &mut Self {
+        let modules = self.modules.get_or_insert_with(|| {
+            registry::directives().iter()
+                .map(|d| d.module.to_string())
+                .collect()
+        });
+        modules.remove(name);
+        self
+    }
+
+    /// Enable modules loaded by `load_module` directives
+    pub fn load_modules(&mut self, directives: &[Directive]) -> &mut Self {
+        for dir in directives {
+            if let Item::LoadModule(ref path) = dir.item {
+                let name = value_to_path(path).and_then(|p| {
+                    Path::new(&p).file_stem()
+                        .map(|s| s.to_string_lossy().into_owned())
+                });
+                if let Some(name) = name {
+                    self.enable_module(&name);
+                }
+            }
+        }
+        self
+    }
+
+    /// Target version of nginx, `None` if any version is accepted
+    pub fn target_version(&self) -> Option<Version> {
+        self.version
+    }
+
+    /// Whether the module is enabled
+    pub fn is_enabled(&self, module: &str) -> bool {
+        self.modules.as_ref().map_or(true, |m| m.contains(module))
+    }
+
+    /// Whether the directive is available in the target build
+    ///
+    /// Directives unknown to the [`registry`](../registry/index.html)
+    /// are considered available.
+    pub fn is_available(&self, name: &str) -> bool {
+        registry::lookup(name).map_or(true, |info| {
+            self.is_enabled(info.module) &&
+            self.version.map_or(true, |v| info.available_in(v))
+        })
+    }
+
+    /// Parse the main config and reject directives which are not available
+    /// in the target build
+    ///
+    /// Errors of [`validate`](#method.validate) are returned as
+    /// `ConfigError::Invalid`.
+    pub fn parse_main<'a>(&self, text: &'a str)
+        -> Result<Main<'a>, ConfigError>
+    {
+        let main = parse_main(text)?;
+        let errors = self.validate(&main.directives);
+        if !errors.is_empty() {
+            return Err(ConfigError::Invalid { errors });
+        }
+        Ok(main)
+    }
+
+    /// Find directives and `listen` parameters which are not available in
+    /// the target build
+    pub fn validate(&self, directives: &[Directive]) -> Vec<Error> {
+        let profile = self.with_loaded(directives);
+        let mut errors = Vec::new();
+        for dir in DirectiveIter::depth_first(directives) {
+            profile.check_directive(dir, &mut errors);
+            if let Item::Listen(ref lst) = dir.item {
+                profile.check_listen(dir, lst.ssl, lst.ext.as_ref(),
+                    &mut errors);
+            }
+        }
+        errors
+    }
+
+    /// Find directives and parameters which still work in the target
+    /// version but are deprecated
+    ///
+    /// If version isn't set, deprecations of the latest nginx are reported.
+    /// All warnings have the [`DEPRECATED_DIRECTIVE`] rule.
+    ///
+    /// [`DEPRECATED_DIRECTIVE`]: ../lint/constant.DEPRECATED_DIRECTIVE.html
+    pub fn lint(&self, directives: &[Directive]) -> Vec<Warning> {
+        let since = |v: Version| self.version.map_or(true, |cur| cur >= v);
+        let mut warnings = Vec::new();
+        for dir in DirectiveIter::depth_first(directives) {
+            let message = match dir.item {
+                Item::Listen(ref lst)
+                if lst.ext == Some(HttpExt::Http2) && since(HTTP2_DIRECTIVE)
+                => format!("the http2 parameter of listen is deprecated \
+                    since nginx {}, use `http2 on;`", HTTP2_DIRECTIVE),
+                Item::Ssl(..)
+                if since(SSL_DEPRECATED) && self.is_available("ssl")
+                => format!("ssl directive is deprecated since nginx {}, \
+                    use `listen ... ssl`", SSL_DEPRECATED),
+                _ => continue,
+            };
+            warnings.push(Warning {
+                position: dir.position,
+                rule: DEPRECATED_DIRECTIVE,
+                message,
+            });
+        }
+        warnings
+    }
+
+    fn with_loaded(&self, directives: &[Directive]) -> ParserProfile {
+        let mut profile = self.clone();
+        profile.load_modules(directives);
+        profile
+    }
+
+    fn configure_option(&mut self, arg: &str) {
+        if let Some(name) = arg.strip_prefix("--without-") {
+            if let Some(module) = module_name(name) {
+                self.disable_module(&module);
+            }
+        } else if let Some(name) = arg.strip_prefix("--with-") {
+            // dynamic modules are enabled by `load_module`
+            if !name.ends_with("=dynamic") {
+                if let Some(module) = module_name(name) {
+                    self.enable_module(&module);
+                }
+            }
+        } else if let Some(path) = arg.strip_prefix("--add-module=") {
+            let dir = Path::new(path).file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            if let Some(&(_, module)) = THIRD_PARTY.iter()
+                .find(|&&(prefix, _)| dir.starts_with(prefix))
+            {
+                self.enable_module(module);
+            }
+        }
+    }
+
+    fn check_directive(&self, dir: &Directive, errors: &mut Vec<Error>) {
+        let name = dir.item.directive_name();
+        let info = match registry::lookup(name) {
+            Some(info) => info,
+            None => return,
+        };
+        if let Some(version) = self.version {
+            if let Some(min) = info.min_version.filter(|&min| version < min) {
+                errors.push(Error::new(dir.position, format!(
+                    "{} requires nginx {} or later", name, min)));
+                return;
+            }
+            if let Some(max) = info.max_version.filter(|&max| version >= max) {
+                errors.push(Error::new(dir.position, format!(
+                    "{} was removed in nginx {}", name, max)));
+                return;
+            }
+        }
+        if !self.is_enabled(info.module) {
+            errors.push(Error::new(dir.position, format!(
+                "{} requires {} which is not enabled", name, info.module)));
+        }
+    }
+
+    fn check_listen(&self, dir: &Directive, ssl: bool, ext: Option<&HttpExt>,
+        errors: &mut Vec<Error>)
+    {
+        let mut require = |module: &str, param: &str| {
+            if !self.is_enabled(module) {
+                errors.push(Error::new(dir.position, format!(
+                    "listen ... {} requires {} which is not enabled",
+                    param, module)));
+            }
+        };
+        if ssl {
+            require(SSL_MODULE, "ssl");
+        }
+        match ext {
+            Some(&HttpExt::Spdy) => {
+                // http2 has replaced spdy in the same release
+                if self.version.map_or(false, |v| v >= HTTP2_ADDED) {
+                    errors.push(Error::new(dir.position, format!(
+                        "listen ... spdy was removed in nginx {}",
+                        HTTP2_ADDED)));
+                } else {
+                    require(SPDY_MODULE, "spdy");
+                }
+            }
+            Some(&HttpExt::Http2) => {
+                if self.version.map_or(false, |v| v < HTTP2_ADDED) {
+                    errors.push(Error::new(dir.position, format!(
+                        "listen ... http2 requires nginx {} or later",
+                        HTTP2_ADDED)));
+                } else {
+                    require(V2_MODULE, "http2");
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+/// Module name from the `configure` option, e.g. `http_ssl_module`
+fn module_name(option: &str) -> Option<String> {
+    let option = option.split('=').next().unwrap_or(option);
+    match option {
+        "stream" | "mail" => Some(format!("ngx_{}_module", option)),
+        _ if option.ends_with("_module") => Some(format!("ngx_{}", option)),
+        _ => None,
+    }
+}
+
+/// nginx releases Angie versions are based on, by Angie `(major, minor)`
+const ANGIE_BASE: &[((u32, u32), Version)] = &[
+    ((1, 0), Version::new(1, 23, 1)),
+    ((1, 1), Version::new(1, 23, 3)),
+    ((1, 2), Version::new(1, 23, 4)),
+    ((1, 3), Version::new(1, 25, 1)),
+    ((1, 4), Version::new(1, 25, 3)),
+    ((1, 5), Version::new(1, 25, 4)),
+];
+
+/// Version of nginx from the first line of `nginx -V`
+///
+/// Forks report their own version, so the `nginx/x.y.z` part is preferred
+/// (Tengine prints it in parentheses). OpenResty follows the nginx version
+/// in its first three numbers, Angie versions are looked up in a table.
+/// Other products, and Angie releases missing from the table, are an
+/// error rather than a guess: set the version with
+/// [`ParserProfile::version`](struct.ParserProfile.html#method.version).
+fn nginx_version(line: &str) -> Result<Version, ParseUnitError> {
+    let err = || ParseUnitError::new("version", line);
+    let nginx = line.match_indices("nginx/").map(|(idx, _)| idx)
+        .find(|&idx| !line[..idx].ends_with(char::is_alphanumeric));
+    let (product, version) = match nginx {
+        Some(idx) => ("nginx", &line[idx + "nginx/".len()..]),
+        None => {
+            let tail = line.split(": ").nth(1).ok_or_else(err)?;
+            let mut pair = tail.splitn(2, '/');
+            let product = pair.next().unwrap_or("");
+            (product, pair.next().ok_or_else(err)?)
+        }
+    };
+    let version = version
+        .split(|c: char| !c.is_ascii_digit() && c != '.')
+        .next().unwrap_or("");
+    let parts = version.split('.').take(3)
+        .map(|p| p.parse::<u32>().map_err(|_| err()))
+        .collect::<Result<Vec<_>, _>>()?;
+    match (&product.to_ascii_lowercase()[..], &parts[..]) {
+        ("nginx", &[major, minor, patch]) |
+        ("openresty", &[major, minor, patch])
+        => Ok(Version::new(major, minor, patch)),
+        ("angie", &[major, minor, _]) => ANGIE_BASE.iter()
+            .find(|&&(angie, _)| angie == (major, minor))
+            .map(|&(_, nginx)| nginx)
+            .ok_or_else(err),
+        _ => Err(err()),
+    }
+}
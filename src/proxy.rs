@@ -1,15 +1,301 @@
 use combine::{Parser};
-use combine::{choice, many1};
+use combine::{choice, many, many1, optional};
 use combine::error::StreamError;
 use combine::easy::Error;
 
 use ast::{self, Item};
 use helpers::{semi, ident, string};
-use tokenizer::TokenStream;
-use grammar::{value, bool, Code};
+use tokenizer::{TokenStream, Token};
+use grammar::{value, bool, size, duration, raw, Code};
 
 
-pub fn directives<'a>() -> impl Parser<Output=Item, Input=TokenStream<'a>> {
+/// Arguments of `proxy_cache_valid` and similar directives
+pub fn cache_valid<'a>()
+    -> impl Parser<Output=ast::ProxyCacheValid<'a>, Input=TokenStream<'a>>
+{
+    many1(value())
+    .and_then(|mut v: Vec<_>| {
+        use ast::ProxyCacheValid::*;
+        use value::Item::*;
+        let time = v.pop().unwrap();
+        if v.len() == 0 {
+            return Ok(Normal(time));
+        }
+        let mut codes = Vec::new();
+        let items = v.len();
+        for item in v {
+            match &item.data[..] {
+                [Literal(x)] if x == "any" => {
+                    if items == 1 {
+                        return Ok(Any(time));
+                    } else {
+                        return Err(Error::unexpected_message(
+                            "`any` must be sole argument before time. \
+                             It's not allowed to combine `any` and \
+                             other codes"));
+                    }
+                }
+                [Literal(x)] => {
+                    match Code::parse(x) {
+                        Ok(code) => {
+                            codes.push(code.as_code())
+                        }
+                        Err(_) => {
+                            return Err(Error::unexpected_message(
+                                format!("invalid http code {:?}", x)));
+                        }
+                    }
+                }
+                _ => {
+                    return Err(Error::unexpected_message(
+                        "variables aren't allowed in list of codes"));
+                }
+            }
+        }
+        return Ok(Specific(codes, time));
+    })
+}
+
+/// Arguments of `proxy_next_upstream` and similar directives
+pub fn next_upstream<'a>()
+    -> impl Parser<Output=Vec<ast::ProxyNextUpstreamFlag>,
+                   Input=TokenStream<'a>>
+{
+    many1(string().and_then(|v| {
+        use ast::ProxyNextUpstreamFlag::*;
+        match v.value {
+            "error" => Ok(Error),
+            "timeout" => Ok(Timeout),
+            "invalid_header" => Ok(InvalidHeader),
+            "http_500" => Ok(Http500),
+            "http_502" => Ok(Http502),
+            "http_503" => Ok(Http503),
+            "http_504" => Ok(Http504),
+            "http_403" => Ok(Http403),
+            "http_404" => Ok(Http404),
+            "http_429" => Ok(Http429),
+            "non_idempotent" => Ok(NonIdempotent),
+            "off" => Ok(Off),
+            _ => Err(::combine::easy::Error::unexpected_message(
+                "invalid proxy upstream flag")),
+        }
+    }))
+}
+
+/// Arguments of `proxy_cache_use_stale` and similar directives
+pub fn cache_use_stale<'a>()
+    -> impl Parser<Output=Vec<ast::CacheUseStale>, Input=TokenStream<'a>>
+{
+    many1(string().and_then(|v| {
+        use ast::CacheUseStale::*;
+        match v.value {
+            "error" => Ok(Error),
+            "timeout" => Ok(Timeout),
+            "invalid_header" => Ok(InvalidHeader),
+            "updating" => Ok(Updating),
+            "http_500" => Ok(Http500),
+            "http_502" => Ok(Http502),
+            "http_503" => Ok(Http503),
+            "http_504" => Ok(Http504),
+            "http_403" => Ok(Http403),
+            "http_404" => Ok(Http404),
+            "http_429" => Ok(Http429),
+            "off" => Ok(Off),
+            _ => Err(::combine::easy::Error::unexpected_message(
+                "invalid cache use stale flag")),
+        }
+    }))
+}
+
+/// Arguments of `proxy_buffers` and similar directives
+pub fn buffers<'a>()
+    -> impl Parser<Output=ast::Buffers, Input=TokenStream<'a>>
+{
+    string().and_then(|t| t.value.parse::<u32>())
+    .and(size())
+    .map(|(number, size)| ast::Buffers { number, size })
+}
+
+/// `fastcgi_param` and similar directives
+pub fn param<'a>(name: &'static str)
+    -> impl Parser<Output=ast::Param<'a>, Input=TokenStream<'a>>
+{
+    ident(name)
+    .with((
+        value(),
+        value(),
+        optional(ident("if_not_empty")),
+    ))
+    .map(|(name, value, flag)| ast::Param {
+        name, value,
+        if_not_empty: flag.is_some(),
+    })
+    .skip(semi())
+}
+
+fn proxy_redirect<'a>()
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
+{
+    use ast::ProxyRedirect::*;
+    ident("proxy_redirect").with(choice((
+        ident("default").map(|_| Default),
+        ident("off").map(|_| Off),
+        value().and(value())
+            .map(|(redirect, replacement)| Replace { redirect, replacement }),
+    )))
+    .skip(semi())
+    .map(Item::ProxyRedirect)
+}
+
+fn cookie_rewrite<'a>()
+    -> impl Parser<Output=ast::ProxyCookieRewrite<'a>, Input=TokenStream<'a>>
+{
+    use ast::ProxyCookieRewrite::*;
+    choice((
+        ident("off").map(|_| Off),
+        value().and(value()).map(|(from, to)| Replace { from, to }),
+    ))
+}
+
+pub fn cache_path<'a>(name: &'static str)
+    -> impl Parser<Output=ast::ProxyCachePath<'a>, Input=TokenStream<'a>>
+{
+    ident(name)
+    .with(value())
+    .and(many(string()))
+    .and_then(move |(path, items): (_, Vec<Token<'a>>)| {
+        let mut levels = None;
+        let mut use_temp_path = None;
+        let mut keys_zone = None;
+        let mut inactive = None;
+        let mut max_size = None;
+        let mut options = Vec::new();
+        let mut order = Vec::new();
+        for tok in items {
+            let mut pair = tok.value.splitn(2, '=');
+            let key = pair.next().unwrap();
+            let val = pair.next().ok_or_else(|| {
+                Error::unexpected_message(format!(
+                    "{} option {:?} must be in form \
+                     `name=value`", name, tok.value))
+            })?;
+            order.push(match key {
+                "levels" => {
+                    levels = Some(val.into());
+                    ast::CachePathKey::Levels
+                }
+                "use_temp_path" => {
+                    use_temp_path = Some(match val {
+                        "on" => true,
+                        "off" => false,
+                        _ => return Err(Error::unexpected_message(
+                            "use_temp_path must be either `on` or `off`")),
+                    });
+                    ast::CachePathKey::UseTempPath
+                }
+                "keys_zone" => {
+                    let mut zone = val.splitn(2, ':');
+                    let name = zone.next().unwrap();
+                    let size = zone.next().ok_or_else(|| {
+                        Error::unexpected_message("keys_zone must be in \
+                            form `keys_zone=name:size`")
+                    })?;
+                    keys_zone = Some((name.into(), size.parse()?));
+                    ast::CachePathKey::KeysZone
+                }
+                "inactive" => {
+                    inactive = Some(val.parse()?);
+                    ast::CachePathKey::Inactive
+                }
+                "max_size" => {
+                    max_size = Some(val.parse()?);
+                    ast::CachePathKey::MaxSize
+                }
+                _ => {
+                    options.push((key.into(), val.into()));
+                    ast::CachePathKey::Other(key.into())
+                }
+            });
+        }
+        let keys_zone = keys_zone.ok_or_else(|| {
+            Error::unexpected_message(
+                format!("{} requires keys_zone", name))
+        })?;
+        Ok(ast::ProxyCachePath {
+            path, levels, use_temp_path, keys_zone, inactive, max_size,
+            options, order,
+        })
+    })
+    .skip(semi())
+}
+
+fn proxy_store<'a>() -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>> {
+    use ast::ProxyStore::*;
+    ident("proxy_store").with(choice((
+        ident("on").map(|_| On),
+        ident("off").map(|_| Off),
+        value().map(Path),
+    )))
+    .skip(semi())
+    .map(Item::ProxyStore)
+}
+
+fn cache_directives<'a>()
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
+{
+    choice((
+        ident("proxy_cache").with(value()).skip(semi())
+            .map(Item::ProxyCache),
+        ident("proxy_cache_key").with(value()).skip(semi())
+            .map(Item::ProxyCacheKey),
+        ident("proxy_cache_valid").with(cache_valid())
+            .skip(semi()).map(Item::ProxyCacheValid),
+        cache_path("proxy_cache_path").map(Item::ProxyCachePath),
+        ident("proxy_cache_bypass").with(many1(value())).skip(semi())
+            .map(Item::ProxyCacheBypass),
+        ident("proxy_no_cache").with(many1(value())).skip(semi())
+            .map(Item::ProxyNoCache),
+        ident("proxy_cache_lock").with(bool()).skip(semi())
+            .map(Item::ProxyCacheLock),
+        ident("proxy_cache_use_stale").with(cache_use_stale()).skip(semi())
+            .map(Item::ProxyCacheUseStale),
+        proxy_store(),
+    ))
+}
+
+fn buffer_directives<'a>()
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
+{
+    choice((
+        ident("proxy_buffering").with(bool()).skip(semi())
+            .map(Item::ProxyBuffering),
+        ident("proxy_request_buffering").with(bool()).skip(semi())
+            .map(Item::ProxyRequestBuffering),
+        ident("proxy_buffers").with(buffers()).skip(semi())
+            .map(Item::ProxyBuffers),
+        ident("proxy_buffer_size").with(size()).skip(semi())
+            .map(Item::ProxyBufferSize),
+        ident("proxy_busy_buffers_size").with(size()).skip(semi())
+            .map(Item::ProxyBusyBuffersSize),
+    ))
+}
+
+fn ssl_directives<'a>()
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
+{
+    choice((
+        ident("proxy_ssl_server_name").with(bool()).skip(semi())
+            .map(Item::ProxySslServerName),
+        ident("proxy_ssl_verify").with(bool()).skip(semi())
+            .map(Item::ProxySslVerify),
+        ident("proxy_ssl_name").with(value()).skip(semi())
+            .map(Item::ProxySslName),
+    ))
+}
+
+pub fn directives<'a>()
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
+{
     choice((
         ident("proxy_pass").with(value()).skip(semi())
             .map(Item::ProxyPass),
@@ -18,57 +304,20 @@ pub fn directives<'a>() -> impl Parser<Output=Item, Input=TokenStream<'a>> {
             .map(|(field, value)| Item::ProxySetHeader { field, value }),
         ident("proxy_method").with(value()).skip(semi())
             .map(Item::ProxyMethod),
-        ident("proxy_cache").with(value()).skip(semi())
-            .map(Item::ProxyCache),
-        ident("proxy_cache_key").with(value()).skip(semi())
-            .map(Item::ProxyCacheKey),
-        ident("proxy_cache_valid")
-            .with(many1(value()))
-            .and_then(|mut v: Vec<_>| {
-                use ast::ProxyCacheValid::*;
-                use value::Item::*;
-                let time = v.pop().unwrap();
-                if v.len() == 0 {
-                    return Ok(Normal(time));
-                }
-                let mut codes = Vec::new();
-                let items = v.len();
-                for item in v {
-                    match &item.data[..] {
-                        [Literal(x)] if x == "any" => {
-                            if items == 1 {
-                                return Ok(Any(time));
-                            } else {
-                                return Err(Error::unexpected_message(
-                                    "`any` must be sole argument before time. \
-                                     It's not allowed to combine `any` and \
-                                     other codes"));
-                            }
-                        }
-                        [Literal(x)] => {
-                            match Code::parse(x) {
-                                Ok(code) => {
-                                    codes.push(code.as_code())
-                                }
-                                Err(_) => {
-                                    return Err(Error::unexpected_message(
-                                        format!("invalid http code {:?}", x)));
-                                }
-                            }
-                        }
-                        _ => {
-                            return Err(Error::unexpected_message(
-                                "variables aren't allowed in list of codes"));
-                        }
-                    }
-                }
-                return Ok(Specific(codes, time));
-            })
-            .skip(semi()).map(Item::ProxyCacheValid),
-        ident("proxy_read_timeout").with(value()).skip(semi())
+        proxy_redirect(),
+        ident("proxy_cookie_domain").with(cookie_rewrite()).skip(semi())
+            .map(Item::ProxyCookieDomain),
+        ident("proxy_cookie_path").with(cookie_rewrite()).skip(semi())
+            .map(Item::ProxyCookiePath),
+        cache_directives(),
+        buffer_directives(),
+        ssl_directives(),
+        ident("proxy_read_timeout").with(duration()).skip(semi())
             .map(Item::ProxyReadTimeout),
-        ident("proxy_connect_timeout").with(value()).skip(semi())
+        ident("proxy_connect_timeout").with(duration()).skip(semi())
             .map(Item::ProxyConnectTimeout),
+        ident("proxy_send_timeout").with(duration()).skip(semi())
+            .map(Item::ProxySendTimeout),
         ident("proxy_hide_header").with(value()).skip(semi())
             .map(Item::ProxyHideHeader),
         ident("proxy_pass_header").with(value()).skip(semi())
@@ -79,12 +328,7 @@ pub fn directives<'a>() -> impl Parser<Output=Item, Input=TokenStream<'a>> {
             .map(Item::ProxyPassRequestBody),
         ident("proxy_intercept_errors").with(bool()).skip(semi())
             .map(Item::ProxyInterceptErrors),
-        ident("proxy_buffering").with(bool()).skip(semi())
-            .map(Item::ProxyBuffering),
-        ident("proxy_ignore_headers").with(many1(string())).skip(semi())
-            .map(|v: Vec<_>| {
-                v.into_iter().map(|v| v.value.to_string()).collect()
-            })
+        ident("proxy_ignore_headers").with(many1(raw())).skip(semi())
             .map(Item::ProxyIgnoreHeaders),
         ident("proxy_http_version")
             .with(string()).and_then(|v| {
@@ -97,31 +341,12 @@ pub fn directives<'a>() -> impl Parser<Output=Item, Input=TokenStream<'a>> {
             })
             .skip(semi())
             .map(Item::ProxyHttpVersion),
-        ident("proxy_next_upstream")
-            .with(many1(string().and_then(|v| {
-                use ast::ProxyNextUpstreamFlag::*;
-                match v.value {
-                    "error" => Ok(Error),
-                    "timeout" => Ok(Timeout),
-                    "invalid_header" => Ok(InvalidHeader),
-                    "http_500" => Ok(Http500),
-                    "http_502" => Ok(Http502),
-                    "http_503" => Ok(Http503),
-                    "http_504" => Ok(Http504),
-                    "http_403" => Ok(Http403),
-                    "http_404" => Ok(Http404),
-                    "http_429" => Ok(Http429),
-                    "non_idempotent" => Ok(NonIdempotent),
-                    "off" => Ok(Off),
-                    _ => Err(::combine::easy::Error::unexpected_message(
-                        "invalid proxy upstream flag")),
-                }
-            })))
+        ident("proxy_next_upstream").with(next_upstream())
             .skip(semi())
             .map(Item::ProxyNextUpstream),
         ident("proxy_next_upstream_tries").with(value()).skip(semi())
             .map(Item::ProxyNextUpstreamTries),
-        ident("proxy_next_upstream_timeout").with(value()).skip(semi())
+        ident("proxy_next_upstream_timeout").with(duration()).skip(semi())
             .map(Item::ProxyNextUpstreamTimeout),
     ))
 }
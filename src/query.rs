@@ -0,0 +1,327 @@
+//! Structural path queries over the AST
+//!
+//! A query is a list of steps separated by `/`. Each step is a directive
+//! name (or `*` for any directive) optionally followed by predicates in
+//! square brackets:
+//!
+//! * `[args]` matches the arguments of the directive itself, e.g.
+//!   `location[^~ /static]` or `location[= /exact]`
+//! * `[name=value]` matches blocks having a direct child `name` with the
+//!   argument `value` (or with exactly `value` as all arguments), e.g.
+//!   `server[server_name=example.com]` or `server[listen=443]`
+//!
+//! Steps separated by `//` instead of `/` match at any depth, so
+//! `//proxy_pass` finds all `proxy_pass` directives in the config, and
+//! `http/server[server_name=example.com]/location[^~ /static]/root`
+//! finds `root` in a single location.
+use std::fmt;
+use std::str::FromStr;
+
+use ast::Directive;
+use display::arguments;
+
+
+/// Error parsing query
+#[derive(Fail, Debug, Clone, PartialEq, Eq)]
+#[fail(display="invalid query at char {}: {}", position, message)]
+pub struct Error {
+    /// Offset of the problem in the query string
+    pub position: usize,
+    /// Human-readable description of the problem
+    pub message: String,
+}
+
+/// A parsed query
+///
+/// Use [`Query::parse`](#method.parse) or `str::parse` to create one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Step {
+    /// Step matches at any depth below the previous one
+    descendant: bool,
+    /// Directive name, `None` for `*`
+    name: Option<String>,
+    predicates: Vec<Predicate>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Predicate {
+    Args(String),
+    Child(String, String),
+}
+
+/// A directive found by query
+#[derive(Debug, Clone)]
+pub struct Match<'a> {
+    /// The matching directive
+    pub directive: &'a Directive<'a>,
+    /// Enclosing blocks, outermost first
+    pub ancestors: Vec<&'a Directive<'a>>,
+}
+
+impl Error {
+    fn new<S: Into<String>>(position: usize, message: S) -> Error {
+        Error { position, message: message.into() }
+    }
+}
+
+impl Predicate {
+    fn matches(&self, dir: &Directive) -> bool {
+        match *self {
+            Predicate::Args(ref args) => arguments(dir) == *args,
+            Predicate::Child(ref name, ref value) => {
+                dir.item.children().unwrap_or(&[]).iter()
+                .filter(|ch| ch.item.directive_name() == name)
+                .any(|ch| {
+                    let args = arguments(ch);
+                    args == *value ||
+                        args.split_whitespace().any(|a| a == value)
+                })
+            }
+        }
+    }
+}
+
+impl Step {
+    fn matches(&self, dir: &Directive) -> bool {
+        self.name.as_ref()
+            .map_or(true, |n| dir.item.directive_name() == n) &&
+        self.predicates.iter().all(|p| p.matches(dir))
+    }
+}
+
+/// Splits predicate `[...]` off the start of the string
+///
+/// Brackets may be nested (as in regex character classes) and `\]` is
+/// kept as is, so location patterns can be written verbatim.
+fn predicate(text: &str, offset: usize) -> Result<(Predicate, &str), Error> {
+    let mut depth = 0;
+    let mut escaped = false;
+    for (idx, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '[' => depth += 1,
+            ']' if depth == 1 => {
+                let body = text[1..idx].trim();
+                let name_len = body.bytes()
+                    .take_while(|&b| b.is_ascii_alphanumeric() || b == b'_')
+                    .count();
+                let pred = if name_len > 0 &&
+                    body[name_len..].starts_with('=')
+                {
+                    Predicate::Child(body[..name_len].to_string(),
+                                     body[name_len+1..].trim().to_string())
+                } else {
+                    Predicate::Args(body.to_string())
+                };
+                return Ok((pred, &text[idx+1..]));
+            }
+            ']' => depth -= 1,
+            _ => {}
+        }
+    }
+    Err(Error::new(offset, "unclosed `[`"))
+}
+
+impl Query {
+    /// Parse query string
+    pub fn parse(query: &str) -> Result<Query, Error> {
+        let mut steps = Vec::new();
+        let mut rest = query.trim();
+        if rest.is_empty() {
+            return Err(Error::new(0, "empty query"));
+        }
+        let mut first = true;
+        loop {
+            let offset = query.len() - rest.len();
+            let descendant = if let Some(tail) = rest.strip_prefix("//") {
+                rest = tail;
+                true
+            } else if let Some(tail) = rest.strip_prefix('/') {
+                rest = tail;
+                false
+            } else if first {
+                false
+            } else {
+                return Err(Error::new(offset, "expected `/`"));
+            };
+            first = false;
+            let offset = query.len() - rest.len();
+            let name_len = rest.find(['/', '['])
+                .unwrap_or(rest.len());
+            let name = rest[..name_len].trim();
+            if name.is_empty() {
+                return Err(Error::new(offset, "expected directive name"));
+            }
+            rest = &rest[name_len..];
+            let mut predicates = Vec::new();
+            while rest.starts_with('[') {
+                let offset = query.len() - rest.len();
+                let (pred, tail) = predicate(rest, offset)?;
+                predicates.push(pred);
+                rest = tail;
+            }
+            steps.push(Step {
+                descendant,
+                name: if name == "*" { None } else { Some(name.to_string()) },
+                predicates,
+            });
+            if rest.is_empty() {
+                break;
+            }
+        }
+        Ok(Query { steps })
+    }
+
+    /// Find all directives matching the query
+    ///
+    /// Directives are returned in the order of the config, each one once
+    /// even if it's matched in multiple ways.
+    pub fn find<'a>(&self, dirs: &'a [Directive<'a>]) -> Vec<Match<'a>> {
+        let mut matches = Vec::new();
+        let mut active = vec![false; self.steps.len()];
+        active[0] = true;
+        self.find_in(dirs, &active, &mut Vec::new(), &mut matches);
+        matches
+    }
+
+    /// Returns steps active for the children of `dir` and whether `dir`
+    /// matches the whole query
+    ///
+    /// Every directive is visited once with all the steps which may apply
+    /// to it, so a directive can't be matched twice.
+    fn advance(&self, dir: &Directive, active: &[bool]) -> (Vec<bool>, bool) {
+        let last = self.steps.len() - 1;
+        let mut next = vec![false; self.steps.len()];
+        let mut is_match = false;
+        for (idx, step) in self.steps.iter().enumerate() {
+            if !active[idx] {
+                continue;
+            }
+            if step.descendant {
+                next[idx] = true;
+            }
+            if step.matches(dir) {
+                if idx == last {
+                    is_match = true;
+                } else {
+                    next[idx + 1] = true;
+                }
+            }
+        }
+        (next, is_match)
+    }
+
+    fn find_in<'a>(&self, dirs: &'a [Directive<'a>], active: &[bool],
+        ancestors: &mut Vec<&'a Directive<'a>>, matches: &mut Vec<Match<'a>>)
+    {
+        for dir in dirs {
+            let (next, is_match) = self.advance(dir, active);
+            if is_match {
+                matches.push(Match {
+                    directive: dir,
+                    ancestors: ancestors.clone(),
+                });
+            }
+            if let Some(children) = dir.item.children() {
+                if next.contains(&true) {
+                    ancestors.push(dir);
+                    self.find_in(children, &next, ancestors, matches);
+                    ancestors.pop();
+                }
+            }
+        }
+    }
+
+    /// Call `f` for every directive matching the query
+    ///
+    /// This is a mutable counterpart of [`find`](#method.find) for bulk
+    /// edits. Directive is visited before its children, so changes made
+    /// by `f` are seen by the rest of the query. Returns the number of
+    /// directives visited.
+    pub fn for_each_mut<'a, F>(&self, dirs: &mut Vec<Directive<'a>>,
+        mut f: F)
+        -> usize
+        where F: FnMut(&mut Directive<'a>)
+    {
+        let mut active = vec![false; self.steps.len()];
+        active[0] = true;
+        self.for_each_in(dirs, &active, &mut f)
+    }
+
+    fn for_each_in<'a, F>(&self, dirs: &mut Vec<Directive<'a>>,
+        active: &[bool], f: &mut F)
+        -> usize
+        where F: FnMut(&mut Directive<'a>)
+    {
+        let mut visited = 0;
+        for dir in dirs {
+            let (next, is_match) = self.advance(dir, active);
+            if is_match {
+                visited += 1;
+                f(dir);
+            }
+            if let Some(children) = dir.item.children_mut() {
+                if next.contains(&true) {
+                    visited += self.for_each_in(children, &next, f);
+                }
+            }
+        }
+        visited
+    }
+}
+
+impl FromStr for Query {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Query, Error> {
+        Query::parse(s)
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, step) in self.steps.iter().enumerate() {
+            if step.descendant {
+                f.write_str("//")?;
+            } else if idx > 0 {
+                f.write_str("/")?;
+            }
+            f.write_str(step.name.as_ref().map_or("*", |n| &n[..]))?;
+            for pred in &step.predicates {
+                match *pred {
+                    Predicate::Args(ref args) => write!(f, "[{}]", args)?,
+                    Predicate::Child(ref name, ref value) => {
+                        write!(f, "[{}={}]", name, value)?
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Find all directives matching `query`
+///
+/// Shortcut for `Query::parse(query)?.find(dirs)`.
+pub fn find<'a>(dirs: &'a [Directive<'a>], query: &str)
+    -> Result<Vec<Match<'a>>, Error>
+{
+    Ok(Query::parse(query)?.find(dirs))
+}
+
+/// Call `f` for every directive matching `query`
+///
+/// Shortcut for `Query::parse(query)?.for_each_mut(dirs, f)`, returns the
+/// number of directives visited.
+pub fn for_each_mut<'a, F>(dirs: &mut Vec<Directive<'a>>, query: &str,
+    f: F)
+    -> Result<usize, Error>
+    where F: FnMut(&mut Directive<'a>)
+{
+    Ok(Query::parse(query)?.for_each_mut(dirs, f))
+}
@@ -30,7 +30,7 @@ fn parse_source<'a>(val: Token<'a>)
 }
 
 pub fn directives<'a>()
-    -> impl Parser<Output=Item, Input=TokenStream<'a>>
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
 {
     choice((
         ident("real_ip_header").with(value())
@@ -0,0 +1,78 @@
+//! Compiling regular expressions found in config
+//!
+//! Nginx uses PCRE for `location ~`, `server_name ~`, `map`, `rewrite`
+//! and `if` patterns. We use `fancy-regex` which supports most of the PCRE
+//! syntax that is used in real configs (including lookarounds and
+//! backreferences).
+use fancy_regex;
+
+
+/// A compiled regular expression along with its capture groups
+#[derive(Debug, Clone)]
+pub struct Regex {
+    regex: fancy_regex::Regex,
+    case_insensitive: bool,
+}
+
+/// Error compiling regular expression
+#[derive(Fail, Debug, Clone, PartialEq, Eq)]
+#[fail(display="invalid regex {:?}: {}", pattern, message)]
+pub struct RegexError {
+    /// Original pattern (without `(?i)` prefix added for `~*`)
+    pub pattern: String,
+    /// Error message from the regex engine
+    pub message: String,
+}
+
+impl Regex {
+    /// Compile a pattern, `case_insensitive` is set for `~*` operators
+    pub fn new(pattern: &str, case_insensitive: bool)
+        -> Result<Regex, RegexError>
+    {
+        let res = if case_insensitive {
+            fancy_regex::Regex::new(&format!("(?i){}", pattern))
+        } else {
+            fancy_regex::Regex::new(pattern)
+        };
+        res.map(|regex| Regex { regex, case_insensitive })
+        .map_err(|e| RegexError {
+            pattern: pattern.to_string(),
+            message: e.to_string(),
+        })
+    }
+
+    /// Number of capture groups, i.e. max `$N` that might be referenced
+    ///
+    /// The implicit `$0` group (a whole match) is not counted.
+    pub fn captures_len(&self) -> usize {
+        self.regex.captures_len() - 1
+    }
+
+    /// Names of named capture groups (`(?<name>...)` or `(?P<name>...)`)
+    ///
+    /// Nginx exposes those as variables, i.e. `$name`.
+    pub fn capture_names(&self) -> Vec<&str> {
+        self.regex.capture_names().flatten().collect()
+    }
+
+    /// Returns true if `$name` or `$N` refers to a capture of this regex
+    pub fn has_capture(&self, name: &str) -> bool {
+        match name.parse::<usize>() {
+            Ok(n) => n <= self.captures_len(),
+            Err(_) => self.capture_names().contains(&name),
+        }
+    }
+
+    /// Whether regex was compiled as case-insensitive
+    pub fn is_case_insensitive(&self) -> bool {
+        self.case_insensitive
+    }
+
+    /// Test whether a string matches the regex
+    ///
+    /// Errors of the backtracking engine (i.e. backtrack limit) are
+    /// considered as no match.
+    pub fn is_match(&self, text: &str) -> bool {
+        self.regex.is_match(text).unwrap_or(false)
+    }
+}
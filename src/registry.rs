@@ -0,0 +1,634 @@
+//! Static metadata about nginx directives
+//!
+//! The registry describes directives the way nginx documentation does:
+//! number of arguments, contexts, default value, module and versions in
+//! which the directive is available. It covers every directive the parser
+//! understands and a number of common ones it doesn't, so the same facts
+//! can be used for directives kept as is.
+//!
+//! Entries are keyed by [`Item::directive_name()`]:
+//!
+//! ```rust
+//! # use clia_nginx_config::registry::{self, Context};
+//! let info = registry::lookup("proxy_pass").unwrap();
+//! assert_eq!(info.module, "ngx_http_proxy_module");
+//! assert!(info.allowed_in(Context::Location));
+//! assert!(!info.allowed_in(Context::Server));
+//! ```
+//!
+//! Some names are shared by directives of different modules, like `server`
+//! in `http` and in `upstream`. Such name is described by the entry of the
+//! directive the parser supports.
+//!
+//! [`Item::directive_name()`]: ../ast/enum.Item.html#method.directive_name
+use std::fmt;
+use std::str::FromStr;
+
+use units::ParseUnitError;
+
+
+/// Block in which a directive may appear
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Context {
+    /// Top level of the main config file
+    Main,
+    /// `events { }`
+    Events,
+    /// `http { }`
+    Http,
+    /// `server { }` inside `http`
+    Server,
+    /// `location { }`
+    Location,
+    /// `if { }` inside `server`
+    ServerIf,
+    /// `if { }` inside `location`
+    LocationIf,
+    /// `limit_except { }`
+    LimitExcept,
+    /// `upstream { }` inside `http`
+    Upstream,
+    /// `stream { }`
+    Stream,
+    /// `mail { }`
+    Mail,
+}
+
+impl fmt::Display for Context {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::Context::*;
+        f.write_str(match *self {
+            Main => "main",
+            Events => "events",
+            Http => "http",
+            Server => "server",
+            Location => "location",
+            ServerIf => "if in server",
+            LocationIf => "if in location",
+            LimitExcept => "limit_except",
+            Upstream => "upstream",
+            Stream => "stream",
+            Mail => "mail",
+        })
+    }
+}
+
+/// Number of arguments of a directive, not counting its name or block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Arity {
+    /// Minimum number of arguments
+    pub min: usize,
+    /// Maximum number of arguments, `None` if unlimited
+    pub max: Option<usize>,
+}
+
+/// Version of nginx
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    /// Major version, `1` in `1.25.3`
+    pub major: u32,
+    /// Minor version, `25` in `1.25.3`
+    pub minor: u32,
+    /// Patch version, `3` in `1.25.3`
+    pub patch: u32,
+}
+
+/// Facts about a single directive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DirectiveInfo {
+    /// Name of the directive
+    pub name: &'static str,
+    /// Module which implements the directive, e.g. `ngx_http_proxy_module`
+    pub module: &'static str,
+    /// Number of arguments
+    pub arity: Arity,
+    /// Blocks in which the directive may appear
+    pub contexts: &'static [Context],
+    /// Default value as written in nginx documentation, `None` if the
+    /// directive has no default
+    pub default: Option<&'static str>,
+    /// Whether the directive has a block of child directives
+    pub block: bool,
+    /// Whether the value set in outer block applies to the inner ones
+    pub inherited: bool,
+    /// Whether the directive may be used multiple times in the same block
+    pub repeatable: bool,
+    /// First version of nginx having the directive, `None` if the directive
+    /// exists since long ago
+    pub min_version: Option<Version>,
+    /// First version of nginx where the directive is removed, `None` if
+    /// the directive still exists
+    pub max_version: Option<Version>,
+}
+
+impl Arity {
+    /// Whether directive may have `n` arguments
+    pub fn accepts(&self, n: usize) -> bool {
+        n >= self.min && self.max.map_or(true, |max| n <= max)
+    }
+}
+
+impl fmt::Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.max {
+            Some(max) if max == self.min => write!(f, "{}", max),
+            Some(max) => write!(f, "{} to {}", self.min, max),
+            None => write!(f, "{} or more", self.min),
+        }
+    }
+}
+
+impl Version {
+    /// Create a version from its parts
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Version {
+        Version { major, minor, patch }
+    }
+}
+
+impl FromStr for Version {
+    type Err = ParseUnitError;
+    fn from_str(s: &str) -> Result<Version, ParseUnitError> {
+        let err = || ParseUnitError::new("version", s);
+        let mut parts = s.split('.').map(|p| {
+            if p.is_empty() || !p.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(err());
+            }
+            p.parse().map_err(|_| err())
+        });
+        let major = parts.next().ok_or_else(err)??;
+        let minor = parts.next().ok_or_else(err)??;
+        let patch = parts.next().unwrap_or(Ok(0))?;
+        if parts.next().is_some() {
+            return Err(err());
+        }
+        Ok(Version { major, minor, patch })
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl DirectiveInfo {
+    /// Whether the directive may appear in the context
+    pub fn allowed_in(&self, context: Context) -> bool {
+        self.contexts.contains(&context)
+    }
+    /// Whether the directive exists in the given version of nginx
+    pub fn available_in(&self, version: Version) -> bool {
+        self.min_version.map_or(true, |min| version >= min) &&
+        self.max_version.map_or(true, |max| version < max)
+    }
+    const fn block(self) -> DirectiveInfo {
+        DirectiveInfo { block: true, ..self }
+    }
+    const fn repeatable(self) -> DirectiveInfo {
+        DirectiveInfo { repeatable: true, ..self }
+    }
+    const fn local(self) -> DirectiveInfo {
+        DirectiveInfo { inherited: false, ..self }
+    }
+    const fn since(self, major: u32, minor: u32, patch: u32)
+        -> DirectiveInfo
+    {
+        DirectiveInfo {
+            min_version: Some(Version::new(major, minor, patch)),
+            ..self
+        }
+    }
+    const fn until(self, major: u32, minor: u32, patch: u32)
+        -> DirectiveInfo
+    {
+        DirectiveInfo {
+            max_version: Some(Version::new(major, minor, patch)),
+            ..self
+        }
+    }
+}
+
+/// Returns metadata of the directive, `None` if the name is unknown
+pub fn lookup(name: &str) -> Option<&'static DirectiveInfo> {
+    DIRECTIVES.binary_search_by_key(&name, |d| d.name).ok()
+        .map(|idx| &DIRECTIVES[idx])
+}
+
+/// All directives in the registry, sorted by name
+pub fn directives() -> &'static [DirectiveInfo] {
+    DIRECTIVES
+}
+
+const NOARGS: Arity = Arity { min: 0, max: Some(0) };
+const FLAG: Arity = Arity { min: 1, max: Some(1) };
+const TAKE1: Arity = Arity { min: 1, max: Some(1) };
+const TAKE2: Arity = Arity { min: 2, max: Some(2) };
+const TAKE12: Arity = Arity { min: 1, max: Some(2) };
+const TAKE23: Arity = Arity { min: 2, max: Some(3) };
+const TAKE34: Arity = Arity { min: 3, max: Some(4) };
+const TAKE123: Arity = Arity { min: 1, max: Some(3) };
+const MORE1: Arity = Arity { min: 1, max: None };
+const MORE2: Arity = Arity { min: 2, max: None };
+
+// Common sets of contexts are named by the first letters of http, server,
+// location, if in location and limit_except (X)
+const MAIN: &[Context] = &[Context::Main];
+const EVENTS: &[Context] = &[Context::Events];
+const HTTP: &[Context] = &[Context::Http];
+const SERVER: &[Context] = &[Context::Server];
+const LOCATION: &[Context] = &[Context::Location];
+const UPSTREAM: &[Context] = &[Context::Upstream];
+const HS: &[Context] = &[Context::Http, Context::Server];
+const SL: &[Context] = &[Context::Server, Context::Location];
+const HSL: &[Context] = &[Context::Http, Context::Server, Context::Location];
+const HSLI: &[Context] = &[
+    Context::Http, Context::Server, Context::Location, Context::LocationIf,
+];
+const HSLX: &[Context] = &[
+    Context::Http, Context::Server, Context::Location, Context::LimitExcept,
+];
+const HSLIX: &[Context] = &[
+    Context::Http, Context::Server, Context::Location, Context::LocationIf,
+    Context::LimitExcept,
+];
+const LOC_IF: &[Context] = &[Context::Location, Context::LocationIf];
+const PASS: &[Context] = &[
+    Context::Location, Context::LocationIf, Context::LimitExcept,
+];
+const REWRITE: &[Context] = &[
+    Context::Server, Context::ServerIf, Context::Location, Context::LocationIf,
+];
+const REWRITE_HTTP: &[Context] = &[
+    Context::Http, Context::Server, Context::ServerIf, Context::Location,
+    Context::LocationIf,
+];
+const LOG: &[Context] = &[
+    Context::Main, Context::Http, Context::Mail, Context::Stream,
+    Context::Server, Context::Location,
+];
+const ANY: &[Context] = &[
+    Context::Main, Context::Events, Context::Http, Context::Server,
+    Context::Location, Context::ServerIf, Context::LocationIf,
+    Context::LimitExcept, Context::Upstream, Context::Stream, Context::Mail,
+];
+
+/// Directive which is inherited if it's allowed in more than one context
+const fn d(name: &'static str, module: &'static str, arity: Arity,
+    contexts: &'static [Context], default: Option<&'static str>)
+    -> DirectiveInfo
+{
+    DirectiveInfo {
+        name, module, arity, contexts, default,
+        block: false,
+        inherited: contexts.len() > 1,
+        repeatable: false,
+        min_version: None,
+        max_version: None,
+    }
+}
+
+static DIRECTIVES: &[DirectiveInfo] = &[
+    d("accept_mutex", "ngx_event_core_module", FLAG, EVENTS, Some("off")),
+    d("access_by_lua_file", "ngx_http_lua_module", TAKE1, HSLI, None),
+    d("access_log", "ngx_http_log_module",
+        MORE1, HSLIX, Some("logs/access.log combined")).repeatable(),
+    d("add_header", "ngx_http_headers_module", TAKE23, HSLI, None)
+        .repeatable(),
+    d("alias", "ngx_http_core_module", TAKE1, LOCATION, None),
+    d("allow", "ngx_http_access_module", TAKE1, HSLX, None).repeatable(),
+    d("auth_basic", "ngx_http_auth_basic_module", TAKE1, HSLX, Some("off")),
+    d("auth_basic_user_file", "ngx_http_auth_basic_module", TAKE1, HSLX, None),
+    d("auth_jwt", "ngx_http_auth_jwt_module", TAKE12, HSLX, Some("off"))
+        .since(1, 11, 3),
+    d("auth_request", "ngx_http_auth_request_module", TAKE1, HSL, Some("off"))
+        .since(1, 5, 4),
+    d("auth_request_set", "ngx_http_auth_request_module", TAKE2, HSL, None)
+        .repeatable().since(1, 5, 4),
+    d("balancer_by_lua_file", "ngx_http_lua_module", TAKE1, UPSTREAM, None),
+    d("body_filter_by_lua_file", "ngx_http_lua_module", TAKE1, HSLI, None),
+    d("break", "ngx_http_rewrite_module", NOARGS, REWRITE, None).local(),
+    d("chunked_transfer_encoding", "ngx_http_core_module",
+        FLAG, HSL, Some("on")),
+    d("client_body_buffer_size", "ngx_http_core_module",
+        TAKE1, HSL, Some("8k|16k")),
+    d("client_body_timeout", "ngx_http_core_module", TAKE1, HSL, Some("60s")),
+    d("client_header_timeout", "ngx_http_core_module", TAKE1, HS, Some("60s")),
+    d("client_max_body_size", "ngx_http_core_module", TAKE1, HSL, Some("1m")),
+    d("content_by_lua_file", "ngx_http_lua_module", TAKE1, LOC_IF, None)
+        .local(),
+    d("daemon", "ngx_core_module", FLAG, MAIN, Some("on")),
+    d("default_type", "ngx_http_core_module", TAKE1, HSL, Some("text/plain")),
+    d("deny", "ngx_http_access_module", TAKE1, HSLX, None).repeatable(),
+    d("empty_gif", "ngx_http_empty_gif_module", NOARGS, LOCATION, None),
+    d("env", "ngx_core_module", TAKE1, MAIN, None).repeatable(),
+    d("error_log", "ngx_core_module", MORE1, LOG, Some("logs/error.log error"))
+        .repeatable(),
+    d("error_page", "ngx_http_core_module", MORE2, HSLI, None).repeatable(),
+    d("etag", "ngx_http_core_module", FLAG, HSL, Some("on")).since(1, 3, 3),
+    d("events", "ngx_events_module", NOARGS, MAIN, None).block(),
+    d("expires", "ngx_http_headers_module", TAKE12, HSLI, Some("off")),
+    d("fastcgi_buffers", "ngx_http_fastcgi_module",
+        TAKE2, HSL, Some("8 4k|8k")),
+    d("fastcgi_cache", "ngx_http_fastcgi_module", TAKE1, HSL, Some("off")),
+    d("fastcgi_cache_background_update", "ngx_http_fastcgi_module",
+        FLAG, HSL, Some("off")).since(1, 11, 10),
+    d("fastcgi_cache_bypass", "ngx_http_fastcgi_module", MORE1, HSL, None)
+        .repeatable(),
+    d("fastcgi_cache_key", "ngx_http_fastcgi_module", TAKE1, HSL, None),
+    d("fastcgi_cache_lock", "ngx_http_fastcgi_module", FLAG, HSL, Some("off"))
+        .since(1, 1, 12),
+    d("fastcgi_cache_lock_age", "ngx_http_fastcgi_module",
+        TAKE1, HSL, Some("5s")).since(1, 7, 8),
+    d("fastcgi_cache_lock_timeout", "ngx_http_fastcgi_module",
+        TAKE1, HSL, Some("5s")).since(1, 1, 12),
+    d("fastcgi_cache_max_range_offset", "ngx_http_fastcgi_module",
+        TAKE1, HSL, None).since(1, 11, 6),
+    d("fastcgi_cache_methods", "ngx_http_fastcgi_module",
+        MORE1, HSL, Some("GET HEAD")).since(0, 7, 59),
+    d("fastcgi_cache_min_uses", "ngx_http_fastcgi_module",
+        TAKE1, HSL, Some("1")),
+    d("fastcgi_cache_path", "ngx_http_fastcgi_module", MORE2, HTTP, None)
+        .repeatable(),
+    d("fastcgi_cache_revalidate", "ngx_http_fastcgi_module",
+        FLAG, HSL, Some("off")).since(1, 5, 7),
+    d("fastcgi_cache_use_stale", "ngx_http_fastcgi_module",
+        MORE1, HSL, Some("off")),
+    d("fastcgi_cache_valid", "ngx_http_fastcgi_module", MORE1, HSL, None)
+        .repeatable(),
+    d("fastcgi_index", "ngx_http_fastcgi_module", TAKE1, HSL, None),
+    d("fastcgi_intercept_errors", "ngx_http_fastcgi_module",
+        FLAG, HSL, Some("off")),
+    d("fastcgi_no_cache", "ngx_http_fastcgi_module", MORE1, HSL, None)
+        .repeatable(),
+    d("fastcgi_param", "ngx_http_fastcgi_module", TAKE23, HSL, None)
+        .repeatable(),
+    d("fastcgi_pass", "ngx_http_fastcgi_module", TAKE1, LOC_IF, None).local(),
+    d("fastcgi_read_timeout", "ngx_http_fastcgi_module",
+        TAKE1, HSL, Some("60s")),
+    d("fastcgi_split_path_info", "ngx_http_fastcgi_module",
+        TAKE1, LOCATION, None),
+    d("grpc_connect_timeout", "ngx_http_grpc_module", TAKE1, HSL, Some("60s"))
+        .since(1, 13, 10),
+    d("grpc_intercept_errors", "ngx_http_grpc_module", FLAG, HSL, Some("off"))
+        .since(1, 13, 10),
+    d("grpc_next_upstream", "ngx_http_grpc_module",
+        MORE1, HSL, Some("error timeout")).since(1, 13, 10),
+    d("grpc_pass", "ngx_http_grpc_module", TAKE1, LOC_IF, None).local()
+        .since(1, 13, 10),
+    d("grpc_read_timeout", "ngx_http_grpc_module", TAKE1, HSL, Some("60s"))
+        .since(1, 13, 10),
+    d("grpc_send_timeout", "ngx_http_grpc_module", TAKE1, HSL, Some("60s"))
+        .since(1, 13, 10),
+    d("grpc_set_header", "ngx_http_grpc_module",
+        TAKE2, HSL, Some("Content-Length $content_length;")).repeatable()
+        .since(1, 13, 10),
+    d("grpc_ssl_certificate", "ngx_http_grpc_module", TAKE1, HSL, None)
+        .since(1, 13, 10),
+    d("grpc_ssl_certificate_key", "ngx_http_grpc_module", TAKE1, HSL, None)
+        .since(1, 13, 10),
+    d("grpc_ssl_ciphers", "ngx_http_grpc_module", TAKE1, HSL, Some("DEFAULT"))
+        .since(1, 13, 10),
+    d("grpc_ssl_name", "ngx_http_grpc_module",
+        TAKE1, HSL, Some("host from grpc_pass")).since(1, 13, 10),
+    d("grpc_ssl_protocols", "ngx_http_grpc_module",
+        MORE1, HSL, Some("TLSv1.2 TLSv1.3")).since(1, 13, 10),
+    d("grpc_ssl_server_name", "ngx_http_grpc_module", FLAG, HSL, Some("off"))
+        .since(1, 13, 10),
+    d("grpc_ssl_trusted_certificate", "ngx_http_grpc_module", TAKE1, HSL, None)
+        .since(1, 13, 10),
+    d("grpc_ssl_verify", "ngx_http_grpc_module", FLAG, HSL, Some("off"))
+        .since(1, 13, 10),
+    d("gzip", "ngx_http_gzip_module", FLAG, HSLI, Some("off")),
+    d("gzip_proxied", "ngx_http_gzip_module", MORE1, HSL, Some("off")),
+    d("gzip_static", "ngx_http_gzip_static_module", TAKE1, HSL, Some("off")),
+    d("gzip_types", "ngx_http_gzip_module", MORE1, HSL, Some("text/html")),
+    d("hash", "ngx_http_upstream_hash_module", TAKE12, UPSTREAM, None)
+        .since(1, 7, 2),
+    d("header_filter_by_lua_file", "ngx_http_lua_module", TAKE1, HSLI, None),
+    d("http", "ngx_http_module", NOARGS, MAIN, None).block(),
+    d("http2", "ngx_http_v2_module", FLAG, HS, Some("off")).since(1, 25, 1),
+    d("if", "ngx_http_rewrite_module", MORE1, SL, None).block().repeatable()
+        .local(),
+    d("include", "ngx_conf_module", TAKE1, ANY, None).repeatable().local(),
+    d("index", "ngx_http_index_module", MORE1, HSL, Some("index.html"))
+        .repeatable(),
+    d("internal", "ngx_http_core_module", NOARGS, LOCATION, None),
+    d("ip_hash", "ngx_http_upstream_ip_hash_module", NOARGS, UPSTREAM, None),
+    d("keepalive", "ngx_http_upstream_keepalive_module", TAKE1, UPSTREAM, None)
+        .since(1, 1, 4),
+    d("keepalive_requests", "ngx_http_core_module", TAKE1, HSL, Some("1000"))
+        .since(0, 8, 0),
+    d("keepalive_timeout", "ngx_http_core_module", TAKE12, HSL, Some("75s")),
+    d("large_client_header_buffers", "ngx_http_core_module",
+        TAKE2, HS, Some("4 8k")),
+    d("least_conn", "ngx_http_upstream_least_conn_module",
+        NOARGS, UPSTREAM, None).since(1, 3, 1),
+    d("limit_conn", "ngx_http_limit_conn_module", TAKE2, HSL, None)
+        .repeatable(),
+    d("limit_conn_status", "ngx_http_limit_conn_module",
+        TAKE1, HSL, Some("503")).since(1, 3, 15),
+    d("limit_conn_zone", "ngx_http_limit_conn_module", TAKE2, HTTP, None)
+        .repeatable(),
+    d("limit_except", "ngx_http_core_module", MORE1, LOCATION, None).block(),
+    d("limit_rate", "ngx_http_core_module", TAKE1, HSLI, Some("0")),
+    d("limit_rate_after", "ngx_http_core_module", TAKE1, HSLI, Some("0")),
+    d("limit_req", "ngx_http_limit_req_module", TAKE123, HSL, None)
+        .repeatable(),
+    d("limit_req_log_level", "ngx_http_limit_req_module",
+        TAKE1, HSL, Some("error")).since(0, 8, 18),
+    d("limit_req_status", "ngx_http_limit_req_module", TAKE1, HSL, Some("503"))
+        .since(1, 3, 15),
+    d("limit_req_zone", "ngx_http_limit_req_module", TAKE34, HTTP, None)
+        .repeatable(),
+    d("listen", "ngx_http_core_module", MORE1, SERVER, Some("*:80 | *:8000"))
+        .repeatable(),
+    d("load_module", "ngx_core_module", TAKE1, MAIN, None).repeatable()
+        .since(1, 9, 11),
+    d("location", "ngx_http_core_module", TAKE12, SL, None).block()
+        .repeatable().local(),
+    d("log_by_lua_file", "ngx_http_lua_module", TAKE1, HSLI, None),
+    d("log_format", "ngx_http_log_module", MORE2, HTTP, None).repeatable(),
+    d("lua_need_request_body", "ngx_http_lua_module", FLAG, HSL, Some("off")),
+    d("mail", "ngx_mail_module", NOARGS, MAIN, None).block(),
+    d("map", "ngx_http_map_module", TAKE2, HTTP, None).block().repeatable(),
+    d("master_process", "ngx_core_module", FLAG, MAIN, Some("on")),
+    d("multi_accept", "ngx_event_core_module", FLAG, EVENTS, Some("off")),
+    d("pcre_jit", "ngx_core_module", FLAG, MAIN, Some("off")).since(1, 1, 12),
+    d("pid", "ngx_core_module", TAKE1, MAIN, Some("logs/nginx.pid")),
+    d("proxy_buffer_size", "ngx_http_proxy_module", TAKE1, HSL, Some("4k|8k")),
+    d("proxy_buffering", "ngx_http_proxy_module", FLAG, HSL, Some("on")),
+    d("proxy_buffers", "ngx_http_proxy_module", TAKE2, HSL, Some("8 4k|8k")),
+    d("proxy_busy_buffers_size", "ngx_http_proxy_module",
+        TAKE1, HSL, Some("8k|16k")),
+    d("proxy_cache", "ngx_http_proxy_module", TAKE1, HSL, Some("off")),
+    d("proxy_cache_bypass", "ngx_http_proxy_module", MORE1, HSL, None)
+        .repeatable(),
+    d("proxy_cache_key", "ngx_http_proxy_module",
+        TAKE1, HSL, Some("$scheme$proxy_host$request_uri")),
+    d("proxy_cache_lock", "ngx_http_proxy_module", FLAG, HSL, Some("off"))
+        .since(1, 1, 12),
+    d("proxy_cache_path", "ngx_http_proxy_module", MORE2, HTTP, None)
+        .repeatable(),
+    d("proxy_cache_use_stale", "ngx_http_proxy_module",
+        MORE1, HSL, Some("off")),
+    d("proxy_cache_valid", "ngx_http_proxy_module", MORE1, HSL, None)
+        .repeatable(),
+    d("proxy_connect_timeout", "ngx_http_proxy_module",
+        TAKE1, HSL, Some("60s")),
+    d("proxy_cookie_domain", "ngx_http_proxy_module", TAKE12, HSL, Some("off"))
+        .repeatable().since(1, 1, 15),
+    d("proxy_cookie_path", "ngx_http_proxy_module", TAKE12, HSL, Some("off"))
+        .repeatable().since(1, 1, 15),
+    d("proxy_hide_header", "ngx_http_proxy_module", TAKE1, HSL, None)
+        .repeatable(),
+    d("proxy_http_version", "ngx_http_proxy_module", TAKE1, HSL, Some("1.0"))
+        .since(1, 1, 4),
+    d("proxy_ignore_headers", "ngx_http_proxy_module", MORE1, HSL, None),
+    d("proxy_intercept_errors", "ngx_http_proxy_module",
+        FLAG, HSL, Some("off")),
+    d("proxy_method", "ngx_http_proxy_module", TAKE1, HSL, None),
+    d("proxy_next_upstream", "ngx_http_proxy_module",
+        MORE1, HSL, Some("error timeout")),
+    d("proxy_next_upstream_timeout", "ngx_http_proxy_module",
+        TAKE1, HSL, Some("0")).since(1, 7, 5),
+    d("proxy_next_upstream_tries", "ngx_http_proxy_module",
+        TAKE1, HSL, Some("0")).since(1, 7, 5),
+    d("proxy_no_cache", "ngx_http_proxy_module", MORE1, HSL, None)
+        .repeatable(),
+    d("proxy_pass", "ngx_http_proxy_module", TAKE1, PASS, None).local(),
+    d("proxy_pass_header", "ngx_http_proxy_module", TAKE1, HSL, None)
+        .repeatable(),
+    d("proxy_pass_request_body", "ngx_http_proxy_module",
+        FLAG, HSL, Some("on")),
+    d("proxy_pass_request_headers", "ngx_http_proxy_module",
+        FLAG, HSL, Some("on")),
+    d("proxy_read_timeout", "ngx_http_proxy_module", TAKE1, HSL, Some("60s")),
+    d("proxy_redirect", "ngx_http_proxy_module", TAKE12, HSL, Some("default"))
+        .repeatable(),
+    d("proxy_request_buffering", "ngx_http_proxy_module",
+        FLAG, HSL, Some("on")).since(1, 7, 11),
+    d("proxy_send_timeout", "ngx_http_proxy_module", TAKE1, HSL, Some("60s")),
+    d("proxy_set_header", "ngx_http_proxy_module",
+        TAKE2, HSL, Some("Host $proxy_host; Connection close;")).repeatable(),
+    d("proxy_ssl_name", "ngx_http_proxy_module",
+        TAKE1, HSL, Some("$proxy_host")).since(1, 7, 0),
+    d("proxy_ssl_server_name", "ngx_http_proxy_module", FLAG, HSL, Some("off"))
+        .since(1, 7, 0),
+    d("proxy_ssl_verify", "ngx_http_proxy_module", FLAG, HSL, Some("off"))
+        .since(1, 7, 0),
+    d("proxy_store", "ngx_http_proxy_module", TAKE1, HSL, Some("off")),
+    d("real_ip_header", "ngx_http_realip_module",
+        TAKE1, HSL, Some("X-Real-IP")),
+    d("real_ip_recursive", "ngx_http_realip_module", FLAG, HSL, Some("off"))
+        .since(1, 3, 0),
+    d("recursive_error_pages", "ngx_http_core_module", FLAG, HSL, Some("off")),
+    d("resolver", "ngx_http_core_module", MORE1, HSL, None),
+    d("return", "ngx_http_rewrite_module", TAKE12, REWRITE, None).repeatable()
+        .local(),
+    d("rewrite", "ngx_http_rewrite_module", TAKE23, REWRITE, None)
+        .repeatable().local(),
+    d("rewrite_by_lua_file", "ngx_http_lua_module", TAKE1, HSLI, None),
+    d("rewrite_log", "ngx_http_rewrite_module",
+        FLAG, REWRITE_HTTP, Some("off")),
+    d("root", "ngx_http_core_module", TAKE1, HSLI, Some("html")),
+    d("satisfy", "ngx_http_core_module", TAKE1, HSL, Some("all")),
+    d("scgi_buffering", "ngx_http_scgi_module", FLAG, HSL, Some("on")),
+    d("scgi_buffers", "ngx_http_scgi_module", TAKE2, HSL, Some("8 4k|8k")),
+    d("scgi_cache", "ngx_http_scgi_module", TAKE1, HSL, Some("off")),
+    d("scgi_cache_key", "ngx_http_scgi_module", TAKE1, HSL, None),
+    d("scgi_cache_valid", "ngx_http_scgi_module", MORE1, HSL, None)
+        .repeatable(),
+    d("scgi_connect_timeout", "ngx_http_scgi_module", TAKE1, HSL, Some("60s")),
+    d("scgi_intercept_errors", "ngx_http_scgi_module", FLAG, HSL, Some("off")),
+    d("scgi_next_upstream", "ngx_http_scgi_module",
+        MORE1, HSL, Some("error timeout")),
+    d("scgi_param", "ngx_http_scgi_module", TAKE23, HSL, None).repeatable(),
+    d("scgi_pass", "ngx_http_scgi_module", TAKE1, LOC_IF, None).local(),
+    d("scgi_read_timeout", "ngx_http_scgi_module", TAKE1, HSL, Some("60s")),
+    d("scgi_send_timeout", "ngx_http_scgi_module", TAKE1, HSL, Some("60s")),
+    d("send_timeout", "ngx_http_core_module", TAKE1, HSL, Some("60s")),
+    d("sendfile", "ngx_http_core_module", FLAG, HSLI, Some("off")),
+    d("server", "ngx_http_core_module", NOARGS, HTTP, None).block()
+        .repeatable(),
+    d("server_name", "ngx_http_core_module", MORE1, SERVER, Some("\"\""))
+        .repeatable(),
+    d("server_names_hash_bucket_size", "ngx_http_core_module",
+        TAKE1, HTTP, Some("32|64|128")),
+    d("server_names_hash_max_size", "ngx_http_core_module",
+        TAKE1, HTTP, Some("512")),
+    d("server_tokens", "ngx_http_core_module", TAKE1, HSL, Some("on")),
+    d("set", "ngx_http_rewrite_module", TAKE2, REWRITE, None).repeatable()
+        .local(),
+    d("set_real_ip_from", "ngx_http_realip_module", TAKE1, HSL, None)
+        .repeatable(),
+    d("spdy_chunk_size", "ngx_http_spdy_module", TAKE1, HSL, Some("8k"))
+        .since(1, 5, 9).until(1, 9, 5),
+    d("spdy_headers_comp", "ngx_http_spdy_module", TAKE1, HS, Some("0"))
+        .since(1, 3, 15).until(1, 9, 5),
+    d("ssl", "ngx_http_ssl_module", FLAG, HS, Some("off")).until(1, 25, 1),
+    d("ssl_certificate", "ngx_http_ssl_module", TAKE1, HS, None).repeatable(),
+    d("ssl_certificate_by_lua_file", "ngx_http_lua_module", TAKE1, HS, None),
+    d("ssl_certificate_key", "ngx_http_ssl_module", TAKE1, HS, None)
+        .repeatable(),
+    d("ssl_ciphers", "ngx_http_ssl_module",
+        TAKE1, HS, Some("HIGH:!aNULL:!MD5")),
+    d("ssl_client_certificate", "ngx_http_ssl_module", TAKE1, HS, None),
+    d("ssl_dhparam", "ngx_http_ssl_module", TAKE1, HS, None).since(0, 7, 2),
+    d("ssl_ecdh_curve", "ngx_http_ssl_module", TAKE1, HS, Some("auto"))
+        .since(1, 1, 0),
+    d("ssl_prefer_server_ciphers", "ngx_http_ssl_module",
+        FLAG, HS, Some("off")),
+    d("ssl_protocols", "ngx_http_ssl_module",
+        MORE1, HS, Some("TLSv1.2 TLSv1.3")),
+    d("ssl_session_cache", "ngx_http_ssl_module", TAKE12, HS, Some("none")),
+    d("ssl_session_fetch_by_lua_file", "ngx_http_lua_module",
+        TAKE1, HTTP, None),
+    d("ssl_session_store_by_lua_file", "ngx_http_lua_module",
+        TAKE1, HTTP, None),
+    d("ssl_session_tickets", "ngx_http_ssl_module", FLAG, HS, Some("on"))
+        .since(1, 5, 9),
+    d("ssl_session_timeout", "ngx_http_ssl_module", TAKE1, HS, Some("5m")),
+    d("ssl_stapling", "ngx_http_ssl_module", FLAG, HS, Some("off"))
+        .since(1, 3, 7),
+    d("ssl_stapling_verify", "ngx_http_ssl_module", FLAG, HS, Some("off"))
+        .since(1, 3, 7),
+    d("ssl_trusted_certificate", "ngx_http_ssl_module", TAKE1, HS, None)
+        .since(1, 3, 7),
+    d("ssl_verify_client", "ngx_http_ssl_module", TAKE1, HS, Some("off")),
+    d("ssl_verify_depth", "ngx_http_ssl_module", TAKE1, HS, Some("1")),
+    d("stream", "ngx_stream_module", NOARGS, MAIN, None).block()
+        .since(1, 9, 0),
+    d("tcp_nodelay", "ngx_http_core_module", FLAG, HSL, Some("on")),
+    d("tcp_nopush", "ngx_http_core_module", FLAG, HSL, Some("off")),
+    d("try_files", "ngx_http_core_module", MORE2, SL, None).local(),
+    d("types", "ngx_http_core_module",
+        NOARGS, HSL, Some("text/html html; image/gif gif; image/jpeg jpg;"))
+        .block(),
+    d("types_hash_max_size", "ngx_http_core_module", TAKE1, HSL, Some("1024")),
+    d("underscores_in_headers", "ngx_http_core_module", FLAG, HS, Some("off")),
+    d("upstream", "ngx_http_upstream_module", TAKE1, HTTP, None).block()
+        .repeatable(),
+    d("use", "ngx_event_core_module", TAKE1, EVENTS, None),
+    d("user", "ngx_core_module", TAKE12, MAIN, Some("nobody nobody")),
+    d("uwsgi_buffering", "ngx_http_uwsgi_module", FLAG, HSL, Some("on")),
+    d("uwsgi_buffers", "ngx_http_uwsgi_module", TAKE2, HSL, Some("8 4k|8k")),
+    d("uwsgi_cache", "ngx_http_uwsgi_module", TAKE1, HSL, Some("off")),
+    d("uwsgi_cache_key", "ngx_http_uwsgi_module", TAKE1, HSL, None),
+    d("uwsgi_cache_valid", "ngx_http_uwsgi_module", MORE1, HSL, None)
+        .repeatable(),
+    d("uwsgi_connect_timeout", "ngx_http_uwsgi_module",
+        TAKE1, HSL, Some("60s")),
+    d("uwsgi_intercept_errors", "ngx_http_uwsgi_module",
+        FLAG, HSL, Some("off")),
+    d("uwsgi_next_upstream", "ngx_http_uwsgi_module",
+        MORE1, HSL, Some("error timeout")),
+    d("uwsgi_param", "ngx_http_uwsgi_module", TAKE23, HSL, None).repeatable(),
+    d("uwsgi_pass", "ngx_http_uwsgi_module", TAKE1, LOC_IF, None).local(),
+    d("uwsgi_read_timeout", "ngx_http_uwsgi_module", TAKE1, HSL, Some("60s")),
+    d("uwsgi_send_timeout", "ngx_http_uwsgi_module", TAKE1, HSL, Some("60s")),
+    d("worker_connections", "ngx_event_core_module",
+        TAKE1, EVENTS, Some("512")),
+    d("worker_priority", "ngx_core_module", TAKE1, MAIN, Some("0")),
+    d("worker_processes", "ngx_core_module", TAKE1, MAIN, Some("1")),
+    d("worker_rlimit_nofile", "ngx_core_module", TAKE1, MAIN, None),
+];
@@ -1,24 +1,26 @@
+use std::borrow::Cow;
+
 use combine::{Parser};
-use combine::{choice, optional, many1, position};
+use combine::{choice, optional, many1};
 use combine::error::StreamError;
 use combine::easy::Error;
 
 use ast::{self, Item};
-use grammar::{value, block, Code};
-use helpers::{semi, ident, string};
-use position::Pos;
+use grammar::{value, block, raw, Code};
+use helpers::{semi, ident, string, spanned};
+use position::Span;
 use tokenizer::{TokenStream, Token};
-use value::{Value};
+use value::{Value, QuoteStyle, unquote};
 
 
 fn rewrite<'a>()
-    -> impl Parser<Output=Item, Input=TokenStream<'a>>
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
 {
     use ast::RewriteFlag::*;
     use ast::Item::Rewrite;
 
     ident("rewrite")
-    .with(string())
+    .with(raw())
     .and(value())
     .and(optional(choice((
         ident("last").map(|_| Last),
@@ -28,40 +30,47 @@ fn rewrite<'a>()
     ))))
     .map(|((regex, replacement), flag)| {
         Rewrite(ast::Rewrite {
-            regex: regex.value.to_string(), replacement, flag,
+            regex, replacement, flag,
         })
     })
     .skip(semi())
 }
 
-fn set<'a>()
-    -> impl Parser<Output=Item, Input=TokenStream<'a>>
+/// Name of the variable being assigned (`$name`), returned without `$`
+pub fn variable<'a>()
+    -> impl Parser<Output=Cow<'a, str>, Input=TokenStream<'a>>
 {
-    ident("set")
-    .with(string().and_then(|t| {
+    string().and_then(|t| {
         let ch1 = t.value.chars().nth(0).unwrap_or(' ');
         let ch2 = t.value.chars().nth(1).unwrap_or(' ');
         if ch1 == '$' && matches!(ch2, 'a'...'z' | 'A'...'Z' | '_') &&
             t.value[2..].chars()
             .all(|x| matches!(x, 'a'...'z' | 'A'...'Z' | '0'...'9' | '_'))
         {
-            Ok(t.value[1..].to_string())
+            Ok(Cow::Borrowed(&t.value[1..]))
         } else {
             Err(Error::unexpected_message("invalid variable"))
         }
-    }))
+    })
+}
+
+fn set<'a>()
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
+{
+    ident("set")
+    .with(variable())
     .and(value())
     .skip(semi())
     .map(|(variable, value)| Item::Set { variable, value })
 }
 
 fn return_directive<'a>()
-    -> impl Parser<Output=Item, Input=TokenStream<'a>>
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
 {
     use ast::Return::*;
     use value::Item::*;
 
-    fn lit<'a, 'x>(val: &'a Value) -> Result<&'a str, Error<Token<'x>, Token<'x>>> {
+    fn lit<'a, 'x>(val: &'a Value<'a>) -> Result<&'a str, Error<Token<'x>, Token<'x>>> {
         if val.data.is_empty() {
             return Err(Error::unexpected_message(
                 "empty return codes are not supported"));
@@ -153,13 +162,26 @@ fn strip_close_paren<'a>(v: &'_ mut Vec<&'a str>)
     Ok(())
 }
 
-fn parse_unary<'a>(mut v: Vec<&str>, position: Pos)
-    -> Result<ast::IfCondition, Error<Token<'a>, Token<'a>>>
+/// Finds the span of `part`, which is a substring of one of the tokens
+fn span_of(tokens: &[(Span, Token)], part: &str) -> Span {
+    let ptr = part.as_ptr() as usize;
+    tokens.iter()
+        .find(|&&(_, tok)| {
+            let start = tok.value.as_ptr() as usize;
+            ptr >= start && ptr + part.len() <= start + tok.value.len()
+        })
+        .map(|&(span, tok)| span.sub(tok.value, part))
+        .expect("condition is a part of the tokens")
+}
+
+fn parse_unary<'a>(mut v: Vec<&'a str>, tokens: &[(Span, Token<'a>)])
+    -> Result<ast::IfCondition<'a>, Error<Token<'a>, Token<'a>>>
 {
     use ast::IfCondition::*;
 
     let oper = v.remove(0);
-    let right = Value::parse_str(position, v.remove(0))?;
+    let arg = v.remove(0);
+    let right = Value::parse_str(span_of(tokens, arg), arg)?;
     if v.len() > 0 {
         return Err(Error::unexpected_message("extra argument to condition"));
     }
@@ -176,40 +198,40 @@ fn parse_unary<'a>(mut v: Vec<&str>, position: Pos)
     }
 }
 
-fn parse_binary<'a>(mut v: Vec<&str>, position: Pos)
-    -> Result<ast::IfCondition, Error<Token<'a>, Token<'a>>>
+fn parse_binary<'a>(mut v: Vec<&'a str>, tokens: &[(Span, Token<'a>)])
+    -> Result<ast::IfCondition<'a>, Error<Token<'a>, Token<'a>>>
 {
     use ast::IfCondition::*;
 
-    let left = Value::parse_str(position, v.remove(0))?;
+    let arg = v.remove(0);
+    let left = Value::parse_str(span_of(tokens, arg), arg)?;
     if v.len() == 0 {
         return Ok(NonEmpty(left));
     }
     let oper = v.remove(0);
-    let right = match &v[..] {
-        [x] => x.to_string(),
+    let (right, quote) = match &v[..] {
+        [x] => (unquote(x), QuoteStyle::of(x)),
         _ => return Err(Error::unexpected_message(
                 "you can only compare against a single literal")),
     };
     match oper {
         "=" => return Ok(Eq(left, right)),
         "!=" => return Ok(Neq(left, right)),
-        "~" => return Ok(RegEq(left, right, true)),
-        "!~" => return Ok(RegNeq(left, right, true)),
-        "~*" => return Ok(RegEq(left, right, false)),
-        "!~*" => return Ok(RegNeq(left, right, false)),
+        "~" => return Ok(RegEq(left, right, true, quote)),
+        "!~" => return Ok(RegNeq(left, right, true, quote)),
+        "~*" => return Ok(RegEq(left, right, false, quote)),
+        "!~*" => return Ok(RegNeq(left, right, false, quote)),
         _ => return Err(Error::unexpected_message("missing parenthesis")),
     }
 }
 
 fn if_directive<'a>()
-    -> impl Parser<Output=Item, Input=TokenStream<'a>>
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
 {
     ident("if")
-    .with(position())
-    .and(many1(string()))
-    .and_then(|(pos, v): (_, Vec<_>)| -> Result<_, Error<_, _>> {
-        let mut v = v.iter().map(|t| t.value).collect();
+    .with(many1(spanned(string())))
+    .and_then(|tokens: Vec<(Span, Token<'a>)>| -> Result<_, Error<_, _>> {
+        let mut v = tokens.iter().map(|&(_, t)| t.value).collect();
         strip_open_paren(&mut v)?;
         strip_close_paren(&mut v)?;
         let binary = match v.get(0) {
@@ -219,9 +241,9 @@ fn if_directive<'a>()
                 "missing parenthesis")),
         };
         if binary {
-            parse_binary(v, pos)
+            parse_binary(v, &tokens)
         } else {
-            parse_unary(v, pos)
+            parse_unary(v, &tokens)
         }
     })
     .and(block())
@@ -232,7 +254,7 @@ fn if_directive<'a>()
 }
 
 pub fn directives<'a>()
-    -> impl Parser<Output=Item, Input=TokenStream<'a>>
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
 {
     choice((
         rewrite(),
@@ -0,0 +1,39 @@
+use combine::{Parser};
+use combine::{choice};
+
+use ast::Item;
+use grammar::{value, bool, duration};
+use helpers::{semi, ident};
+use proxy::{param, cache_valid, next_upstream, buffers};
+use tokenizer::TokenStream;
+
+
+pub fn directives<'a>()
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
+{
+    choice((
+        ident("scgi_pass").with(value()).skip(semi())
+            .map(Item::ScgiPass),
+        param("scgi_param").map(Item::ScgiParam),
+        ident("scgi_read_timeout").with(duration()).skip(semi())
+            .map(Item::ScgiReadTimeout),
+        ident("scgi_connect_timeout").with(duration()).skip(semi())
+            .map(Item::ScgiConnectTimeout),
+        ident("scgi_send_timeout").with(duration()).skip(semi())
+            .map(Item::ScgiSendTimeout),
+        ident("scgi_buffering").with(bool()).skip(semi())
+            .map(Item::ScgiBuffering),
+        ident("scgi_buffers").with(buffers()).skip(semi())
+            .map(Item::ScgiBuffers),
+        ident("scgi_intercept_errors").with(bool()).skip(semi())
+            .map(Item::ScgiInterceptErrors),
+        ident("scgi_next_upstream").with(next_upstream()).skip(semi())
+            .map(Item::ScgiNextUpstream),
+        ident("scgi_cache").with(value()).skip(semi())
+            .map(Item::ScgiCache),
+        ident("scgi_cache_key").with(value()).skip(semi())
+            .map(Item::ScgiCacheKey),
+        ident("scgi_cache_valid").with(cache_valid()).skip(semi())
+            .map(Item::ScgiCacheValid),
+    ))
+}
@@ -0,0 +1,115 @@
+use combine::{many1, Parser};
+use combine::{choice};
+use combine::error::StreamError;
+use combine::easy::Error;
+
+use ast::{Item, SslProtocol, SslSessionCache};
+use grammar::{value, bool, duration, raw};
+use helpers::{semi, ident, string};
+use tokenizer::{TokenStream, Token};
+
+
+/// Arguments of `ssl_protocols` and similar directives
+pub fn protocols<'a>()
+    -> impl Parser<Output=Vec<SslProtocol>, Input=TokenStream<'a>>
+{
+    use ast::SslProtocol::*;
+    many1(choice((
+        ident("SSLv2").map(|_| SslV2),
+        ident("SSLv3").map(|_| SslV3),
+        ident("TLSv1").map(|_| TlsV1),
+        ident("TLSv1.1").map(|_| TlsV1_1),
+        ident("TLSv1.2").map(|_| TlsV1_2),
+        ident("TLSv1.3").map(|_| TlsV1_3),
+    )))
+}
+
+fn ssl_session_cache<'a>()
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
+{
+    ident("ssl_session_cache").with(choice((
+        ident("off").map(|_| SslSessionCache::Off),
+        ident("none").map(|_| SslSessionCache::None),
+        many1(string()).and_then(|items: Vec<Token<'a>>| {
+            let mut builtin = None;
+            let mut shared = None;
+            for tok in items {
+                if tok.value == "builtin" {
+                    builtin = Some(None);
+                } else if let Some(sz) = tok.value.strip_prefix("builtin:") {
+                    builtin = Some(Some(sz.parse()?));
+                } else if let Some(zone) = tok.value.strip_prefix("shared:") {
+                    let mut pair = zone.splitn(2, ':');
+                    let name = pair.next().unwrap();
+                    let size = pair.next().ok_or_else(|| {
+                        Error::unexpected_message("shared session cache \
+                            must be in form `shared:name:size`")
+                    })?;
+                    shared = Some((name.into(), size.parse()?));
+                } else {
+                    return Err(Error::unexpected_message(
+                        format!("invalid session cache {:?}", tok.value)));
+                }
+            }
+            Ok(SslSessionCache::Store { builtin, shared })
+        }),
+    )))
+    .skip(semi())
+    .map(Item::SslSessionCache)
+}
+
+fn ssl_verify_client<'a>()
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
+{
+    use ast::SslVerifyClient::*;
+    ident("ssl_verify_client").with(choice((
+        ident("on").map(|_| On),
+        ident("off").map(|_| Off),
+        ident("optional").map(|_| Optional),
+        ident("optional_no_ca").map(|_| OptionalNoCa),
+    )))
+    .skip(semi())
+    .map(Item::SslVerifyClient)
+}
+
+pub fn directives<'a>()
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
+{
+    choice((
+        ident("ssl_certificate").with(value()).skip(semi())
+            .map(Item::SslCertificate),
+        ident("ssl_certificate_key").with(value()).skip(semi())
+            .map(Item::SslCertificateKey),
+        ident("ssl_protocols").with(protocols()).skip(semi())
+            .map(Item::SslProtocols),
+        ident("ssl_ciphers").with(raw())
+            .skip(semi())
+            .map(Item::SslCiphers),
+        ident("ssl_prefer_server_ciphers").with(bool()).skip(semi())
+            .map(Item::SslPreferServerCiphers),
+        ssl_session_cache(),
+        ident("ssl_session_timeout").with(duration()).skip(semi())
+            .map(Item::SslSessionTimeout),
+        ident("ssl_session_tickets").with(bool()).skip(semi())
+            .map(Item::SslSessionTickets),
+        ident("ssl_stapling").with(bool()).skip(semi())
+            .map(Item::SslStapling),
+        ident("ssl_stapling_verify").with(bool()).skip(semi())
+            .map(Item::SslStaplingVerify),
+        ident("ssl_trusted_certificate").with(value()).skip(semi())
+            .map(Item::SslTrustedCertificate),
+        ident("ssl").with(bool()).skip(semi()).map(Item::Ssl),
+        ident("ssl_dhparam").with(value()).skip(semi())
+            .map(Item::SslDhparam),
+        ident("ssl_ecdh_curve").with(raw())
+            .skip(semi())
+            .map(Item::SslEcdhCurve),
+        ident("ssl_client_certificate").with(value()).skip(semi())
+            .map(Item::SslClientCertificate),
+        ssl_verify_client(),
+        ident("ssl_verify_depth")
+            .with(string().and_then(|t| t.value.parse::<u32>()))
+            .skip(semi())
+            .map(Item::SslVerifyDepth),
+    ))
+}
@@ -5,6 +5,7 @@ use combine::error::{StreamError};
 use combine::stream::{Resetable};
 use combine::easy::{Error, Errors};
 
+use extension::Extensions;
 use position::Pos;
 
 
@@ -22,17 +23,22 @@ pub struct Token<'a> {
     pub value: &'a str,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug)]
 pub struct TokenStream<'a> {
     buf: &'a str,
     position: Pos,
+    /// Position right after the last token returned
+    end: Pos,
     off: usize,
-    next_state: Option<(usize, Token<'a>, usize, Pos)>,
+    next_state: Option<(usize, Token<'a>, usize, Pos, Pos)>,
+    /// Parsers of the directives implemented outside of this crate
+    extensions: Extensions,
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Checkpoint {
     position: Pos,
+    end: Pos,
     off: usize,
 }
 
@@ -43,19 +49,23 @@ impl<'a> StreamOnce for TokenStream<'a> {
     type Error = Errors<Token<'a>, Token<'a>, Pos>;
 
     fn uncons(&mut self) -> Result<Self::Item, Error<Token<'a>, Token<'a>>> {
-        if let Some((at, tok, off, pos)) = self.next_state {
+        if let Some((at, tok, off, pos, end)) = self.next_state {
             if at == self.off {
                 self.off = off;
                 self.position = pos;
+                self.end = end;
                 return Ok(tok);
             }
         }
         let old_pos = self.off;
         let (kind, len) = self.peek_token()?;
         let value = &self.buf[self.off-len..self.off];
+        self.position.offset = self.off;
+        self.end = self.position;
         self.skip_whitespace();
         let token = Token { kind, value };
-        self.next_state = Some((old_pos, token, self.off, self.position));
+        self.next_state = Some((old_pos, token, self.off, self.position,
+                                self.end));
         Ok(token)
     }
 }
@@ -71,27 +81,51 @@ impl<'a> Resetable for TokenStream<'a> {
     fn checkpoint(&self) -> Self::Checkpoint {
         Checkpoint {
             position: self.position,
+            end: self.end,
             off: self.off,
         }
     }
     fn reset(&mut self, checkpoint: Checkpoint) {
         self.position = checkpoint.position;
+        self.end = checkpoint.end;
         self.off = checkpoint.off;
     }
 }
 
 impl<'a> TokenStream<'a> {
-    pub fn new(s: &str) -> TokenStream {
+    #[cfg(test)]
+    pub fn new(s: &str) -> TokenStream<'_> {
+        TokenStream::with_extensions(s, Extensions::new())
+    }
+
+    pub fn with_extensions(s: &str, extensions: Extensions)
+        -> TokenStream<'_>
+    {
+        let start = Pos { line: 1, column: 1, offset: 0 };
         let mut me = TokenStream {
             buf: s,
-            position: Pos { line: 1, column: 1 },
+            position: start,
+            end: start,
             off: 0,
             next_state: None,
+            extensions,
         };
         me.skip_whitespace();
         me
     }
 
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    /// Position right after the last token consumed
+    ///
+    /// Unlike `position()` this doesn't include whitespace and comments
+    /// following the token.
+    pub fn end_position(&self) -> Pos {
+        self.end
+    }
+
     fn peek_token(&mut self)
         -> Result<(Kind, usize), Error<Token<'a>, Token<'a>>>
     {
@@ -125,6 +159,10 @@ impl<'a> TokenStream<'a> {
                 for (idx, cur_char) in iter {
                     nchars += 1;
                     match cur_char {
+                        '\\' if prev_char == '\\' => {
+                            prev_char = ' ';  // reset pending escape
+                            continue;
+                        }
                         x if x == open_quote && prev_char != '\\' => {
                             self.position.column += nchars;
                             self.off += idx+1;
@@ -153,7 +191,7 @@ impl<'a> TokenStream<'a> {
                         '\r' | '\t' | '\n' => {
                             self.position.column += nchars;
                             self.off += idx;
-                            return Ok((String, nchars));
+                            return Ok((String, idx));
                         }
                         '{' if prev_char == '$' => {
                             while let Some((_, cur_char)) = iter.next() {
@@ -170,7 +208,7 @@ impl<'a> TokenStream<'a> {
                             } else {
                                 self.position.column += nchars;
                                 self.off += idx;
-                                return Ok((String, nchars));
+                                return Ok((String, idx));
                             }
                         }
                         '\\' if prev_char == '\\' => {
@@ -186,7 +224,7 @@ impl<'a> TokenStream<'a> {
                 let len = self.buf.len() - self.off;
                 self.position.column += nchars;
                 self.off += len;
-                return Ok((String, nchars));
+                Ok((String, len))
             }
         }
     }
@@ -200,7 +238,7 @@ impl<'a> TokenStream<'a> {
             };
             match cur_char {
                 '\u{feff}' | '\r' => continue,
-                '\t' => self.position.column += 8,
+                '\t' => self.position.column += 1,
                 '\n' => {
                     self.position.column = 1;
                     self.position.line += 1;
@@ -212,7 +250,7 @@ impl<'a> TokenStream<'a> {
                 //comment
                 '#' => {
                     while let Some((_, cur_char)) = iter.next() {
-                        if cur_char == '\r' || cur_char == '\n' {
+                        if cur_char == '\n' {
                             self.position.column = 1;
                             self.position.line += 1;
                             break;
@@ -224,6 +262,7 @@ impl<'a> TokenStream<'a> {
             }
         };
         self.off += idx;
+        self.position.offset = self.off;
     }
 }
 
@@ -237,6 +276,7 @@ impl<'a> fmt::Display for Token<'a> {
 mod test {
     use super::{Kind, TokenStream};
     use super::Kind::*;
+    use position::Pos;
     use combine::easy::Error;
 
     use combine::{StreamOnce, Positioned};
@@ -293,4 +333,17 @@ mod test {
         assert_eq!(tok_typ("proxy_pass http://${a b};"),
                    [String, String, Semicolon]);
     }
+
+    #[test]
+    fn non_ascii() {
+        assert_eq!(tok_str("root /caf\u{e9};"), ["root", "/caf\u{e9}", ";"]);
+    }
+
+    #[test]
+    fn positions() {
+        let mut s = TokenStream::new("\ta\t# x\r\n  b;");
+        s.uncons().unwrap();
+        assert_eq!(s.end_position(), Pos { line: 1, column: 3, offset: 2 });
+        assert_eq!(s.position(), Pos { line: 2, column: 3, offset: 10 });
+    }
 }
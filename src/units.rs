@@ -0,0 +1,273 @@
+//! Sizes, durations and rates in nginx syntax
+use std::error::Error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use std::time;
+
+
+const SIZE_UNITS: &[(char, u64)] = &[
+    ('g', 1 << 30),
+    ('m', 1 << 20),
+    ('k', 1 << 10),
+];
+
+const TIME_UNITS: &[(&str, u64)] = &[
+    ("y", 365*24*60*60*1000),
+    ("M", 30*24*60*60*1000),
+    ("w", 7*24*60*60*1000),
+    ("d", 24*60*60*1000),
+    ("h", 60*60*1000),
+    ("m", 60*1000),
+    ("s", 1000),
+    ("ms", 1),
+];
+
+/// Units used when displaying a duration which wasn't parsed from config
+const DISPLAY_TIME_UNITS: &[(&str, u64)] = &[
+    ("d", 24*60*60*1000),
+    ("h", 60*60*1000),
+    ("m", 60*1000),
+    ("s", 1000),
+    ("ms", 1),
+];
+
+
+/// Size in bytes, e.g. `512`, `16k`, `10m`, `1g`
+///
+/// Original spelling is kept for display, but comparison is made by the
+/// number of bytes, so `1024` equals `1k`.
+#[derive(Debug, Clone)]
+pub struct Size {
+    bytes: u64,
+    original: Option<String>,
+}
+
+/// Time interval, e.g. `30s`, `100ms`, `1h30m`
+///
+/// A number without a unit means seconds. Like [`Size`](struct.Size.html)
+/// the original spelling is kept for display and comparison is made by
+/// value.
+#[derive(Debug, Clone)]
+pub struct Duration {
+    millis: u64,
+    original: Option<String>,
+}
+
+/// Request rate of `limit_req_zone`, e.g. `10r/s` or `30r/m`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rate {
+    /// `Nr/s`
+    PerSecond(u32),
+    /// `Nr/m`
+    PerMinute(u32),
+}
+
+/// Error parsing size, duration, rate or version
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseUnitError {
+    kind: &'static str,
+    value: String,
+}
+
+impl ParseUnitError {
+    pub(crate) fn new(kind: &'static str, value: &str) -> ParseUnitError {
+        ParseUnitError { kind, value: value.to_string() }
+    }
+}
+
+impl fmt::Display for ParseUnitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid {} {:?}", self.kind, self.value)
+    }
+}
+
+impl Error for ParseUnitError {}
+
+impl Size {
+    /// Create size from the number of bytes
+    pub fn from_bytes(bytes: u64) -> Size {
+        Size { bytes, original: None }
+    }
+    /// Number of bytes
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+}
+
+impl FromStr for Size {
+    type Err = ParseUnitError;
+    fn from_str(s: &str) -> Result<Size, ParseUnitError> {
+        let err = || ParseUnitError::new("size", s);
+        let last = s.chars().last().ok_or_else(err)?;
+        let (number, scale) = match SIZE_UNITS.iter()
+            .find(|&&(unit, _)| unit == last.to_ascii_lowercase())
+        {
+            Some(&(_, scale)) => (&s[..s.len()-1], scale),
+            None => (s, 1),
+        };
+        if number.is_empty() || !number.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(err());
+        }
+        let bytes = number.parse::<u64>().ok()
+            .and_then(|n| n.checked_mul(scale))
+            .ok_or_else(err)?;
+        Ok(Size { bytes, original: Some(s.to_string()) })
+    }
+}
+
+impl fmt::Display for Size {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref original) = self.original {
+            return f.write_str(original);
+        }
+        for &(unit, scale) in SIZE_UNITS {
+            if self.bytes != 0 && self.bytes % scale == 0 {
+                return write!(f, "{}{}", self.bytes / scale, unit);
+            }
+        }
+        write!(f, "{}", self.bytes)
+    }
+}
+
+impl PartialEq for Size {
+    fn eq(&self, other: &Size) -> bool {
+        self.bytes == other.bytes
+    }
+}
+
+impl Eq for Size {}
+
+impl Hash for Size {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bytes.hash(state)
+    }
+}
+
+impl Duration {
+    /// Create duration from the number of milliseconds
+    pub fn from_millis(millis: u64) -> Duration {
+        Duration { millis, original: None }
+    }
+    /// Create duration from the number of seconds
+    ///
+    /// Saturates at `u64::MAX` milliseconds instead of overflowing.
+    pub fn from_secs(secs: u64) -> Duration {
+        Duration::from_millis(secs.saturating_mul(1000))
+    }
+    /// Number of milliseconds
+    pub fn as_millis(&self) -> u64 {
+        self.millis
+    }
+    /// Replaces the original spelling used for display
+    pub(crate) fn spelled(self, original: &str) -> Duration {
+        Duration { original: Some(original.to_string()), ..self }
+    }
+}
+
+impl FromStr for Duration {
+    type Err = ParseUnitError;
+    fn from_str(s: &str) -> Result<Duration, ParseUnitError> {
+        let err = || ParseUnitError::new("time", s);
+        let mut millis = 0u64;
+        // nginx requires units to go from the largest to the smallest
+        let mut allowed = 0;
+        let mut rest = s.trim_start();
+        if rest.is_empty() {
+            return Err(err());
+        }
+        while !rest.is_empty() {
+            let digits = rest.bytes().take_while(|b| b.is_ascii_digit())
+                .count();
+            if digits == 0 {
+                return Err(err());
+            }
+            let number = rest[..digits].parse::<u64>().map_err(|_| err())?;
+            rest = &rest[digits..];
+            let unit_len = rest.bytes().take_while(|b| b.is_ascii_alphabetic())
+                .count();
+            let unit = &rest[..unit_len];
+            rest = rest[unit_len..].trim_start();
+            let scale = if unit.is_empty() {
+                // bare number means seconds and must be the last one
+                if !rest.is_empty() {
+                    return Err(err());
+                }
+                allowed = TIME_UNITS.len();
+                1000
+            } else {
+                let idx = TIME_UNITS[allowed..].iter()
+                    .position(|&(name, _)| name == unit)
+                    .ok_or_else(err)?;
+                allowed += idx + 1;
+                TIME_UNITS[allowed - 1].1
+            };
+            millis = number.checked_mul(scale)
+                .and_then(|n| n.checked_add(millis))
+                .ok_or_else(err)?;
+        }
+        Ok(Duration { millis, original: Some(s.to_string()) })
+    }
+}
+
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref original) = self.original {
+            return f.write_str(original);
+        }
+        if self.millis == 0 {
+            return f.write_str("0s");
+        }
+        let mut left = self.millis;
+        for &(unit, scale) in DISPLAY_TIME_UNITS {
+            if left >= scale {
+                write!(f, "{}{}", left / scale, unit)?;
+                left %= scale;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq for Duration {
+    fn eq(&self, other: &Duration) -> bool {
+        self.millis == other.millis
+    }
+}
+
+impl Eq for Duration {}
+
+impl Hash for Duration {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.millis.hash(state)
+    }
+}
+
+impl From<Duration> for time::Duration {
+    fn from(d: Duration) -> time::Duration {
+        time::Duration::from_millis(d.millis)
+    }
+}
+
+impl FromStr for Rate {
+    type Err = ParseUnitError;
+    fn from_str(s: &str) -> Result<Rate, ParseUnitError> {
+        let err = || ParseUnitError::new("rate", s);
+        if let Some(num) = s.strip_suffix("r/s") {
+            num.parse().map(Rate::PerSecond).map_err(|_| err())
+        } else if let Some(num) = s.strip_suffix("r/m") {
+            num.parse().map(Rate::PerMinute).map_err(|_| err())
+        } else {
+            Err(err())
+        }
+    }
+}
+
+impl fmt::Display for Rate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Rate::PerSecond(n) => write!(f, "{}r/s", n),
+            Rate::PerMinute(n) => write!(f, "{}r/m", n),
+        }
+    }
+}
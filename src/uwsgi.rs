@@ -0,0 +1,39 @@
+use combine::{Parser};
+use combine::{choice};
+
+use ast::Item;
+use grammar::{value, bool, duration};
+use helpers::{semi, ident};
+use proxy::{param, cache_valid, next_upstream, buffers};
+use tokenizer::TokenStream;
+
+
+pub fn directives<'a>()
+    -> impl Parser<Output=Item<'a>, Input=TokenStream<'a>>
+{
+    choice((
+        ident("uwsgi_pass").with(value()).skip(semi())
+            .map(Item::UwsgiPass),
+        param("uwsgi_param").map(Item::UwsgiParam),
+        ident("uwsgi_read_timeout").with(duration()).skip(semi())
+            .map(Item::UwsgiReadTimeout),
+        ident("uwsgi_connect_timeout").with(duration()).skip(semi())
+            .map(Item::UwsgiConnectTimeout),
+        ident("uwsgi_send_timeout").with(duration()).skip(semi())
+            .map(Item::UwsgiSendTimeout),
+        ident("uwsgi_buffering").with(bool()).skip(semi())
+            .map(Item::UwsgiBuffering),
+        ident("uwsgi_buffers").with(buffers()).skip(semi())
+            .map(Item::UwsgiBuffers),
+        ident("uwsgi_intercept_errors").with(bool()).skip(semi())
+            .map(Item::UwsgiInterceptErrors),
+        ident("uwsgi_next_upstream").with(next_upstream()).skip(semi())
+            .map(Item::UwsgiNextUpstream),
+        ident("uwsgi_cache").with(value()).skip(semi())
+            .map(Item::UwsgiCache),
+        ident("uwsgi_cache_key").with(value()).skip(semi())
+            .map(Item::UwsgiCacheKey),
+        ident("uwsgi_cache_valid").with(cache_valid()).skip(semi())
+            .map(Item::UwsgiCacheValid),
+    ))
+}
@@ -0,0 +1,288 @@
+//! Semantic checks of the parsed config
+//!
+//! Parser only checks the syntax of each directive. Functions in this module
+//! look deeper: compile patterns or look at multiple directives at once.
+use std::collections::HashSet;
+
+use ast::{Directive, Item, LocationPattern};
+use position::Pos;
+use registry::{self, Context};
+use visitors::DirectiveIter;
+
+
+/// A problem found by one of the checks
+#[derive(Fail, Debug, Clone, PartialEq, Eq)]
+#[fail(display="{}: {}", position, message)]
+pub struct Error {
+    /// Position of the directive that has the problem
+    pub position: Pos,
+    /// Human-readable description of the problem
+    pub message: String,
+}
+
+impl Error {
+    pub(crate) fn new<S: Into<String>>(position: Pos, message: S) -> Error {
+        Error { position, message: message.into() }
+    }
+}
+
+/// Run all checks enabled in this build
+///
+/// Errors are returned in the order of directives in the config.
+pub fn validate(directives: &[Directive]) -> Vec<Error> {
+    let mut errors = Vec::new();
+    #[cfg(feature="regex")]
+    errors.extend(check_regexes(directives));
+    errors.extend(check_named_locations(directives));
+    errors.extend(check_limit_zones(directives));
+    errors.extend(check_extensions(directives));
+    errors.sort_by_key(|e| e.position);
+    errors
+}
+
+/// Returns regular expressions used by the directive
+///
+/// Each pattern is returned along with the case-insensitivity flag.
+#[cfg(feature="regex")]
+fn regexes<'a>(dir: &'a Directive<'a>) -> Vec<(&'a str, bool)> {
+    use ast::Item::*;
+    use ast::{ServerName, MapPattern, IfCondition};
+    match dir.item {
+        Location(ref loc) => match loc.pattern {
+            LocationPattern::Regex(ref p, _) => vec![(&p[..], false)],
+            LocationPattern::RegexInsensitive(ref p, _) => {
+                vec![(&p[..], true)]
+            }
+            _ => Vec::new(),
+        },
+        // nginx always matches server names case-insensitively
+        ServerName(ref names) => names.iter().filter_map(|n| match *n {
+            ServerName::Regex(ref p) => Some((&p[..], true)),
+            _ => None,
+        }).collect(),
+        Map(ref map) => map.patterns.iter().filter_map(|(p, _)| {
+            match *p {
+                MapPattern::Regex(ref p, _) if p.starts_with('*') => {
+                    Some((&p[1..], true))
+                }
+                MapPattern::Regex(ref p, _) => Some((&p[..], false)),
+                _ => None,
+            }
+        }).collect(),
+        Rewrite(ref rw) => vec![(&rw.regex[..], false)],
+        If(ref cond) => match cond.condition {
+            | IfCondition::RegEq(_, ref p, case, _)
+            | IfCondition::RegNeq(_, ref p, case, _)
+            => vec![(&p[..], !case)],
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// Compile all regular expressions and check references to their captures
+///
+/// Checks location, server_name, map, rewrite and if patterns. Also checks
+/// that `rewrite` replacement doesn't refer to a numeric capture which is
+/// not in the regex.
+///
+/// References to named captures (`$name` for `(?<name>...)`) are not
+/// checked: they can't be told apart from ordinary variables like `$host`,
+/// so a `$name` without a matching group is left alone.
+#[cfg(feature="regex")]
+pub fn check_regexes(directives: &[Directive]) -> Vec<Error> {
+    use regex::Regex;
+
+    let mut errors = Vec::new();
+    for dir in DirectiveIter::depth_first(directives) {
+        for (pattern, case_insensitive) in regexes(dir) {
+            let regex = match Regex::new(pattern, case_insensitive) {
+                Ok(regex) => regex,
+                Err(e) => {
+                    errors.push(Error::new(dir.position, e.to_string()));
+                    continue;
+                }
+            };
+            if let Item::Rewrite(ref rw) = dir.item {
+                for var in rw.replacement.variables() {
+                    if var.parse::<usize>().is_ok() && !regex.has_capture(var)
+                    {
+                        errors.push(Error::new(dir.position, format!(
+                            "rewrite replacement refers to ${} but \
+                             regex {:?} has only {} capture group(s)",
+                            var, pattern, regex.captures_len())));
+                    }
+                }
+            }
+        }
+    }
+    errors
+}
+
+/// Returns the name of the named location (without `@`) the directive
+/// jumps to, if any
+fn named_location_ref<'a>(dir: &'a Directive<'a>) -> Option<&'a str> {
+    use ast::{Return, TryFilesLastOption};
+    use value::Item::Literal;
+
+    let value = match dir.item {
+        Item::TryFiles(ref tf) => match tf.last_option {
+            TryFilesLastOption::NamedLocation(ref name) => return Some(name),
+            _ => return None,
+        },
+        Item::ErrorPage(ref ep) => &ep.uri,
+        Item::Return(Return::Redirect { ref url, .. }) => url,
+        _ => return None,
+    };
+    match value.data[..] {
+        [Literal(ref x)] if x.starts_with('@') => Some(&x[1..]),
+        _ => None,
+    }
+}
+
+fn named_locations_in_server(server: &[Directive], errors: &mut Vec<Error>) {
+    let mut defined = HashSet::new();
+    for dir in server {
+        if let Item::Location(ref loc) = dir.item {
+            if let LocationPattern::Named(ref name) = loc.pattern {
+                defined.insert(&name[..]);
+            }
+        }
+    }
+    for dir in server {
+        let nested = dir.item.children().unwrap_or(&[]);
+        for sub in DirectiveIter::depth_first(nested) {
+            if let Item::Location(ref loc) = sub.item {
+                if let LocationPattern::Named(ref name) = loc.pattern {
+                    errors.push(Error::new(sub.position, format!(
+                        "named location @{} must be defined at server level",
+                        name)));
+                }
+            }
+        }
+    }
+    for dir in DirectiveIter::depth_first(server) {
+        if let Some(name) = named_location_ref(dir) {
+            if !defined.contains(name) {
+                errors.push(Error::new(dir.position, format!(
+                    "{} refers to undefined named location @{}",
+                    dir.item.directive_name(), name)));
+            }
+        }
+    }
+}
+
+/// Check that every `@name` used in `try_files`, `error_page` and `return`
+/// refers to a named location defined in the same server
+///
+/// Also checks that named locations are defined at server level. References
+/// outside of any `server` block are not checked.
+pub fn check_named_locations(directives: &[Directive]) -> Vec<Error> {
+    let mut errors = Vec::new();
+    for dir in DirectiveIter::depth_first(directives) {
+        if let Item::Server(ref srv) = dir.item {
+            named_locations_in_server(&srv.directives, &mut errors);
+        }
+    }
+    errors
+}
+
+/// Check that every zone used in `limit_req` and `limit_conn` is declared
+/// by `limit_req_zone` or `limit_conn_zone` respectively
+///
+/// Zones are global, so declaration may be anywhere in the config.
+pub fn check_limit_zones(directives: &[Directive]) -> Vec<Error> {
+    let mut req_zones = HashSet::new();
+    let mut conn_zones = HashSet::new();
+    for dir in DirectiveIter::depth_first(directives) {
+        match dir.item {
+            Item::LimitReqZone(ref z) => { req_zones.insert(&z.name[..]); }
+            Item::LimitConnZone(ref z) => { conn_zones.insert(&z.name[..]); }
+            _ => {}
+        }
+    }
+    let mut errors = Vec::new();
+    for dir in DirectiveIter::depth_first(directives) {
+        let (zone, declared, declaration) = match dir.item {
+            Item::LimitReq(ref lim) => {
+                (&lim.zone, &req_zones, "limit_req_zone")
+            }
+            Item::LimitConn { ref zone, .. } => {
+                (zone, &conn_zones, "limit_conn_zone")
+            }
+            _ => continue,
+        };
+        if !declared.contains(&zone[..]) {
+            errors.push(Error::new(dir.position, format!(
+                "{} refers to zone {:?} which is not declared by {}",
+                dir.item.directive_name(), zone, declaration)));
+        }
+    }
+    errors
+}
+
+/// Run checks of directives registered with [`Extensions::register`]
+///
+/// See [`ExtensionDirective::validate`].
+///
+/// [`Extensions::register`]: ../extension/struct.Extensions.html#method.register
+/// [`ExtensionDirective::validate`]: ../extension/trait.ExtensionDirective.html#method.validate
+pub fn check_extensions(directives: &[Directive]) -> Vec<Error> {
+    let mut errors = Vec::new();
+    for dir in DirectiveIter::depth_first(directives) {
+        if let Item::Extension(ref ext) = dir.item {
+            errors.extend(ext.directive.validate().into_iter()
+                .map(|message| Error::new(dir.position, message)));
+        }
+    }
+    errors
+}
+
+/// Context of the block of the directive, `None` if the directive has no
+/// block or its context isn't known
+fn block_context(parent: Context, item: &Item) -> Option<Context> {
+    match *item {
+        Item::Http(..) => Some(Context::Http),
+        Item::Server(..) => Some(Context::Server),
+        Item::Location(..) => Some(Context::Location),
+        Item::If(..) if parent == Context::Server => Some(Context::ServerIf),
+        Item::If(..) => Some(Context::LocationIf),
+        Item::LimitExcept(..) => Some(Context::LimitExcept),
+        _ => None,
+    }
+}
+
+/// Check that directives are allowed in the block they are in
+///
+/// `directives` are assumed to be in the `context` block, contexts of the
+/// nested blocks are derived from it. Allowed contexts are taken from the
+/// [`registry`], directives missing there and children of blocks which
+/// aren't described by [`Context`] (like extension ones) are not checked.
+///
+/// [`registry`]: ../registry/index.html
+/// [`Context`]: ../registry/enum.Context.html
+pub fn check_context(context: Context, directives: &[Directive])
+    -> Vec<Error>
+{
+    let mut errors = Vec::new();
+    check_context_in(context, directives, &mut errors);
+    errors
+}
+
+fn check_context_in(context: Context, directives: &[Directive],
+    errors: &mut Vec<Error>)
+{
+    for dir in directives {
+        let name = dir.item.directive_name();
+        if let Some(info) = registry::lookup(name) {
+            if !info.allowed_in(context) {
+                errors.push(Error::new(dir.position, format!(
+                    "{} is not allowed in {}", name, context)));
+            }
+        }
+        let inner = block_context(context, &dir.item);
+        if let (Some(inner), Some(children)) = (inner, dir.item.children()) {
+            check_context_in(inner, children, errors);
+        }
+    }
+}
@@ -1,11 +1,11 @@
-use std::mem;
+use std::borrow::Cow;
 use std::str::FromStr;
 
 use combine::easy::Error;
 use combine::error::StreamError;
 
-use format::{Displayable, Formatter};
-use position::Pos;
+use format::{Displayable, Formatter, Quotes};
+use position::{Pos, Span};
 use tokenizer::Token;
 
 /// Generic string value
@@ -13,232 +13,416 @@ use tokenizer::Token;
 /// It may consist of strings and variable references
 ///
 /// Some string parts might originally be escaped or quoted. We get rid of
-/// quotes when parsing
+/// quotes and escapes when parsing, but remember the kind of quotes to
+/// print the value the same way.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Value {
-    position: Pos,
-    pub(crate) data: Vec<Item>,
+pub struct Value<'a> {
+    span: Span,
+    quote: Option<QuoteStyle>,
+    pub(crate) data: Vec<Item<'a>>,
+}
+
+/// Quotes around a value in the source text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuoteStyle {
+    /// Value isn't quoted
+    None,
+    /// Value is in single quotes
+    Single,
+    /// Value is in double quotes
+    Double,
+}
+
+impl QuoteStyle {
+    /// Quotes the token is written with
+    pub(crate) fn of(token: &str) -> QuoteStyle {
+        match token.chars().next() {
+            Some('"') => QuoteStyle::Double,
+            Some('\'') => QuoteStyle::Single,
+            _ => QuoteStyle::None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub(crate) enum Item {
-    Literal(String),
-    Variable(String),
+pub(crate) enum Item<'a> {
+    Literal(Cow<'a, str>),
+    Variable(Cow<'a, str>),
 }
 
 
-impl Value {
-    pub(crate) fn parse<'a>(position: Pos, tok: Token<'a>)
-        -> Result<Value, Error<Token<'a>, Token<'a>>>
+impl<'a> Value<'a> {
+    pub(crate) fn parse(span: Span, tok: Token<'a>)
+        -> Result<Value<'a>, Error<Token<'a>, Token<'a>>>
     {
-        Value::parse_str(position, tok.value)
+        Value::parse_str(span, tok.value)
     }
-    pub(crate) fn parse_str<'a>(position: Pos, token: &str)
-        -> Result<Value, Error<Token<'a>, Token<'a>>>
+    pub(crate) fn parse_str(span: Span, token: &'a str)
+        -> Result<Value<'a>, Error<Token<'a>, Token<'a>>>
     {
-        let data = if token.starts_with('"') {
-            Value::scan_quoted('"', token)?
-        } else if token.starts_with("'") {
-            Value::scan_quoted('\'', token)?
-        } else {
-            Value::scan_raw(token)?
+        let quote = token.chars().next().filter(|&c| c == '"' || c == '\'');
+        let data = match quote {
+            Some(quote) => {
+                let body = token[1..].strip_suffix(quote)
+                    .filter(|_| token.len() > 1)
+                    .ok_or_else(|| Error::unexpected_message(
+                        "unclosed quote"))?;
+                Value::scan(body, Some(quote))?
+            }
+            None => Value::scan(token, None)?,
         };
-        Ok(Value { position, data })
+        let quote = match quote {
+            Some('"') => QuoteStyle::Double,
+            Some(_) => QuoteStyle::Single,
+            None => QuoteStyle::None,
+        };
+        Ok(Value { span, quote: Some(quote), data })
     }
 
-    fn scan_raw<'a>(value: &str)
-        -> Result<Vec<Item>, Error<Token<'a>, Token<'a>>>
+    /// Splits (unquoted) value into literals and variables
+    ///
+    /// Escapes are replaced the same way nginx does. There is no escape for
+    /// a dollar sign, so `\$x` is a backslash followed by variable `x`.
+    /// Literals without escapes and variable names are borrowed from the
+    /// source.
+    fn scan(value: &'a str, quote: Option<char>)
+        -> Result<Vec<Item<'a>>, Error<Token<'a>, Token<'a>>>
     {
         use self::Item::*;
         let mut buf = Vec::new();
+        // literal is `value[lit_start..]` until it has escapes
+        let mut lit_start = 0;
+        let mut owned: Option<String> = None;
         let mut chiter = value.char_indices().peekable();
-        let mut prev_char = ' ';  // any having no special meaning
-        let mut cur_slice = 0;
-        // TODO(unquote) single and double quotes
         while let Some((idx, cur_char)) = chiter.next() {
             match cur_char {
-                _ if prev_char == '\\' => {
-                    prev_char = ' ';
-                    continue;
+                '\\' => {
+                    let next = chiter.peek().map(|&(_, c)| c);
+                    match next.and_then(unescape_char) {
+                        Some(c) => {
+                            chiter.next();
+                            owned.get_or_insert_with(|| {
+                                value[lit_start..idx].to_string()
+                            }).push(c);
+                        }
+                        None => {
+                            if let Some(ref mut lit) = owned {
+                                lit.push('\\');
+                            }
+                        }
+                    }
                 }
                 '$' => {
-                    let vstart = idx + 1;
-                    if idx != cur_slice {
-                        buf.push(Literal(value[cur_slice..idx].to_string()));
-                    }
-                    let fchar = chiter.next().map(|(_, c)| c)
+                    push_literal(&mut buf, &value[lit_start..idx],
+                                 owned.take());
+                    let (vstart, fchar) = chiter.next()
                         .ok_or_else(|| Error::unexpected_message(
                             "bare $ in expression"))?;
-                    match fchar {
-                        '{' => {
-                            while let Some(&(_, c)) = chiter.peek() {
-                                match c {
-                                    'a'...'z' | 'A'...'Z' | '_' | '0'...'9'
-                                    => chiter.next(),
-                                    '}' => break,
-                                    _ => {
-                                        return Err(Error::expected("}".into()));
-                                    }
-                                };
-                            }
-                            let now = chiter.peek().map(|&(idx, _)| idx)
-                                .unwrap();
-                            buf.push(Variable(
-                                value[vstart+1..now].to_string()));
-                            cur_slice = now+1;
-                        }
-                        'a'...'z' | 'A'...'Z' | '_' | '0'...'9' => {
-                            while let Some(&(_, c)) = chiter.peek() {
-                                match c {
-                                    'a'...'z' | 'A'...'Z' | '_' | '0'...'9'
-                                    => chiter.next(),
-                                    _ => break,
-                                };
-                            }
-                            let now = chiter.peek().map(|&(idx, _)| idx)
-                                .unwrap_or(value.len());
-                            buf.push(Variable(
-                                value[vstart..now].to_string()));
-                            cur_slice = now;
+                    let braced = fchar == '{';
+                    if !braced && !is_var_char(fchar) {
+                        return Err(Error::unexpected_message(
+                            format!("variable name starts with \
+                                bad char {:?}", fchar)));
+                    }
+                    let start = if braced { vstart + 1 } else { vstart };
+                    let mut end = value.len();
+                    while let Some(&(idx, c)) = chiter.peek() {
+                        if !is_var_char(c) {
+                            end = idx;
+                            break;
                         }
-                        _ => {
-                            return Err(Error::unexpected_message(
-                                format!("variable name starts with \
-                                    bad char {:?}", fchar)));
+                        chiter.next();
+                    }
+                    lit_start = end;
+                    if braced {
+                        match chiter.next() {
+                            Some((_, '}')) => lit_start += 1,
+                            _ => return Err(Error::expected("}".into())),
                         }
                     }
+                    buf.push(Variable(Cow::Borrowed(&value[start..end])));
+                }
+                c if Some(c) == quote => {
+                    return Err(Error::unexpected_message(
+                        "quote closes prematurely"));
+                }
+                c => {
+                    if let Some(ref mut lit) = owned {
+                        lit.push(c);
+                    }
                 }
-                _ => {}
             }
-            prev_char = cur_char;
-        }
-        if cur_slice != value.len() {
-            buf.push(Literal(value[cur_slice..].to_string()));
         }
+        push_literal(&mut buf, &value[lit_start..], owned);
         Ok(buf)
     }
 
-    fn scan_quoted<'a>(quote: char, value: &str)
-        -> Result<Vec<Item>, Error<Token<'a>, Token<'a>>>
-    {
-        use self::Item::*;
-        let mut buf = Vec::new();
-        let mut chiter = value.char_indices().peekable();
-        chiter.next(); // skip quote
-        let mut prev_char = ' ';  // any having no special meaning
-        let mut cur_slice = String::new();
-        while let Some((idx, cur_char)) = chiter.next() {
-            match cur_char {
-                _ if prev_char == '\\' => {
-                    cur_slice.push(cur_char);
-                    continue;
-                }
-                '"' | '\'' if cur_char == quote => {
-                    if cur_slice.len() > 0 {
-                        buf.push(Literal(cur_slice));
-                    }
-                    if idx + 1 != value.len() {
-                        // TODO(tailhook) figure out maybe this is actually a
-                        // tokenizer error, or maybe make this cryptic message
-                        // better
-                        return Err(Error::unexpected_message(
-                            "quote closes prematurely"));
-                    }
-                    return Ok(buf);
-                }
-                '$' => {
-                    let vstart = idx + 1;
-                    if cur_slice.len() > 0 {
-                        buf.push(Literal(
-                            mem::replace(&mut cur_slice, String::new())));
-                    }
-                    let fchar = chiter.next().map(|(_, c)| c)
-                        .ok_or_else(|| Error::unexpected_message(
-                            "bare $ in expression"))?;
-                    match fchar {
-                        '{' => {
-                            unimplemented!();
-                        }
-                        'a'...'z' | 'A'...'Z' | '_' | '0'...'9' => {
-                            while let Some(&(_, c)) = chiter.peek() {
-                                match c {
-                                    'a'...'z' | 'A'...'Z' | '_' | '0'...'9'
-                                    => chiter.next(),
-                                    _ => break,
-                                };
-                            }
-                            let now = chiter.peek().map(|&(idx, _)| idx)
-                                .ok_or_else(|| {
-                                    Error::unexpected_message("unclosed quote")
-                                })?;
-                            buf.push(Variable(
-                                value[vstart..now].to_string()));
-                        }
-                        _ => {
-                            return Err(Error::unexpected_message(
-                                format!("variable name starts with \
-                                    bad char {:?}", fchar)));
-                        }
-                    }
-                }
-                _ => cur_slice.push(cur_char),
+    /// Converts value into one which doesn't borrow the source text
+    pub fn into_owned(self) -> Value<'static> {
+        Value {
+            span: self.span,
+            quote: self.quote,
+            data: self.data.into_iter().map(|item| match item {
+                Item::Literal(v) => Item::Literal(v.into_owned().into()),
+                Item::Variable(v) => Item::Variable(v.into_owned().into()),
+            }).collect(),
+        }
+    }
+}
+
+fn push_literal<'a>(buf: &mut Vec<Item<'a>>, borrowed: &'a str,
+    owned: Option<String>)
+{
+    let literal = match owned {
+        Some(lit) => Cow::Owned(lit),
+        None => Cow::Borrowed(borrowed),
+    };
+    if !literal.is_empty() {
+        buf.push(Item::Literal(literal));
+    }
+}
+
+fn is_var_char(c: char) -> bool {
+    matches!(c, 'a'..='z' | 'A'..='Z' | '_' | '0'..='9')
+}
+
+/// Returns the char denoted by `\c` in nginx config
+fn unescape_char(c: char) -> Option<char> {
+    match c {
+        '"' | '\'' | '\\' => Some(c),
+        't' => Some('\t'),
+        'r' => Some('\r'),
+        'n' => Some('\n'),
+        _ => None,
+    }
+}
+
+/// Strips quotes and replaces escapes the same way nginx does
+///
+/// This is used for strings which can't contain variables, like regular
+/// expressions or location prefixes.
+pub(crate) fn unquote(token: &str) -> Cow<'_, str> {
+    let body = match token.chars().next() {
+        Some(q @ '"') | Some(q @ '\'') if token.len() > 1 &&
+            token.ends_with(q)
+        => &token[1..token.len()-1],
+        _ => token,
+    };
+    if !body.contains('\\') {
+        return Cow::Borrowed(body);
+    }
+    let mut result = String::with_capacity(body.len());
+    let mut chars = body.chars().peekable();
+    while let Some(c) = chars.next() {
+        match chars.peek().cloned().and_then(unescape_char) {
+            Some(escaped) if c == '\\' => {
+                chars.next();
+                result.push(escaped);
+            }
+            _ => result.push(c),
+        }
+    }
+    Cow::Owned(result)
+}
+
+/// Whether the string must be quoted to be read back as a single token
+fn needs_quotes(s: &str, starts_token: bool) -> bool {
+    starts_token && s.starts_with('#') ||
+    s.chars().any(|c| matches!(c,
+        ' ' | ';' | '\r' | '\n' | '\t' | '{' | '}' | '"' | '\''))
+}
+
+/// Appends escaped string to `buf`
+///
+/// Backslash is doubled only when it's followed by something that would
+/// be unescaped (`next` is the char following the string, if any), so
+/// regular expressions like `\.php$` are kept intact.
+fn escape_into(buf: &mut String, s: &str, quote: Option<char>,
+    next: Option<char>)
+{
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                let after = chars.peek().cloned().or(next);
+                let special = after.map_or(true,
+                    |a| unescape_char(a).is_some());
+                buf.push_str(if special { "\\\\" } else { "\\" });
+            }
+            '\t' => buf.push_str("\\t"),
+            '\r' => buf.push_str("\\r"),
+            '\n' => buf.push_str("\\n"),
+            c if Some(c) == quote => {
+                buf.push('\\');
+                buf.push(c);
             }
-            prev_char = cur_char;
+            c => buf.push(c),
         }
-        return Err(Error::unexpected_message("unclosed quote"));
     }
 }
 
-impl FromStr for Value {
+/// Quotes and escapes string which can't contain variables
+///
+/// This is the reverse of [`unquote`](fn.unquote.html).
+pub(crate) fn quote(s: &str) -> Cow<'_, str> {
+    let quoted = s.is_empty() || needs_quotes(s, true);
+    if !quoted && !s.contains('\\') {
+        return Cow::Borrowed(s);
+    }
+    let quote = if quoted { Some('"') } else { None };
+    let mut buf = String::with_capacity(s.len() + 2);
+    buf.extend(quote);
+    escape_into(&mut buf, s, quote, None);
+    buf.extend(quote);
+    Cow::Owned(buf)
+}
+
+/// Quotes and escapes string which can't contain variables using `style`
+///
+/// Unquoted style falls back to double quotes if the string can't be
+/// written without them.
+pub(crate) fn quote_as(s: &str, style: QuoteStyle) -> Cow<'_, str> {
+    let quote = match style {
+        QuoteStyle::None => return quote(s),
+        QuoteStyle::Single => '\'',
+        QuoteStyle::Double => '"',
+    };
+    let mut buf = String::with_capacity(s.len() + 2);
+    buf.push(quote);
+    escape_into(&mut buf, s, Some(quote), None);
+    buf.push(quote);
+    Cow::Owned(buf)
+}
+
+/// Parses value as written in the config
+///
+/// Quotes of the string are kept when displaying the value. Unquoted value
+/// is considered new and is quoted according to the
+/// [`Style`](../struct.Style.html).
+impl<'a> FromStr for Value<'a> {
     type Err = String;
-    fn from_str(s: &str) -> Result<Value, String> {
-        Value::parse_str(Pos { line: 0, column: 0 }, s)
-        .map_err(|e| e.to_string())
+    fn from_str(s: &str) -> Result<Value<'a>, String> {
+        let start = Pos { line: 0, column: 0, offset: 0 };
+        let span = Span { start, end: start };
+        let mut value = Value::parse_str(span, s)
+            .map_err(|e| e.to_string())?;
+        if value.quote == Some(QuoteStyle::None) {
+            value.quote = None;
+        }
+        Ok(value.into_owned())
     }
 }
 
-impl Value {
+impl<'a> Value<'a> {
     fn has_specials(&self) -> bool {
         use self::Item::*;
-        for item in &self.data {
-            match *item {
-                Literal(ref x) => {
-                    for c in x.chars() {
-                        match c {
-                            ' ' | ';' | '\r' | '\n' | '\t' | '{' | '}' => {
-                                return true;
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-                Variable(_) => {}
-            }
-        }
-        return false;
+        self.data.iter().enumerate().any(|(idx, item)| match *item {
+            Literal(ref x) => needs_quotes(x, idx == 0),
+            Variable(_) => false,
+        })
+    }
+
+    /// Returns the range of the source text the value was parsed from
+    ///
+    /// The span includes quotes. Values created in code have an empty
+    /// span at line zero.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Returns quotes the value was written with
+    ///
+    /// Returns `None` for values created in code rather than parsed from
+    /// the config.
+    pub fn quote_style(&self) -> Option<QuoteStyle> {
+        self.quote
+    }
+
+    /// Change quotes used to display the value
+    ///
+    /// `None` means the quotes are chosen by the formatting
+    /// [`Style`](../struct.Style.html). Value is quoted anyway if it
+    /// contains characters which can't be written without quotes.
+    pub fn set_quote_style(&mut self, quote: Option<QuoteStyle>) {
+        self.quote = quote;
+    }
+
+    /// Returns names of the variables referenced in this value
+    ///
+    /// Names are returned without `$` and braces, regex captures are
+    /// returned as numbers (i.e. `1` for `$1`).
+    pub fn variables(&self) -> impl Iterator<Item=&str> {
+        use self::Item::*;
+        self.data.iter().filter_map(|item| match *item {
+            Literal(..) => None,
+            Variable(ref name) => Some(&name[..]),
+        })
     }
 
     /// Replace variable references in this string with literal values
-    pub fn replace_vars<'a, F, S>(&mut self, mut f: F)
+    ///
+    /// nginx has no escape for a dollar sign, so `$name` in the replacement
+    /// becomes a reference to variable `name`, the same as in the config.
+    /// A replacement with a `$` that doesn't start a variable name can't be
+    /// written in the config, such variable is left as is.
+    pub fn replace_vars<'b, F, S>(&mut self, mut f: F)
         where F: FnMut(&str) -> Option<S>,
-              S: AsRef<str> + Into<String> + 'a,
+              S: AsRef<str> + Into<String> + 'b,
     {
         use self::Item::*;
         // TODO(tailhook) join literal blocks
-        for item in &mut self.data {
-            let new_value = match *item {
-                Literal(..) => continue,
-                Variable(ref name) => match f(name) {
-                    Some(value) => value.into(),
-                    None => continue,
-                },
+        let mut data = Vec::with_capacity(self.data.len());
+        for item in self.data.drain(..) {
+            let replaced = match item {
+                Variable(ref name) => f(name)
+                    .and_then(|value| split_vars(value.as_ref())),
+                Literal(..) => None,
             };
-            *item = Literal(new_value);
+            match replaced {
+                Some(items) => data.extend(items),
+                None => data.push(item),
+            }
+        }
+        self.data = data;
+    }
+}
+
+/// Splits replacement text into literals and variables
+///
+/// Returns `None` if there is a `$` which doesn't start a variable name.
+fn split_vars(value: &str) -> Option<Vec<Item<'static>>> {
+    use self::Item::*;
+    let mut buf = Vec::new();
+    let mut rest = value;
+    while let Some(dollar) = rest.find('$') {
+        if dollar > 0 {
+            buf.push(Literal(rest[..dollar].to_string().into()));
         }
+        rest = &rest[dollar+1..];
+        let (name, len) = match rest.strip_prefix('{') {
+            Some(braced) => {
+                let end = braced.find('}')?;
+                (&braced[..end], end + 2)
+            }
+            None => {
+                let end = rest.find(|c| !is_var_char(c))
+                    .unwrap_or(rest.len());
+                (&rest[..end], end)
+            }
+        };
+        if name.is_empty() || !name.chars().all(is_var_char) {
+            return None;
+        }
+        buf.push(Variable(name.to_string().into()));
+        rest = &rest[len..];
+    }
+    if !rest.is_empty() {
+        buf.push(Literal(rest.to_string().into()));
     }
+    Some(buf)
 }
 
-fn next_alphanum(data: &Vec<Item>, index: usize) -> bool {
+fn next_alphanum(data: &[Item], index: usize) -> bool {
     use self::Item::*;
     data.get(index+1).and_then(|item| {
         match item {
@@ -250,42 +434,40 @@ fn next_alphanum(data: &Vec<Item>, index: usize) -> bool {
     }).unwrap_or(false)
 }
 
-impl Displayable for Value {
+impl<'a> Displayable for Value<'a> {
     fn display(&self, f: &mut Formatter) {
         use self::Item::*;
-        if self.data.is_empty() || self.has_specials() {
-            f.write("\"");
-            for (index, item) in self.data.iter().enumerate() {
-                match *item {
-                    // TODO(tailhook) escape special chars
-                    Literal(ref v) => f.write(v),
-                    Variable(ref v) if next_alphanum(&self.data, index) => {
-                        f.write("${");
-                        f.write(v);
-                        f.write("}");
-                    }
-                    Variable(ref v) => {
-                        f.write("$");
-                        f.write(v);
-                    }
+        let needs_quotes = self.data.is_empty() || self.has_specials();
+        let recorded = if f.style().preserve_quotes { self.quote } else { None };
+        let quote = match (recorded, f.style().quotes) {
+            (Some(QuoteStyle::None), _) if !needs_quotes => None,
+            (Some(QuoteStyle::Single), _) => Some('\''),
+            (Some(QuoteStyle::Double), _) => Some('"'),
+            (_, Quotes::Minimal) if needs_quotes => Some('"'),
+            (_, Quotes::Minimal) => None,
+            (_, Quotes::Double) => Some('"'),
+            (_, Quotes::Single) => Some('\''),
+        };
+        let mut buf = String::new();
+        buf.extend(quote);
+        for (index, item) in self.data.iter().enumerate() {
+            match *item {
+                Literal(ref v) => {
+                    let next = self.data.get(index+1).map(|_| '$');
+                    escape_into(&mut buf, v, quote, next);
                 }
-            }
-            f.write("\"");
-        } else {
-            for (index, item) in self.data.iter().enumerate() {
-                match *item {
-                    Literal(ref v) => f.write(v),
-                    Variable(ref v) if next_alphanum(&self.data, index) => {
-                        f.write("${");
-                        f.write(v);
-                        f.write("}");
-                    }
-                    Variable(ref v) => {
-                        f.write("$");
-                        f.write(v);
-                    }
+                Variable(ref v) if next_alphanum(&self.data, index) => {
+                    buf.push_str("${");
+                    buf.push_str(v);
+                    buf.push('}');
+                }
+                Variable(ref v) => {
+                    buf.push('$');
+                    buf.push_str(v);
                 }
             }
         }
+        buf.extend(quote);
+        f.write(&buf);
     }
 }
@@ -1,15 +1,17 @@
 //! Various visitors for working with AST
 use std::collections::VecDeque;
 
-use ast::Directive;
+use ast::{Directive, Http, Server, Location, If, LimitExcept};
+use display::arguments;
+use position::Pos;
 use value::Value;
 
 
 /// A deep iterator over all directives in configuration file or a part of it
 #[derive(Debug)]
 pub struct DirectiveIter<'a> {
-    cur: Option<&'a Directive>,
-    queue: VecDeque<&'a Directive>,
+    cur: Option<&'a Directive<'a>>,
+    queue: VecDeque<&'a Directive<'a>>,
 }
 
 impl<'a> DirectiveIter<'a> {
@@ -19,7 +21,7 @@ impl<'a> DirectiveIter<'a> {
     /// [`config.all_directives()`] instead.
     ///
     /// [`config.all_directives()`]: ast/fn.all_directives.html
-    pub fn depth_first(start: &[Directive]) -> DirectiveIter {
+    pub fn depth_first(start: &'a [Directive<'a>]) -> DirectiveIter<'a> {
         DirectiveIter {
             cur: None,
             queue: start.iter().rev().collect()
@@ -28,8 +30,8 @@ impl<'a> DirectiveIter<'a> {
 }
 
 impl<'a> Iterator for DirectiveIter<'a> {
-    type Item = &'a Directive;
-    fn next(&mut self) -> Option<&'a Directive> {
+    type Item = &'a Directive<'a>;
+    fn next(&mut self) -> Option<&'a Directive<'a>> {
         match self.cur.take() {
             Some(dir) => {
                 if let Some(ch) = dir.item.children() {
@@ -49,14 +51,14 @@ impl<'a> Iterator for DirectiveIter<'a> {
 }
 
 /// A recursive mutable depth-first visitor of directives
-pub fn visit_mutable<F>(dirs: &mut Vec<Directive>, mut f: F)
-    where F: FnMut(&mut Directive)
+pub fn visit_mutable<'a, F>(dirs: &mut Vec<Directive<'a>>, mut f: F)
+    where F: FnMut(&mut Directive<'a>)
 {
     _visit_mutable(dirs, &mut f)
 }
 
-fn _visit_mutable<F>(dirs: &mut Vec<Directive>, f: &mut F)
-    where F: FnMut(&mut Directive)
+fn _visit_mutable<'a, F>(dirs: &mut Vec<Directive<'a>>, f: &mut F)
+    where F: FnMut(&mut Directive<'a>)
 {
     for dir in dirs {
         f(dir);
@@ -84,3 +86,324 @@ pub fn replace_vars<'a, F, S>(dirs: &mut Vec<Directive>, mut f: F)
         dir.visit_values_mut(inner_visitor);
     });
 }
+
+/// What to do after a [`Visitor`] or [`VisitorMut`] hook returns
+///
+/// [`Visitor`]: trait.Visitor.html
+/// [`VisitorMut`]: trait.VisitorMut.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Control {
+    /// Go on with the traversal
+    Continue,
+    /// Don't descend into the children of the current block
+    SkipChildren,
+    /// Stop the traversal immediately
+    Stop,
+}
+
+/// Enclosing block as seen by [`VisitorMut`]
+///
+/// Mutable visitor can't borrow the blocks it is inside of, so it gets
+/// their description instead.
+///
+/// [`VisitorMut`]: trait.VisitorMut.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ancestor {
+    /// Position of the block directive
+    pub position: Pos,
+    /// Directive name, e.g. `location`
+    pub name: &'static str,
+    /// Arguments of the block as written in config, e.g. `^~ /static`
+    pub args: String,
+}
+
+impl Ancestor {
+    fn new(dir: &Directive) -> Ancestor {
+        Ancestor {
+            position: dir.position,
+            name: dir.item.directive_name(),
+            args: arguments(dir),
+        }
+    }
+}
+
+/// A read-only visitor of the directive tree
+///
+/// All hooks do nothing by default, so implement only the ones you need
+/// and run the visitor with [`walk`]. For each directive the hooks are
+/// called in this order: `visit_directive`, the hook of the specific
+/// block (like `visit_server`), `visit_value` for each of its values,
+/// then for blocks `enter_block`, the children and `exit_block`.
+///
+/// `ancestors` are the enclosing blocks, outermost first. Returning
+/// `SkipChildren` from any hook prevents descending into the current
+/// block, `exit_block` is still called for each `enter_block`.
+///
+/// [`walk`]: fn.walk.html
+#[allow(unused_variables)]
+pub trait Visitor<'ast> {
+    /// Called for every directive
+    fn visit_directive(&mut self, dir: &'ast Directive<'ast>,
+        ancestors: &[&'ast Directive<'ast>]) -> Control
+    {
+        Control::Continue
+    }
+    /// Called for every `http` block
+    fn visit_http(&mut self, http: &'ast Http<'ast>,
+        ancestors: &[&'ast Directive<'ast>]) -> Control
+    {
+        Control::Continue
+    }
+    /// Called for every `server` block
+    fn visit_server(&mut self, server: &'ast Server<'ast>,
+        ancestors: &[&'ast Directive<'ast>]) -> Control
+    {
+        Control::Continue
+    }
+    /// Called for every `location` block
+    fn visit_location(&mut self, location: &'ast Location<'ast>,
+        ancestors: &[&'ast Directive<'ast>]) -> Control
+    {
+        Control::Continue
+    }
+    /// Called for every `if` block
+    fn visit_if(&mut self, cond: &'ast If<'ast>,
+        ancestors: &[&'ast Directive<'ast>]) -> Control
+    {
+        Control::Continue
+    }
+    /// Called for every `limit_except` block
+    fn visit_limit_except(&mut self, limit: &'ast LimitExcept<'ast>,
+        ancestors: &[&'ast Directive<'ast>]) -> Control
+    {
+        Control::Continue
+    }
+    /// Called for every value of a directive (not recursively)
+    fn visit_value(&mut self, value: &'ast Value<'ast>,
+        ancestors: &[&'ast Directive<'ast>]) -> Control
+    {
+        Control::Continue
+    }
+    /// Called before visiting children of a block
+    fn enter_block(&mut self, dir: &'ast Directive<'ast>,
+        ancestors: &[&'ast Directive<'ast>]) -> Control
+    {
+        Control::Continue
+    }
+    /// Called after visiting children of a block
+    fn exit_block(&mut self, dir: &'ast Directive<'ast>,
+        ancestors: &[&'ast Directive<'ast>]) -> Control
+    {
+        Control::Continue
+    }
+}
+
+/// A mutable visitor of the directive tree
+///
+/// Hooks are called in the same order as for [`Visitor`]. Changes made
+/// to the block before `enter_block` returns are seen when visiting
+/// its children.
+///
+/// [`Visitor`]: trait.Visitor.html
+#[allow(unused_variables)]
+pub trait VisitorMut {
+    /// Called for every directive
+    fn visit_directive(&mut self, dir: &mut Directive,
+        ancestors: &[Ancestor]) -> Control
+    {
+        Control::Continue
+    }
+    /// Called for every `http` block
+    fn visit_http(&mut self, http: &mut Http, ancestors: &[Ancestor])
+        -> Control
+    {
+        Control::Continue
+    }
+    /// Called for every `server` block
+    fn visit_server(&mut self, server: &mut Server, ancestors: &[Ancestor])
+        -> Control
+    {
+        Control::Continue
+    }
+    /// Called for every `location` block
+    fn visit_location(&mut self, location: &mut Location,
+        ancestors: &[Ancestor]) -> Control
+    {
+        Control::Continue
+    }
+    /// Called for every `if` block
+    fn visit_if(&mut self, cond: &mut If, ancestors: &[Ancestor])
+        -> Control
+    {
+        Control::Continue
+    }
+    /// Called for every `limit_except` block
+    fn visit_limit_except(&mut self, limit: &mut LimitExcept,
+        ancestors: &[Ancestor]) -> Control
+    {
+        Control::Continue
+    }
+    /// Called for every value of a directive (not recursively)
+    fn visit_value(&mut self, value: &mut Value, ancestors: &[Ancestor])
+        -> Control
+    {
+        Control::Continue
+    }
+    /// Called before visiting children of a block
+    fn enter_block(&mut self, dir: &mut Directive, ancestors: &[Ancestor])
+        -> Control
+    {
+        Control::Continue
+    }
+    /// Called after visiting children of a block
+    fn exit_block(&mut self, dir: &mut Directive, ancestors: &[Ancestor])
+        -> Control
+    {
+        Control::Continue
+    }
+}
+
+/// Run a read-only visitor over directives
+///
+/// Returns `Control::Stop` if the visitor has stopped the traversal.
+pub fn walk<'ast, V>(visitor: &mut V, dirs: &'ast [Directive<'ast>]) -> Control
+    where V: Visitor<'ast> + ?Sized
+{
+    walk_list(visitor, dirs, &mut Vec::new())
+}
+
+fn walk_list<'ast, V>(visitor: &mut V, dirs: &'ast [Directive<'ast>],
+    ancestors: &mut Vec<&'ast Directive<'ast>>)
+    -> Control
+    where V: Visitor<'ast> + ?Sized
+{
+    for dir in dirs {
+        if walk_directive(visitor, dir, ancestors) == Control::Stop {
+            return Control::Stop;
+        }
+    }
+    Control::Continue
+}
+
+fn walk_directive<'ast, V>(visitor: &mut V, dir: &'ast Directive<'ast>,
+    ancestors: &mut Vec<&'ast Directive<'ast>>)
+    -> Control
+    where V: Visitor<'ast> + ?Sized
+{
+    use ast::Item::*;
+
+    let mut ctl = visitor.visit_directive(dir, ancestors);
+    if ctl == Control::Stop {
+        return ctl;
+    }
+    ctl = ctl.max(match dir.item {
+        Http(ref http) => visitor.visit_http(http, ancestors),
+        Server(ref server) => visitor.visit_server(server, ancestors),
+        Location(ref loc) => visitor.visit_location(loc, ancestors),
+        If(ref cond) => visitor.visit_if(cond, ancestors),
+        LimitExcept(ref limit) => visitor.visit_limit_except(limit, ancestors),
+        _ => Control::Continue,
+    });
+    dir.visit_values(|val| {
+        if ctl != Control::Stop {
+            ctl = ctl.max(visitor.visit_value(val, ancestors));
+        }
+    });
+    if ctl == Control::Stop {
+        return ctl;
+    }
+    let children = match dir.item.children() {
+        Some(children) => children,
+        None => return Control::Continue,
+    };
+    ctl = ctl.max(visitor.enter_block(dir, ancestors));
+    if ctl == Control::Stop {
+        return ctl;
+    }
+    if ctl == Control::Continue {
+        ancestors.push(dir);
+        ctl = walk_list(visitor, children, ancestors);
+        ancestors.pop();
+        if ctl == Control::Stop {
+            return ctl;
+        }
+    }
+    match visitor.exit_block(dir, ancestors) {
+        Control::Stop => Control::Stop,
+        _ => Control::Continue,
+    }
+}
+
+/// Run a mutable visitor over directives
+///
+/// Returns `Control::Stop` if the visitor has stopped the traversal.
+pub fn walk_mut<V>(visitor: &mut V, dirs: &mut Vec<Directive>) -> Control
+    where V: VisitorMut + ?Sized
+{
+    walk_list_mut(visitor, dirs, &mut Vec::new())
+}
+
+fn walk_list_mut<V>(visitor: &mut V, dirs: &mut Vec<Directive>,
+    ancestors: &mut Vec<Ancestor>)
+    -> Control
+    where V: VisitorMut + ?Sized
+{
+    for dir in dirs {
+        if walk_directive_mut(visitor, dir, ancestors) == Control::Stop {
+            return Control::Stop;
+        }
+    }
+    Control::Continue
+}
+
+fn walk_directive_mut<V>(visitor: &mut V, dir: &mut Directive,
+    ancestors: &mut Vec<Ancestor>)
+    -> Control
+    where V: VisitorMut + ?Sized
+{
+    use ast::Item::*;
+
+    let mut ctl = visitor.visit_directive(dir, ancestors);
+    if ctl == Control::Stop {
+        return ctl;
+    }
+    ctl = ctl.max(match dir.item {
+        Http(ref mut http) => visitor.visit_http(http, ancestors),
+        Server(ref mut server) => visitor.visit_server(server, ancestors),
+        Location(ref mut loc) => visitor.visit_location(loc, ancestors),
+        If(ref mut cond) => visitor.visit_if(cond, ancestors),
+        LimitExcept(ref mut limit) => {
+            visitor.visit_limit_except(limit, ancestors)
+        }
+        _ => Control::Continue,
+    });
+    dir.visit_values_mut(|val| {
+        if ctl != Control::Stop {
+            ctl = ctl.max(visitor.visit_value(val, ancestors));
+        }
+    });
+    if ctl == Control::Stop {
+        return ctl;
+    }
+    if dir.item.children().is_none() {
+        return Control::Continue;
+    }
+    ctl = ctl.max(visitor.enter_block(dir, ancestors));
+    if ctl == Control::Stop {
+        return ctl;
+    }
+    if ctl == Control::Continue {
+        ancestors.push(Ancestor::new(dir));
+        if let Some(children) = dir.item.children_mut() {
+            ctl = walk_list_mut(visitor, children, ancestors);
+        }
+        ancestors.pop();
+        if ctl == Control::Stop {
+            return ctl;
+        }
+    }
+    match visitor.exit_block(dir, ancestors) {
+        Control::Stop => Control::Stop,
+        _ => Control::Continue,
+    }
+}
@@ -0,0 +1,71 @@
+extern crate clia_nginx_config;
+#[cfg(test)] #[macro_use] extern crate pretty_assertions;
+
+use std::borrow::Cow;
+
+use clia_nginx_config::{parse_main, parse_directives};
+use clia_nginx_config::ast::{Directive, Item, LocationPattern, Main};
+
+
+fn borrows(text: &str, s: &str) -> bool {
+    let start = text.as_ptr() as usize;
+    let ptr = s.as_ptr() as usize;
+    ptr >= start && ptr + s.len() <= start + text.len()
+}
+
+#[test]
+fn strings_are_borrowed() {
+    let text = String::from("location /static { rewrite ^/(.*)$ /$1; }");
+    let dirs = parse_directives(&text).unwrap();
+    let loc = match dirs[0].item {
+        Item::Location(ref loc) => loc,
+        ref item => panic!("unexpected {:?}", item),
+    };
+    match loc.pattern {
+        LocationPattern::Prefix(Cow::Borrowed(p)) => {
+            assert_eq!(p, "/static");
+            assert!(borrows(&text, p));
+        }
+        ref pat => panic!("unexpected {:?}", pat),
+    }
+    match loc.directives[0].item {
+        Item::Rewrite(ref rw) => {
+            assert!(matches!(rw.regex, Cow::Borrowed(..)));
+            assert!(borrows(&text, &rw.regex));
+        }
+        ref item => panic!("unexpected {:?}", item),
+    }
+}
+
+#[test]
+fn escapes_are_owned() {
+    let dirs = parse_directives(r#"rewrite "^/a\"b$" /c;"#).unwrap();
+    match dirs[0].item {
+        Item::Rewrite(ref rw) => {
+            assert!(matches!(rw.regex, Cow::Owned(..)));
+            assert_eq!(rw.regex, "^/a\"b$");
+        }
+        ref item => panic!("unexpected {:?}", item),
+    }
+}
+
+#[test]
+fn into_owned() {
+    let text = String::from("server { location = /x { root /srv; } }");
+    let config = parse_main(&text).unwrap();
+    let printed = config.to_string();
+    let owned: Main<'static> = config.clone().into_owned();
+    assert_eq!(owned, config);
+    drop(config);
+    drop(text);
+    assert_eq!(owned.to_string(), printed);
+}
+
+#[test]
+fn directive_into_owned() {
+    let dir: Directive<'static> = {
+        let text = String::from("root /srv/www;");
+        parse_directives(&text).unwrap().remove(0).into_owned()
+    };
+    assert_eq!(dir.to_string(), "root /srv/www;\n");
+}
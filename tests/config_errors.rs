@@ -0,0 +1,115 @@
+extern crate clia_nginx_config;
+extern crate tempfile;
+#[cfg(test)] #[macro_use] extern crate pretty_assertions;
+
+use std::error::Error;
+use std::fs;
+use std::io;
+
+use tempfile::tempdir;
+
+use clia_nginx_config::{parse_main, parse_main_from_file, ConfigError};
+use clia_nginx_config::parse_directives_from_file;
+
+
+#[test]
+fn io() {
+    let dir = tempdir().unwrap();
+    let main = dir.path().join("missing.conf");
+    let err = parse_main_from_file(&main).unwrap_err();
+    match err {
+        ConfigError::Io { ref path, ref include_chain, ref error } => {
+            assert_eq!(path, &main);
+            assert!(include_chain.is_empty());
+            assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        }
+        ref e => panic!("unexpected {:?}", e),
+    }
+    assert!(err.source().is_some());
+    assert_eq!(err.path(), Some(main.as_path()));
+}
+
+#[test]
+fn parse_error_in_include() {
+    let dir = tempdir().unwrap();
+    let main = dir.path().join("main.conf");
+    let inner = dir.path().join("inner.conf");
+    let broken = dir.path().join("broken.conf");
+    fs::write(&main, "http { include inner.conf; }\n").unwrap();
+    fs::write(&inner, "include broken.conf;\n").unwrap();
+    fs::write(&broken, "\nserver { listen; }\n").unwrap();
+    let err = parse_directives_from_file(&main).unwrap_err();
+    match err {
+        ConfigError::Parse { ref path, ref include_chain, ref error } => {
+            assert_eq!(path.as_ref(), Some(&broken));
+            assert_eq!(include_chain, &vec![main.clone(), inner.clone()]);
+            assert!(error.to_string()
+                    .starts_with("parse error: Parse error at 2:16"));
+        }
+        ref e => panic!("unexpected {:?}", e),
+    }
+    let source = err.source().unwrap().to_string();
+    assert!(source.starts_with("parse error: Parse error at 2:16"));
+    let text = err.to_string();
+    assert!(text.starts_with(&format!("{}: parse error: Parse error at 2:16",
+                                      broken.display())));
+    assert!(text.ends_with(&format!("(included from {} -> {})",
+                                    main.display(), inner.display())));
+}
+
+#[test]
+fn include_cycle() {
+    let dir = tempdir().unwrap();
+    let main = dir.path().join("main.conf");
+    let a = dir.path().join("a.conf");
+    let b = dir.path().join("b.conf");
+    fs::write(&main, "include a.conf;\n").unwrap();
+    fs::write(&a, "include b.conf;\n").unwrap();
+    fs::write(&b, "include a.conf;\n").unwrap();
+    match parse_main_from_file(&main).unwrap_err() {
+        ConfigError::IncludeCycle { path, include_chain } => {
+            assert_eq!(path, a);
+            assert_eq!(include_chain, vec![main, a, b]);
+        }
+        e => panic!("unexpected {:?}", e),
+    }
+}
+
+#[test]
+fn self_include_skipped() {
+    let dir = tempdir().unwrap();
+    let main = dir.path().join("main.conf");
+    fs::write(&main, "daemon off;\ninclude *.conf;\n").unwrap();
+    // nothing else matches, so the include is kept
+    assert_eq!(parse_main_from_file(&main).unwrap().to_string(),
+               "daemon off;\ninclude *.conf;\n");
+}
+
+#[test]
+fn pattern() {
+    let dir = tempdir().unwrap();
+    let main = dir.path().join("main.conf");
+    fs::write(&main, "daemon off;\ninclude conf.d/[a.conf;\n").unwrap();
+    match parse_main_from_file(&main).unwrap_err() {
+        ConfigError::Pattern { path, include_chain, position, pattern, .. }
+        => {
+            assert_eq!(path, Some(main));
+            assert!(include_chain.is_empty());
+            assert_eq!((position.line, position.column), (2, 1));
+            assert!(pattern.ends_with("conf.d/[a.conf"));
+        }
+        e => panic!("unexpected {:?}", e),
+    }
+}
+
+#[test]
+fn parse_main_from_string() {
+    let err = parse_main("daemon;").unwrap_err();
+    match err {
+        ConfigError::Parse { path: None, ref include_chain, .. } => {
+            assert!(include_chain.is_empty());
+        }
+        ref e => panic!("unexpected {:?}", e),
+    }
+    assert!(err.to_string().starts_with("parse error: Parse error at 1:7"));
+}
@@ -0,0 +1,119 @@
+extern crate clia_nginx_config;
+#[cfg(test)] #[macro_use] extern crate pretty_assertions;
+
+use clia_nginx_config::{parse_directives, parse_in_context, ConfigError};
+use clia_nginx_config::ast::Value;
+use clia_nginx_config::extension::{Extensions, ExtensionDirective};
+use clia_nginx_config::registry::Context;
+use clia_nginx_config::validate::check_context;
+
+
+#[derive(Debug, Clone, PartialEq)]
+struct Sandbox;
+
+impl ExtensionDirective for Sandbox {
+    fn name(&self) -> &'static str {
+        "sandbox"
+    }
+    fn arguments(&self) -> Vec<Value<'static>> {
+        Vec::new()
+    }
+}
+
+fn errors(context: Context, text: &str) -> Vec<String> {
+    let dirs = parse_directives(text).unwrap();
+    check_context(context, &dirs).into_iter()
+        .map(|e| e.to_string())
+        .collect()
+}
+
+#[test]
+fn location_snippet() {
+    let dirs = parse_in_context(Context::Location, "\
+proxy_pass http://backend;
+proxy_set_header Host $host;
+if ($request_method = POST) {
+    return 405;
+}
+").unwrap();
+    assert_eq!(dirs.len(), 3);
+    assert_eq!((dirs[2].position.line, dirs[2].position.column), (3, 1));
+}
+
+#[test]
+fn snippet_positions() {
+    let err = parse_in_context(Context::Location, "\
+root /srv;
+server_name example.com;
+location /a {
+    listen 80;
+}
+").unwrap_err();
+    match err {
+        ConfigError::Invalid { ref errors } => {
+            assert_eq!(errors.iter()
+                .map(|e| (e.position.line, e.position.column))
+                .collect::<Vec<_>>(), vec![(2, 1), (4, 5)]);
+        }
+        ref e => panic!("unexpected {:?}", e),
+    }
+    assert_eq!(err.to_string(), "2:1: server_name is not allowed in location; \
+        4:5: listen is not allowed in location");
+    assert_eq!(errors(Context::Location, "\
+root /srv;
+location /a {
+    listen 80;
+}
+"), vec!["3:5: listen is not allowed in location"]);
+}
+
+#[test]
+fn nested_blocks() {
+    assert_eq!(errors(Context::Server, "\
+if ($host = example.com) {
+    return 301 https://example.com;
+    proxy_pass http://backend;
+}
+location / {
+    if ($uri = /) {
+        proxy_pass http://backend;
+    }
+    limit_except GET {
+        deny all;
+        return 403;
+    }
+}
+"), vec![
+        "3:5: proxy_pass is not allowed in if in server",
+        "11:9: return is not allowed in limit_except",
+    ]);
+}
+
+#[test]
+fn main_context() {
+    assert_eq!(errors(Context::Main, "\
+worker_processes 2;
+http {
+    server {
+        listen 80;
+    }
+}
+"), Vec::<String>::new());
+    assert_eq!(errors(Context::Main, "listen 80;"),
+               vec!["1:1: listen is not allowed in main"]);
+    assert_eq!(errors(Context::Http, "http { }"),
+               vec!["1:1: http is not allowed in http"]);
+}
+
+#[test]
+fn unknown_and_parse_errors() {
+    // registered directives aren't in the registry, so aren't checked
+    let mut ext = Extensions::new();
+    ext.register("sandbox", true, |_| Ok(Box::new(Sandbox)));
+    assert!(ext.parse_in_context(Context::Location,
+        "sandbox { listen 80; }").is_ok());
+    match parse_in_context(Context::Location, "root /srv") {
+        Err(ConfigError::Parse { path: None, .. }) => {}
+        res => panic!("unexpected {:?}", res),
+    }
+}
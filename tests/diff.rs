@@ -0,0 +1,127 @@
+extern crate clia_nginx_config;
+#[cfg(test)] #[macro_use] extern crate pretty_assertions;
+
+use clia_nginx_config::parse_main;
+use clia_nginx_config::diff::{diff, ChangeKind};
+
+fn changes(old: &str, new: &str) -> Vec<String> {
+    let old = parse_main(old).unwrap();
+    let new = parse_main(new).unwrap();
+    diff(&old, &new).iter().map(|c| c.to_string()).collect()
+}
+
+const BASE: &str = r#"
+http {
+    gzip on;
+    server {
+        listen 443 ssl;
+        server_name api.example.com;
+        location /v1 {
+            proxy_pass http://backend-v1;
+            proxy_set_header Host $host;
+            proxy_set_header X-Real-IP $remote_addr;
+        }
+    }
+    server {
+        listen 80;
+        server_name example.com;
+        return 301 https://example.com$request_uri;
+    }
+}
+"#;
+
+#[test]
+fn formatting_and_comments() {
+    assert_eq!(changes(BASE, r#"
+        # production config
+        http {
+            gzip   on;
+            server {
+                listen 80; server_name example.com;
+                return 301 https://example.com$request_uri;
+            }
+            server {
+                server_name api.example.com;  # the API
+                listen 443 ssl;
+                location /v1 {
+                    proxy_set_header X-Real-IP $remote_addr;
+                    proxy_set_header Host $host;
+                    proxy_pass http://backend-v1;
+                }
+            }
+        }
+    "#), Vec::<String>::new());
+}
+
+#[test]
+fn changed_directive() {
+    let new = BASE.replace("http://backend-v1", "http://backend-v2");
+    assert_eq!(changes(BASE, &new), vec![
+        "~ http > server[api.example.com:443] > location[/v1] > proxy_pass: \
+         proxy_pass http://backend-v1; => proxy_pass http://backend-v2;",
+    ]);
+}
+
+#[test]
+fn added_and_removed() {
+    let new = BASE
+        .replace("    gzip on;\n", "")
+        .replace("            proxy_set_header Host $host;\n", "")
+        .replace("    location /v1 {",
+                 "    location = /health {\n        return 200;\n    }\n\
+                  \x20   location /v1 {\n            proxy_read_timeout 5s;");
+    let old = parse_main(BASE).unwrap();
+    let new = parse_main(&new).unwrap();
+    let changes = diff(&old, &new);
+    assert_eq!(changes.iter().map(|c| c.to_string()).collect::<Vec<_>>(), vec![
+        "- http > gzip: gzip on;",
+        "- http > server[api.example.com:443] > location[/v1] \
+         > proxy_set_header: proxy_set_header Host $host;",
+        "+ http > server[api.example.com:443] > location[/v1] \
+         > proxy_read_timeout: proxy_read_timeout 5s;",
+        "+ http > server[api.example.com:443] > location[= /health]",
+    ]);
+    assert_eq!(changes[3].kind, ChangeKind::Added);
+    assert_eq!(changes[3].path, vec![
+        "http", "server[api.example.com:443]", "location[= /health]"]);
+    assert!(changes[3].old.is_none());
+}
+
+#[test]
+fn server_identity() {
+    let new = BASE.replace("listen 80;", "listen 8080;");
+    assert_eq!(changes(BASE, &new), vec![
+        "- http > server[example.com:80]",
+        "+ http > server[example.com:8080]",
+    ]);
+}
+
+#[test]
+fn access_rules_order() {
+    let old = "location / { allow 10.0.0.0/8; deny all; }";
+    assert_eq!(changes(old, "location / { deny all; allow 10.0.0.0/8; }"),
+        vec![
+            "- location[/] > allow: allow 10.0.0.0/8;",
+            "+ location[/] > deny: deny all;",
+            "- location[/] > deny: deny all;",
+            "+ location[/] > allow: allow 10.0.0.0/8;",
+        ]);
+    assert_eq!(changes(old, "location / { allow 10.0.0.0/16; deny all; }"),
+        vec![
+            "~ location[/] > allow: \
+             allow 10.0.0.0/8; => allow 10.0.0.0/16;",
+        ]);
+}
+
+#[test]
+fn regex_locations_order() {
+    let old = "location ~ ^/a { return 200; } location ~ ^/ { return 404; }";
+    assert_eq!(changes(old, old), Vec::<String>::new());
+    let new = "location ~ ^/ { return 404; } location ~ ^/a { return 200; }";
+    assert_eq!(changes(old, new), vec![
+        "- location[~ ^/a]",
+        "+ location[~ ^/]",
+        "- location[~ ^/]",
+        "+ location[~ ^/a]",
+    ]);
+}
@@ -0,0 +1,142 @@
+extern crate clia_nginx_config;
+#[cfg(test)] #[macro_use] extern crate pretty_assertions;
+
+use clia_nginx_config::{parse_main, parse_directives, Span};
+use clia_nginx_config::ast::{Directive, Item, LocationPattern};
+use clia_nginx_config::edit::Block;
+use clia_nginx_config::visitors::visit_mutable;
+
+const VHOST: &str = "\
+server {
+    listen 80;
+    server_name example.com;
+    root /srv/www;
+    add_header X-Frame-Options DENY;
+    proxy_set_header Host $host;
+    proxy_pass http://backend;
+
+    location /static {
+        root /srv/static;
+    }
+}
+";
+
+fn directive(text: &str) -> Directive<'_> {
+    parse_directives(text).unwrap().pop().unwrap()
+}
+
+fn is(name: &'static str) -> impl Fn(&Directive) -> bool {
+    move |d| d.item.directive_name() == name
+}
+
+fn is_server(dir: &Directive) -> bool {
+    matches!(dir.item, Item::Server(..))
+}
+
+/// Applies `f` to every server in the config and returns the result
+fn edit_servers<F>(text: &'static str, mut f: F) -> String
+    where F: FnMut(&mut Vec<Directive<'static>>)
+{
+    let mut ast = parse_main(text).unwrap();
+    visit_mutable(&mut ast.directives, |dir| {
+        if is_server(dir) {
+            f(dir.item.children_mut().unwrap());
+        }
+    });
+    ast.to_string()
+}
+
+#[test]
+fn remove_where() {
+    let mut removed = Vec::new();
+    let text = edit_servers(VHOST, |dirs| {
+        removed = dirs.remove_where(is("add_header"));
+    });
+    assert_eq!(removed.len(), 1);
+    assert!(!text.contains("add_header"));
+}
+
+#[test]
+fn insert_after() {
+    assert_eq!(edit_servers(VHOST, |dirs| {
+        assert!(dirs.insert_after(is("listen"),
+                                  directive("listen 443 ssl;")));
+        assert!(!dirs.insert_after(is("ssl_certificate"),
+                                   directive("ssl_stapling on;")));
+    }), VHOST.replace("listen 80;\n", "listen 80;\n    listen 443 ssl;\n"));
+}
+
+#[test]
+fn upsert() {
+    let mut ast = parse_main(VHOST).unwrap();
+    let server = ast.directives[0].item.children_mut().unwrap();
+    let old = server.upsert(directive("root /var/www;")).unwrap();
+    assert_eq!(old.to_string(), "root /srv/www;\n");
+    assert!(server.upsert(directive("client_max_body_size 10m;"))
+        .is_none());
+    // position of untouched directives is preserved
+    assert_eq!(server[0].position.line, 2);
+    assert_eq!(ast.to_string(), VHOST
+        .replace("root /srv/www;", "root /var/www;")
+        .replace("    }\n}", "    }\n    client_max_body_size 10m;\n}"));
+}
+
+#[test]
+fn upsert_removes_duplicates() {
+    let mut dirs = parse_directives("index a.html; gzip on; index b.html;")
+        .unwrap();
+    dirs.upsert(directive("index c.html;"));
+    assert_eq!(dirs.iter().map(|d| d.to_string()).collect::<Vec<_>>(),
+               vec!["index c.html;\n", "gzip on;\n"]);
+}
+
+#[test]
+fn wrap_in_location() {
+    assert_eq!(edit_servers(VHOST, |dirs| {
+        assert!(dirs.wrap_in_location(
+            LocationPattern::Prefix("/".into()),
+            |d| is("proxy_set_header")(d) || is("proxy_pass")(d)));
+        assert!(!dirs.wrap_in_location(
+            LocationPattern::Prefix("/".into()), is("fastcgi_pass")));
+    }), VHOST.replace(
+        "    proxy_set_header Host $host;\n    proxy_pass http://backend;\n",
+        "\n    location / {\n        proxy_set_header Host $host;\n        \
+         proxy_pass http://backend;\n    }\n"));
+}
+
+#[test]
+fn wrap_in_location_range() {
+    let mut dirs = parse_directives("\
+        root /srv;
+        proxy_pass http://backend;
+        index index.html;
+        add_header X-A a;
+    ").unwrap();
+    let start = dirs[1].position;
+    let end = dirs[3].span.end;
+    assert!(dirs.wrap_in_location(LocationPattern::Prefix("/".into()),
+        |d| is("proxy_pass")(d) || is("add_header")(d)));
+    let names = dirs.iter()
+        .map(|d| d.item.directive_name())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["root", "location", "index"]);
+    assert_eq!(dirs[1].span, Span { start, end });
+    match dirs[1].item {
+        Item::Location(ref loc) => {
+            assert_eq!(loc.position, (start, end));
+            assert_eq!(loc.directives.len(), 2);
+        }
+        ref item => panic!("unexpected {:?}", item),
+    }
+}
+
+#[test]
+fn move_to() {
+    assert_eq!(edit_servers(VHOST, |dirs| {
+        assert_eq!(dirs.move_to(is("add_header"), is("location")), 1);
+        assert_eq!(dirs.move_to(is("add_header"), is("if")), 0);
+    }), VHOST
+        .replace("    add_header X-Frame-Options DENY;\n", "")
+        .replace("root /srv/static;\n",
+                 "root /srv/static;\n        add_header X-Frame-Options DENY;\n"));
+}
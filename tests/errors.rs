@@ -24,6 +24,9 @@ fn test_error(filename: &str) {
 }
 
 #[test] fn invalid_directive() { test_error("invalid_directive"); }
+#[test] fn proxy_cache_path_no_zone() {
+    test_error("proxy_cache_path_no_zone");
+}
 #[test] fn invalid_directive_in_block() {
     test_error("invalid_directive_in_block");
 }
@@ -0,0 +1,121 @@
+extern crate clia_nginx_config;
+#[cfg(test)] #[macro_use] extern crate pretty_assertions;
+
+use clia_nginx_config::parse_main;
+use clia_nginx_config::ast::{Item, ServerName, Value};
+
+
+fn roundtrip(text: &str) -> String {
+    let once = parse_main(text).unwrap().to_string();
+    let twice = parse_main(&once).unwrap().to_string();
+    assert_eq!(once, twice);
+    once
+}
+
+fn first_value(text: &str) -> Value<'static> {
+    let main = parse_main(text).unwrap();
+    let mut result = None;
+    main.directives[0].visit_values(|v| {
+        if result.is_none() {
+            result = Some(v.clone().into_owned());
+        }
+    });
+    result.unwrap()
+}
+
+#[test]
+fn escaped_quotes() {
+    assert_eq!(roundtrip(r#"add_header X "say \"hi\"";"#),
+        "add_header X \"say \\\"hi\\\"\";\n");
+    assert_eq!(roundtrip(r#"add_header X 'it\'s';"#),
+        "add_header X 'it\\'s';\n");
+    assert_eq!(roundtrip(r#"add_header X "it's";"#),
+        "add_header X \"it's\";\n");
+}
+
+#[test]
+fn backslashes() {
+    assert_eq!(roundtrip(r#"add_header X "a\\";"#),
+        "add_header X \"a\\\\\";\n");
+    assert_eq!(roundtrip(r#"add_header X "a\\\"b";"#),
+        "add_header X \"a\\\\\\\"b\";\n");
+    // backslash which doesn't escape anything is kept verbatim
+    assert_eq!(roundtrip(r#"rewrite ^/(.*)\.php$ /$1;"#),
+        "rewrite ^/(.*)\\.php$ /$1;\n");
+}
+
+#[test]
+fn control_chars() {
+    assert_eq!(roundtrip(r#"add_header X "a\tb\nc";"#),
+        "add_header X \"a\\tb\\nc\";\n");
+}
+
+#[test]
+fn variables() {
+    assert_eq!(roundtrip(r#"add_header X "${host}name $uri";"#),
+        "add_header X \"${host}name $uri\";\n");
+    // nginx has no escape for `$`, backslash is kept before the variable
+    let value = first_value(r#"root "price: \$5 for $uri";"#);
+    assert_eq!(value.variables().collect::<Vec<_>>(), vec!["5", "uri"]);
+    assert_eq!(roundtrip(r#"add_header X "price: \$5 for $uri";"#),
+        "add_header X \"price: \\$5 for $uri\";\n");
+}
+
+#[test]
+fn replaced_vars() {
+    let mut value = first_value("root $dir;");
+    value.replace_vars(|_| Some("/srv/my \"dir\"; ${x}y"));
+    let text = format!("root {};", value);
+    let main = parse_main(&text).unwrap();
+    assert_eq!(main.to_string(), "root \"/srv/my \\\"dir\\\"; ${x}y\";\n");
+    let reparsed = first_value(&main.to_string());
+    assert_eq!(reparsed.variables().collect::<Vec<_>>(), vec!["x"]);
+    assert_eq!(reparsed.to_string(), value.to_string());
+}
+
+#[test]
+fn unrepresentable_dollar() {
+    let mut value = first_value("root /srv/$dir;");
+    value.replace_vars(|_| Some("price: $ 5"));
+    assert_eq!(value.to_string(), "/srv/$dir");
+}
+
+#[test]
+fn location_regex() {
+    assert_eq!(roundtrip(r#"location ~ "^/a{2}\.html$" { }"#),
+        "location ~ \"^/a{2}\\.html$\" {\n}\n");
+}
+
+#[test]
+fn raw_strings_unquoted() {
+    let main = parse_main(r#"
+        server_name "~^(?<name>\w+)\.example\.com$" 'www.example.com';
+        rewrite "^/a b$" /c;
+    "#).unwrap();
+    match main.directives[0].item {
+        Item::ServerName(ref names) => assert_eq!(names, &vec![
+            ServerName::Regex(r"^(?<name>\w+)\.example\.com$".into()),
+            ServerName::Exact("www.example.com".into()),
+        ]),
+        ref item => panic!("unexpected {:?}", item),
+    }
+    match main.directives[1].item {
+        Item::Rewrite(ref rw) => assert_eq!(rw.regex, "^/a b$"),
+        ref item => panic!("unexpected {:?}", item),
+    }
+    assert_eq!(roundtrip(&main.to_string()),
+        "server_name ~^(?<name>\\w+)\\.example\\.com$ www.example.com;\n\
+         rewrite \"^/a b$\" /c;\n");
+}
+
+#[test]
+fn quoted_regexes() {
+    assert_eq!(roundtrip("location ~ '^/a b' { }"),
+        "location ~ '^/a b' {\n}\n");
+    assert_eq!(roundtrip("location ~* \"\\.(gif|jpg)$\" { }"),
+        "location ~* \"\\.(gif|jpg)$\" {\n}\n");
+    assert_eq!(roundtrip("if ($x ~ '^a') { } if ($x !~* \"b\") { }"),
+        "if ($x ~ '^a') {\n}\nif ($x !~* \"b\") {\n}\n");
+    assert_eq!(roundtrip("map $uri $y { '~^x' 1; ~^y 2; }"),
+        "map $uri $y {\n    '~^x' 1;\n    ~^y 2;\n}\n");
+}
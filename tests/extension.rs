@@ -0,0 +1,202 @@
+extern crate clia_nginx_config;
+#[cfg(test)] #[macro_use] extern crate pretty_assertions;
+
+use clia_nginx_config::ast::{Item, Value};
+use clia_nginx_config::extension::{Extensions, ExtensionDirective};
+use clia_nginx_config::validate::validate;
+use clia_nginx_config::visitors::replace_vars;
+use clia_nginx_config::{parse_directives, Quotes, Style};
+
+
+/// `vhost_traffic_status_filter_by_set_key <key> [<name>];`
+#[derive(Debug, Clone, PartialEq)]
+struct FilterKey {
+    key: Value<'static>,
+    name: Option<Value<'static>>,
+}
+
+impl ExtensionDirective for FilterKey {
+    fn name(&self) -> &'static str {
+        "vhost_traffic_status_filter_by_set_key"
+    }
+    fn arguments(&self) -> Vec<Value<'static>> {
+        let mut args = vec![self.key.clone()];
+        args.extend(self.name.clone());
+        args
+    }
+    fn visit_values<'a>(&'a self, f: &mut dyn FnMut(&'a Value<'static>)) {
+        f(&self.key);
+        if let Some(ref name) = self.name {
+            f(name);
+        }
+    }
+    fn visit_values_mut(&mut self, f: &mut dyn FnMut(&mut Value<'static>)) {
+        f(&mut self.key);
+        if let Some(ref mut name) = self.name {
+            f(name);
+        }
+    }
+}
+
+/// `sandbox <level> { ... }`
+#[derive(Debug, Clone, PartialEq)]
+struct Sandbox {
+    level: u32,
+}
+
+impl ExtensionDirective for Sandbox {
+    fn name(&self) -> &'static str {
+        "sandbox"
+    }
+    fn arguments(&self) -> Vec<Value<'static>> {
+        vec![self.level.to_string().parse().unwrap()]
+    }
+    fn validate(&self) -> Vec<String> {
+        if self.level > 3 {
+            vec![format!("sandbox level {} is too high", self.level)]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+fn extensions() -> Extensions {
+    let mut extensions = Extensions::new();
+    extensions.register("vhost_traffic_status_filter_by_set_key", false,
+        |mut args| {
+            if args.is_empty() || args.len() > 2 {
+                return Err("expected one or two arguments".into());
+            }
+            let key = args.remove(0);
+            Ok(Box::new(FilterKey { key, name: args.pop() }))
+        });
+    extensions.register("sandbox", true, |args| {
+        match args.first().map(|v| v.to_string().parse()) {
+            Some(Ok(level)) if args.len() == 1 => {
+                Ok(Box::new(Sandbox { level }))
+            }
+            _ => Err("expected sandbox level".into()),
+        }
+    });
+    extensions
+}
+
+#[test]
+fn parse_and_display() {
+    let ext = extensions();
+    let text = "\
+location / {
+    vhost_traffic_status_filter_by_set_key $uri uri::$server_name;
+
+    sandbox 2 {
+        root /srv;
+    }
+}
+";
+    let main = ext.parse_main(text).unwrap();
+    assert_eq!(main.to_string(), text);
+    let names = main.all_directives()
+        .map(|d| d.item.directive_name())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec![
+        "location", "vhost_traffic_status_filter_by_set_key",
+        "sandbox", "root",
+    ]);
+}
+
+#[test]
+fn quoting() {
+    let ext = extensions();
+    let main = ext.parse_main("\
+        vhost_traffic_status_filter_by_set_key '$uri x';
+        sandbox 2 { }
+    ").unwrap();
+    assert_eq!(main.to_string(), "\
+vhost_traffic_status_filter_by_set_key '$uri x';
+
+sandbox 2 {
+}
+");
+    assert_eq!(main.format(Style::default().quotes(Quotes::Double)), "\
+vhost_traffic_status_filter_by_set_key '$uri x';
+
+sandbox \"2\" {
+}
+");
+}
+
+#[test]
+fn downcast() {
+    let ext = extensions();
+    let dirs = ext.parse_directives("sandbox 1 { }").unwrap();
+    match dirs[0].item {
+        Item::Extension(ref ext) => {
+            let sandbox = ext.directive.downcast_ref::<Sandbox>().unwrap();
+            assert_eq!(sandbox.level, 1);
+            assert!(ext.directive.downcast_ref::<FilterKey>().is_none());
+            assert_eq!(ext.directives.as_ref().map(|d| d.len()), Some(0));
+        }
+        ref item => panic!("unexpected {:?}", item),
+    }
+    let copy = dirs[0].clone().into_owned();
+    assert_eq!(copy, dirs[0]);
+    assert_ne!(copy, ext.parse_directives("sandbox 2 { }").unwrap()[0]);
+}
+
+#[test]
+fn values() {
+    let ext = extensions();
+    let mut dirs = ext.parse_directives("\
+        vhost_traffic_status_filter_by_set_key $uri $name;
+        sandbox 1 { root $root; }
+    ").unwrap();
+    let mut values = Vec::new();
+    dirs[0].visit_values(|v| values.push(v.to_string()));
+    assert_eq!(values, vec!["$uri", "$name"]);
+    replace_vars(&mut dirs, |name| match name {
+        "name" => Some("main"),
+        "root" => Some("/srv"),
+        _ => None,
+    });
+    assert_eq!(dirs[0].to_string(),
+        "vhost_traffic_status_filter_by_set_key $uri main;\n");
+    assert_eq!(dirs[1].to_string(), "sandbox 1 {\n    root /srv;\n}\n");
+}
+
+#[test]
+fn validation() {
+    let ext = extensions();
+    let main = ext.parse_main("sandbox 1 { }\nsandbox 5 { }").unwrap();
+    let errors = validate(&main.directives).into_iter()
+        .map(|e| e.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(errors, vec!["2:1: sandbox level 5 is too high"]);
+}
+
+#[test]
+fn errors() {
+    let ext = extensions();
+    assert!(ext.parse_directives("sandbox 1;").is_err());
+    assert!(ext.parse_directives("sandbox x { }").is_err());
+    assert!(ext.parse_directives(
+        "vhost_traffic_status_filter_by_set_key a { }").is_err());
+    assert!(ext.parse_directives(
+        "vhost_traffic_status_filter_by_set_key;").is_err());
+    assert!(ext.parse_directives("vhost_traffic_status on;").is_err());
+}
+
+#[test]
+fn builtin_first() {
+    let mut ext = Extensions::new();
+    ext.register("root", false, |_| Err("never called".into()));
+    let dirs = ext.parse_directives("root /srv;").unwrap();
+    assert_eq!(dirs[0].item.directive_name(), "root");
+    assert!(matches!(dirs[0].item, Item::Root(..)));
+}
+
+#[test]
+fn separate_sets() {
+    assert!(parse_directives("sandbox 1 { }").is_err());
+    assert!(Extensions::new().parse_directives("sandbox 1 { }").is_err());
+    assert!(extensions().parse_directives("sandbox 1 { }").is_ok());
+}
@@ -0,0 +1,45 @@
+extern crate clia_nginx_config;
+#[cfg(test)] #[macro_use] extern crate pretty_assertions;
+
+use clia_nginx_config::parse_directives;
+use clia_nginx_config::validate::check_limit_zones;
+
+fn errors(text: &str) -> Vec<String> {
+    let dirs = parse_directives(text).unwrap();
+    check_limit_zones(&dirs).iter().map(|e| e.to_string()).collect()
+}
+
+#[test]
+fn declared() {
+    assert_eq!(errors(r#"
+        limit_req_zone $binary_remote_addr zone=api:10m rate=10r/s;
+        limit_conn_zone $binary_remote_addr zone=addr:10m;
+        server {
+            location /api {
+                limit_req zone=api burst=20 nodelay;
+                limit_conn addr 10;
+            }
+        }
+    "#), Vec::<String>::new());
+}
+
+#[test]
+fn undeclared() {
+    assert_eq!(errors(r#"limit_req_zone $binary_remote_addr zone=api:10m rate=1r/s;
+        limit_conn_zone $binary_remote_addr zone=addr:10m;
+        server {
+            limit_req zone=addr;
+            location /api {
+                limit_conn api 1;
+                limit_req zone=other burst=5;
+            }
+        }
+    "#), vec![
+        "4:13: limit_req refers to zone \"addr\" \
+            which is not declared by limit_req_zone",
+        "6:17: limit_conn refers to zone \"api\" \
+            which is not declared by limit_conn_zone",
+        "7:17: limit_req refers to zone \"other\" \
+            which is not declared by limit_req_zone",
+    ]);
+}
@@ -0,0 +1,99 @@
+extern crate clia_nginx_config;
+#[cfg(test)] #[macro_use] extern crate pretty_assertions;
+
+use clia_nginx_config::parse_directives;
+use clia_nginx_config::lint::{Linter, TLS_MISSING_HSTS};
+
+fn tls(text: &str) -> Vec<String> {
+    let dirs = parse_directives(text).unwrap();
+    Linter::tls().check(&dirs).iter().map(|w| w.to_string()).collect()
+}
+
+#[test]
+fn secure() {
+    assert_eq!(tls(r#"
+        http {
+            ssl_certificate /keys/my.crt;
+            ssl_protocols TLSv1.2 TLSv1.3;
+            ssl_ciphers HIGH:!aNULL:!MD5:!RC4;
+            add_header Strict-Transport-Security "max-age=31536000" always;
+            server {
+                listen 443 ssl;
+            }
+            server {
+                listen 80;
+            }
+        }
+    "#), Vec::<String>::new());
+}
+
+#[test]
+fn deprecated_protocols() {
+    assert_eq!(tls("ssl_protocols SSLv3 TLSv1.1 TLSv1.2;"), vec![
+        "1:1: protocol SSLv3 is deprecated [tls-deprecated-protocol]",
+        "1:1: protocol TLSv1.1 is deprecated [tls-deprecated-protocol]",
+    ]);
+}
+
+#[test]
+fn weak_ciphers() {
+    assert_eq!(tls("ssl_ciphers HIGH:RC4-SHA:!aNULL:DES-CBC3-SHA:eNULL;"), vec![
+        "1:1: weak cipher \"RC4-SHA\" is enabled [tls-weak-cipher]",
+        "1:1: weak cipher \"DES-CBC3-SHA\" is enabled [tls-weak-cipher]",
+        "1:1: weak cipher \"eNULL\" is enabled [tls-weak-cipher]",
+    ]);
+}
+
+#[test]
+fn des_and_export_ciphers() {
+    assert_eq!(tls("ssl_ciphers DES-CBC-SHA:EXP-DES-CBC-SHA:+EXP:LOW;"), vec![
+        "1:1: weak cipher \"DES-CBC-SHA\" is enabled [tls-weak-cipher]",
+        "1:1: weak cipher \"EXP-DES-CBC-SHA\" is enabled [tls-weak-cipher]",
+        "1:1: weak cipher \"+EXP\" is enabled [tls-weak-cipher]",
+        "1:1: weak cipher \"LOW\" is enabled [tls-weak-cipher]",
+    ]);
+}
+
+#[test]
+fn space_separated_ciphers() {
+    assert_eq!(tls(r#"ssl_ciphers "ECDHE-RSA-AES128-SHA RC4-MD5, !DES";"#),
+        vec![
+            "1:1: weak cipher \"RC4-MD5\" is enabled [tls-weak-cipher]",
+        ]);
+}
+
+#[test]
+fn server_without_certificate() {
+    assert_eq!(tls(r#"server {
+            listen 443 ssl;
+            add_header Strict-Transport-Security max-age=600;
+            location / {
+                ssl_protocols TLSv1;
+            }
+        }
+    "#), vec![
+        "1:1: server listens with ssl but has no ssl_certificate \
+         [tls-missing-certificate]",
+        "5:17: protocol TLSv1 is deprecated [tls-deprecated-protocol]",
+    ]);
+}
+
+#[test]
+fn hsts_not_inherited() {
+    // add_header on server level overrides the ones from http
+    let text = r#"http {
+            add_header Strict-Transport-Security max-age=600;
+            server {
+                listen 443 ssl;
+                ssl_certificate /keys/my.crt;
+                add_header X-Frame-Options DENY;
+            }
+        }
+    "#;
+    assert_eq!(tls(text), vec![
+        "3:13: https server has no `add_header Strict-Transport-Security` \
+         [tls-missing-hsts]",
+    ]);
+    let dirs = parse_directives(text).unwrap();
+    assert_eq!(Linter::tls().suppress(TLS_MISSING_HSTS).check(&dirs), vec![]);
+}
@@ -0,0 +1,69 @@
+extern crate clia_nginx_config;
+#[cfg(test)] #[macro_use] extern crate pretty_assertions;
+
+use clia_nginx_config::{parse_main, parse_directives};
+use clia_nginx_config::ast::{Item, LocationPattern};
+use clia_nginx_config::validate::check_named_locations;
+
+fn errors(text: &str) -> Vec<String> {
+    let dirs = parse_directives(text).unwrap();
+    check_named_locations(&dirs).iter().map(|e| e.to_string()).collect()
+}
+
+#[test]
+fn parse_named() {
+    let ast = parse_main("location @fallback {\n}\n").unwrap();
+    match ast.directives[0].item {
+        Item::Location(ref loc) => {
+            assert_eq!(loc.pattern, LocationPattern::Named("fallback".into()));
+        }
+        _ => unreachable!(),
+    }
+    assert_eq!(ast.to_string(), "location @fallback {\n}\n");
+}
+
+#[test]
+fn defined() {
+    assert_eq!(errors(r#"
+        server {
+            location / {
+                try_files $uri @fallback;
+                error_page 404 @fallback;
+            }
+            error_page 500 = @fallback;
+            location @fallback { }
+        }
+    "#), Vec::<String>::new());
+}
+
+#[test]
+fn undefined() {
+    assert_eq!(errors(r#"server {
+            location / {
+                try_files $uri @missing;
+            }
+            error_page 404 @notfound;
+            location @fallback { }
+        }
+        server {
+            location @missing { }
+            return 302 @fallback;
+        }
+    "#), vec![
+        "3:17: try_files refers to undefined named location @missing",
+        "5:13: error_page refers to undefined named location @notfound",
+        "10:13: return refers to undefined named location @fallback",
+    ]);
+}
+
+#[test]
+fn nested() {
+    assert_eq!(errors(r#"server {
+            location / {
+                location @inner { }
+            }
+        }
+    "#), vec![
+        "3:17: named location @inner must be defined at server level",
+    ]);
+}
@@ -0,0 +1,108 @@
+extern crate clia_nginx_config;
+#[cfg(test)] #[macro_use] extern crate pretty_assertions;
+
+use std::path::Path;
+
+use clia_nginx_config::{parse_nginx_t_dump, ConfigError};
+use clia_nginx_config::ast::Directive;
+
+
+const DUMP: &str = "\
+nginx: the configuration file /etc/nginx/nginx.conf syntax is ok
+nginx: configuration file /etc/nginx/nginx.conf test is successful
+# configuration file /etc/nginx/nginx.conf:
+worker_processes 2;
+http {
+    include mime.types;
+    include /etc/nginx/conf.d/*.conf;
+}
+
+# configuration file /etc/nginx/mime.types:
+types {
+    text/html html;
+}
+
+# configuration file /etc/nginx/conf.d/b.conf:
+server {
+    listen 8080;
+}
+
+# configuration file /etc/nginx/conf.d/a.conf:
+server {
+    listen 80;
+}
+
+";
+
+fn file<'a>(dir: &'a Directive) -> &'a str {
+    dir.file.as_ref().unwrap().to_str().unwrap()
+}
+
+#[test]
+fn includes_resolved() {
+    let main = parse_nginx_t_dump(DUMP).unwrap();
+    assert_eq!(main.to_string(), "\
+worker_processes 2;
+
+http {
+    types {
+        text/html html;
+    }
+
+    server {
+        listen 80;
+    }
+
+    server {
+        listen 8080;
+    }
+}
+");
+}
+
+#[test]
+fn directive_files() {
+    let main = parse_nginx_t_dump(DUMP).unwrap();
+    let files = main.all_directives()
+        .map(|d| (d.item.directive_name(), file(d)))
+        .collect::<Vec<_>>();
+    assert_eq!(files, vec![
+        ("worker_processes", "/etc/nginx/nginx.conf"),
+        ("http", "/etc/nginx/nginx.conf"),
+        ("types", "/etc/nginx/mime.types"),
+        ("server", "/etc/nginx/conf.d/a.conf"),
+        ("listen", "/etc/nginx/conf.d/a.conf"),
+        ("server", "/etc/nginx/conf.d/b.conf"),
+        ("listen", "/etc/nginx/conf.d/b.conf"),
+    ]);
+}
+
+#[test]
+fn positions_within_file() {
+    let main = parse_nginx_t_dump(DUMP).unwrap();
+    let server = main.all_directives()
+        .find(|d| d.item.directive_name() == "server")
+        .unwrap();
+    assert_eq!(server.file.as_deref(),
+               Some(Path::new("/etc/nginx/conf.d/a.conf")));
+    assert_eq!((server.position.line, server.position.column), (1, 1));
+    let listen = &server.item.children().unwrap()[0];
+    assert_eq!((listen.position.line, listen.position.column), (2, 5));
+}
+
+#[test]
+fn unknown_include_kept() {
+    let main = parse_nginx_t_dump("\
+# configuration file /etc/nginx/nginx.conf:
+include missing/*.conf;
+").unwrap();
+    assert_eq!(main.to_string(), "include missing/*.conf;\n");
+}
+
+#[test]
+fn no_files() {
+    match parse_nginx_t_dump("nginx: [emerg] unknown directive") {
+        Err(ConfigError::EmptyDump) => {}
+        res => panic!("unexpected {:?}", res),
+    }
+}
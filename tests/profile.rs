@@ -0,0 +1,172 @@
+extern crate clia_nginx_config;
+#[cfg(test)] #[macro_use] extern crate pretty_assertions;
+
+use clia_nginx_config::{parse_main, ConfigError};
+use clia_nginx_config::lint::DEPRECATED_DIRECTIVE;
+use clia_nginx_config::profile::ParserProfile;
+use clia_nginx_config::registry::Version;
+
+
+fn errors(profile: &ParserProfile, text: &str) -> Vec<String> {
+    let main = parse_main(text).unwrap();
+    profile.validate(&main.directives).into_iter()
+        .map(|e| e.message).collect()
+}
+
+fn warnings(profile: &ParserProfile, text: &str) -> Vec<String> {
+    let main = parse_main(text).unwrap();
+    profile.lint(&main.directives).into_iter()
+        .inspect(|w| assert_eq!(w.rule, DEPRECATED_DIRECTIVE))
+        .map(|w| w.message).collect()
+}
+
+#[test]
+fn nginx_v() {
+    let profile = ParserProfile::from_nginx_v("\
+nginx version: nginx/1.24.0
+built by gcc 12.2.0 (Debian 12.2.0-14)
+built with OpenSSL 3.0.9 30 May 2023
+TLS SNI support enabled
+configure arguments: --prefix=/etc/nginx --with-http_ssl_module \
+--with-http_v2_module --with-http_geoip_module=dynamic \
+--without-http_gzip_module --with-stream
+").unwrap();
+    assert_eq!(profile.target_version(), Some(Version::new(1, 24, 0)));
+    assert!(profile.is_enabled("ngx_http_core_module"));
+    assert!(profile.is_enabled("ngx_http_ssl_module"));
+    assert!(profile.is_enabled("ngx_http_v2_module"));
+    assert!(profile.is_enabled("ngx_stream_module"));
+    assert!(!profile.is_enabled("ngx_http_geoip_module"));
+    assert!(!profile.is_enabled("ngx_http_gzip_module"));
+    assert!(!profile.is_available("gzip"));
+    assert!(profile.is_available("ssl"));
+    assert!(!profile.is_available("http2"));
+}
+
+#[test]
+fn forks() {
+    let openresty = ParserProfile::from_nginx_v("\
+nginx version: openresty/1.21.4.1
+configure arguments: --add-module=../ngx_lua-0.10.21
+").unwrap();
+    assert_eq!(openresty.target_version(), Some(Version::new(1, 21, 4)));
+    assert!(openresty.is_enabled("ngx_http_lua_module"));
+    let tengine = ParserProfile::from_nginx_v("\
+Tengine version: Tengine/2.3.3 (nginx/1.18.0)
+built by gcc 8.5.0 20210514 (Red Hat 8.5.0-4) (GCC)
+built with OpenSSL 1.1.1k  FIPS 25 Mar 2021
+TLS SNI support enabled
+configure arguments: --prefix=/usr/local/nginx --with-http_ssl_module \
+--with-http_v2_module
+").unwrap();
+    assert_eq!(tengine.target_version(), Some(Version::new(1, 18, 0)));
+    assert!(tengine.is_enabled("ngx_http_v2_module"));
+    let angie = ParserProfile::from_nginx_v("\
+Angie version: Angie/1.4.0
+built by gcc 12.2.0 (Debian 12.2.0-14)
+built with OpenSSL 3.0.11 19 Sep 2023
+TLS SNI support enabled
+configure arguments: --prefix=/etc/angie --with-http_ssl_module \
+--with-http_v2_module
+").unwrap();
+    assert_eq!(angie.target_version(), Some(Version::new(1, 25, 3)));
+    assert!(angie.parse_main("http { server { http2 on; } }").is_ok());
+    let angie = ParserProfile::from_nginx_v(
+        "nginx version: Angie/1.4.0").unwrap();
+    assert_eq!(angie.target_version(), Some(Version::new(1, 25, 3)));
+    // unknown releases and forks aren't guessed
+    assert!(ParserProfile::from_nginx_v(
+        "nginx version: Angie/9.0.0").is_err());
+    assert!(ParserProfile::from_nginx_v(
+        "nginx version: zengine/1.27.2").is_err());
+    assert!(ParserProfile::from_nginx_v("nginx: command not found").is_err());
+}
+
+#[test]
+fn unrestricted() {
+    let profile = ParserProfile::new();
+    assert_eq!(errors(&profile, "\
+        http { gzip on; server { listen 443 ssl http2; ssl on; http2 on; } }
+    "), Vec::<String>::new());
+}
+
+#[test]
+fn version() {
+    let mut profile = ParserProfile::new();
+    profile.version(Version::new(1, 25, 1));
+    assert_eq!(errors(&profile, "\
+        http { server { listen 443 spdy; ssl on; http2 on; } }
+    "), vec![
+        "listen ... spdy was removed in nginx 1.9.5",
+        "ssl was removed in nginx 1.25.1",
+    ]);
+    profile.version(Version::new(1, 9, 4));
+    assert_eq!(errors(&profile, "\
+        http { server { listen 443 http2; http2 on; } }
+    "), vec![
+        "listen ... http2 requires nginx 1.9.5 or later",
+        "http2 requires nginx 1.25.1 or later",
+    ]);
+    assert!(profile.parse_main("http { server { ssl on; } }").is_ok());
+    match profile.parse_main("http { server { http2 on; } }") {
+        Err(ConfigError::Invalid { ref errors }) => {
+            assert_eq!(errors.iter().map(|e| e.to_string())
+                .collect::<Vec<_>>(),
+                vec!["1:17: http2 requires nginx 1.25.1 or later"]);
+        }
+        res => panic!("unexpected {:?}", res),
+    }
+}
+
+#[test]
+fn modules() {
+    let mut profile = ParserProfile::new();
+    profile.standard_modules();
+    assert_eq!(errors(&profile, "\
+        http { server { listen 443 ssl; ssl_certificate a.pem; } }
+    "), vec![
+        "listen ... ssl requires ngx_http_ssl_module which is not enabled",
+        "ssl_certificate requires ngx_http_ssl_module which is not enabled",
+    ]);
+    profile.enable_module("ngx_http_ssl_module");
+    assert!(profile.parse_main("\
+        http { server { listen 443 ssl; ssl_certificate a.pem; } }
+    ").is_ok());
+    profile.disable_module("ngx_http_gzip_module");
+    assert!(profile.parse_main("http { gzip on; }").is_err());
+}
+
+#[test]
+fn load_module() {
+    let mut profile = ParserProfile::new();
+    profile.disable_module("ngx_http_v2_module");
+    assert_eq!(errors(&profile, "http { server { http2 on; } }"), vec![
+        "http2 requires ngx_http_v2_module which is not enabled",
+    ]);
+    assert!(profile.parse_main("\
+        load_module /usr/lib/nginx/modules/ngx_http_v2_module.so;
+        http { server { http2 on; } }
+    ").is_ok());
+}
+
+#[test]
+fn deprecated() {
+    let latest = ParserProfile::new();
+    assert_eq!(warnings(&latest, "\
+        http { server { listen 443 ssl http2; } }
+    "), vec![
+        "the http2 parameter of listen is deprecated since nginx 1.25.1, \
+         use `http2 on;`",
+    ]);
+    let mut old = ParserProfile::new();
+    old.version(Version::new(1, 16, 1));
+    assert_eq!(warnings(&old, "\
+        http { server { listen 443 http2; ssl on; } }
+    "), vec![
+        "ssl directive is deprecated since nginx 1.15.0, \
+         use `listen ... ssl`",
+    ]);
+    old.version(Version::new(1, 14, 0));
+    assert_eq!(warnings(&old, "http { server { ssl on; } }"),
+               Vec::<String>::new());
+}
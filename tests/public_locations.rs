@@ -0,0 +1,77 @@
+extern crate clia_nginx_config;
+#[cfg(test)] #[macro_use] extern crate pretty_assertions;
+
+use clia_nginx_config::parse_directives;
+use clia_nginx_config::lint::public_locations;
+
+fn public(text: &str) -> Vec<String> {
+    let dirs = parse_directives(text).unwrap();
+    public_locations(&dirs).iter().map(|l| l.pattern.to_string()).collect()
+}
+
+#[test]
+fn no_auth() {
+    assert_eq!(public(r#"
+        server {
+            location / { }
+            location @fallback { }
+            location /internal { internal; }
+        }
+    "#), vec!["/"]);
+}
+
+#[test]
+fn inherited_auth() {
+    assert_eq!(public(r#"
+        server {
+            auth_basic "Restricted";
+            location / { }
+            location /public {
+                auth_basic off;
+                location /public/secret {
+                    auth_request /auth;
+                }
+                location /public/other { }
+            }
+        }
+    "#), vec!["/public", "/public/other"]);
+}
+
+#[test]
+fn deny_all() {
+    assert_eq!(public(r#"
+        server {
+            allow 10.0.0.0/8;
+            deny all;
+            location / { }
+            location /status {
+                deny 10.0.0.1;
+            }
+            location /health {
+                allow all;
+            }
+        }
+    "#), vec!["/status", "/health"]);
+}
+
+#[test]
+fn satisfy_any() {
+    assert_eq!(public(r#"
+        server {
+            satisfy any;
+            auth_jwt "API";
+            location /a {
+                allow 10.0.0.0/8;
+                deny all;
+            }
+            location /b {
+                allow all;
+            }
+            location /c { }
+            location /d {
+                satisfy all;
+                allow all;
+            }
+        }
+    "#), vec!["/b"]);
+}
@@ -0,0 +1,147 @@
+extern crate clia_nginx_config;
+#[macro_use] extern crate matches;
+#[cfg(test)] #[macro_use] extern crate pretty_assertions;
+
+use clia_nginx_config::parse_main;
+use clia_nginx_config::ast::{Item, Main};
+use clia_nginx_config::query::{find, for_each_mut, Query};
+
+const CONFIG: &str = r#"
+http {
+    root /srv/default;
+    server {
+        listen 80;
+        server_name example.com www.example.com;
+        location ^~ /static {
+            root /srv/static;
+        }
+        location ~ ^/img/[a-z]+$ {
+            root /srv/img;
+        }
+        location / {
+            proxy_pass http://backend;
+            location /nested {
+                proxy_pass http://nested;
+            }
+        }
+    }
+    server {
+        listen 443 ssl;
+        server_name api.example.com;
+        location ^~ /static {
+            root /srv/api-static;
+        }
+    }
+}
+"#;
+
+fn texts_of(main: &Main, query: &str) -> Vec<String> {
+    find(&main.directives, query).unwrap().iter()
+        .map(|m| m.directive.to_string().trim().to_string())
+        .collect()
+}
+
+fn texts(query: &str) -> Vec<String> {
+    texts_of(&parse_main(CONFIG).unwrap(), query)
+}
+
+#[test]
+fn child_predicate() {
+    assert_eq!(texts(
+        "http/server[server_name=example.com]/location[^~ /static]/root"),
+        vec!["root /srv/static;"]);
+    assert_eq!(texts("http/server[listen=443]/location/root"),
+        vec!["root /srv/api-static;"]);
+    assert_eq!(texts(
+        "http/server[server_name=www.example.com][listen=80]/*/root"),
+        vec!["root /srv/static;", "root /srv/img;"]);
+    assert!(texts("http/server[server_name=example.org]/location")
+        .is_empty());
+}
+
+#[test]
+fn args_predicate() {
+    assert_eq!(texts("http/server/location[^~ /static]/root"),
+        vec!["root /srv/static;", "root /srv/api-static;"]);
+    assert_eq!(texts("http/server/location[~ ^/img/[a-z]+$]/root"),
+        vec!["root /srv/img;"]);
+}
+
+#[test]
+fn descendant() {
+    assert_eq!(texts("//proxy_pass"),
+        vec!["proxy_pass http://backend;", "proxy_pass http://nested;"]);
+    assert_eq!(texts("http//root"), vec![
+        "root /srv/default;",
+        "root /srv/static;",
+        "root /srv/img;",
+        "root /srv/api-static;",
+    ]);
+    assert_eq!(texts("//location//location/proxy_pass"),
+        vec!["proxy_pass http://nested;"]);
+    // nested proxy_pass is reachable from both locations
+    assert_eq!(texts("//location//proxy_pass"),
+        vec!["proxy_pass http://backend;", "proxy_pass http://nested;"]);
+}
+
+#[test]
+fn ancestors() {
+    let main = parse_main(CONFIG).unwrap();
+    let found = find(&main.directives, "//location[/nested]").unwrap();
+    assert_eq!(found.len(), 1);
+    let names = found[0].ancestors.iter()
+        .map(|d| d.item.directive_name())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["http", "server", "location"]);
+    assert!(matches!(found[0].directive.item, Item::Location(_)));
+}
+
+#[test]
+fn bulk_edit() {
+    let mut main = parse_main(CONFIG).unwrap();
+    let count = for_each_mut(&mut main.directives,
+        "http/server[server_name=example.com]//proxy_pass", |dir| {
+            if let Item::ProxyPass(ref mut val) = dir.item {
+                *val = "http://new-backend".parse().unwrap();
+            }
+        }).unwrap();
+    assert_eq!(count, 2);
+    assert_eq!(texts_of(&main, "//proxy_pass"),
+        vec!["proxy_pass http://new-backend;"; 2]);
+    let count = for_each_mut(&mut main.directives, "//root", |dir| {
+        if let Item::Root(ref mut val) = dir.item {
+            *val = "/var/www".parse().unwrap();
+        }
+    }).unwrap();
+    assert_eq!(count, 4);
+    assert_eq!(texts_of(&main, "//root"), vec!["root /var/www;"; 4]);
+    let mut seen = Vec::new();
+    let count = for_each_mut(&mut main.directives, "//location//*", |dir| {
+        seen.push(dir.item.directive_name());
+        dir.item = Item::ProxyPass("http://other".parse().unwrap());
+    }).unwrap();
+    // replaced nested location has no children to visit
+    assert_eq!(count, 5);
+    assert_eq!(seen,
+        vec!["root", "root", "proxy_pass", "location", "root"]);
+}
+
+#[test]
+fn parse_errors() {
+    assert_eq!(Query::parse("").unwrap_err().to_string(),
+        "invalid query at char 0: empty query");
+    assert_eq!(Query::parse("http/").unwrap_err().to_string(),
+        "invalid query at char 5: expected directive name");
+    assert_eq!(Query::parse("http/server[listen=80").unwrap_err().to_string(),
+        "invalid query at char 11: unclosed `[`");
+}
+
+#[test]
+fn display() {
+    for query in &[
+        "http/server[server_name=example.com]/location[^~ /static]/root",
+        "//location[= /exact]//*",
+    ] {
+        assert_eq!(query.parse::<Query>().unwrap().to_string(), *query);
+    }
+}
@@ -0,0 +1,77 @@
+#![cfg(feature="regex")]
+extern crate clia_nginx_config;
+#[cfg(test)] #[macro_use] extern crate pretty_assertions;
+
+use clia_nginx_config::parse_directives;
+use clia_nginx_config::validate::validate;
+use clia_nginx_config::regex::Regex;
+
+fn errors(text: &str) -> Vec<String> {
+    let dirs = parse_directives(text).unwrap();
+    validate(&dirs).iter().map(|e| e.to_string()).collect()
+}
+
+#[test]
+fn valid_patterns() {
+    assert_eq!(errors(r#"
+        server_name ~^(?<sub>\w+)\.example\.com$;
+        location ~* \.(?:jpg|png)$ { }
+        location ~ ^/(?!api/) { }
+        if ($uri ~ ^/x(\d+)) { }
+        rewrite ^/old/(.*)$ /new/$1 last;
+        map $uri $x {
+            ~*^/A 1;
+            ~^/b(\d) 2;
+        }
+    "#), Vec::<String>::new());
+}
+
+#[test]
+fn invalid_location() {
+    let errs = errors("location ~ ^/(unclosed { }");
+    assert_eq!(errs.len(), 1);
+    assert!(errs[0].starts_with("1:1: invalid regex \"^/(unclosed\""),
+        "{}", errs[0]);
+}
+
+#[test]
+fn invalid_nested() {
+    let errs = errors("server {\n    if ($uri ~* [z-a]) { }\n}");
+    assert_eq!(errs.len(), 1);
+    assert!(errs[0].starts_with("2:5: invalid regex"), "{}", errs[0]);
+}
+
+#[test]
+fn rewrite_captures() {
+    assert_eq!(errors("rewrite ^/x$ /y/$1;"), vec![
+        "1:1: rewrite replacement refers to $1 but \
+         regex \"^/x$\" has only 0 capture group(s)",
+    ]);
+    assert_eq!(errors("rewrite ^/(x)$ /y/$1$0;"), Vec::<String>::new());
+}
+
+#[test]
+fn rewrite_named_captures_not_checked() {
+    assert_eq!(errors("rewrite ^/(?<user>x)$ /y/$user;"), Vec::<String>::new());
+    // `$id` may be a variable defined elsewhere, so it is not reported
+    assert_eq!(errors("rewrite ^/(?<user>x)$ /y/$id;"), Vec::<String>::new());
+}
+
+#[test]
+fn captures() {
+    let re = Regex::new(r"^/(?<user>\w+)/(\d+)", false).unwrap();
+    assert_eq!(re.captures_len(), 2);
+    assert_eq!(re.capture_names(), vec!["user"]);
+    assert!(re.has_capture("2"));
+    assert!(re.has_capture("user"));
+    assert!(!re.has_capture("3"));
+    assert!(!re.has_capture("id"));
+}
+
+#[test]
+fn case_insensitive() {
+    let re = Regex::new(r"^/abc$", true).unwrap();
+    assert!(re.is_match("/ABC"));
+    let re = Regex::new(r"^/abc$", false).unwrap();
+    assert!(!re.is_match("/ABC"));
+}
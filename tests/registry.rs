@@ -0,0 +1,93 @@
+extern crate clia_nginx_config;
+#[cfg(test)] #[macro_use] extern crate pretty_assertions;
+
+use std::fs;
+
+use clia_nginx_config::parse_main;
+use clia_nginx_config::registry::{self, Arity, Context, Version};
+
+
+#[test]
+fn sorted_and_unique() {
+    let names = registry::directives().iter()
+        .map(|d| d.name).collect::<Vec<_>>();
+    let mut sorted = names.clone();
+    sorted.sort();
+    sorted.dedup();
+    assert_eq!(names, sorted);
+}
+
+#[test]
+fn parsed_directives_known() {
+    for entry in fs::read_dir("tests/configs").unwrap() {
+        let text = fs::read_to_string(entry.unwrap().path()).unwrap();
+        let config = parse_main(&text).unwrap();
+        for dir in config.all_directives() {
+            let name = dir.item.directive_name();
+            let info = registry::lookup(name)
+                .unwrap_or_else(|| panic!("no entry for {:?}", name));
+            if dir.item.children().is_some() {
+                assert!(info.block, "{} is a block", name);
+            }
+        }
+    }
+}
+
+#[test]
+fn unparsed_directives() {
+    let info = registry::lookup("worker_connections").unwrap();
+    assert_eq!(info.module, "ngx_event_core_module");
+    assert_eq!(info.contexts, &[Context::Events]);
+    assert_eq!(info.default, Some("512"));
+    assert!(registry::lookup("no_such_directive").is_none());
+}
+
+#[test]
+fn facts() {
+    let header = registry::lookup("add_header").unwrap();
+    assert!(header.repeatable);
+    assert!(header.inherited);
+    assert!(header.allowed_in(Context::LocationIf));
+    assert!(!header.allowed_in(Context::Main));
+    assert_eq!(header.arity, Arity { min: 2, max: Some(3) });
+
+    let pass = registry::lookup("proxy_pass").unwrap();
+    assert!(!pass.inherited);
+    assert!(!pass.repeatable);
+    assert_eq!(pass.default, None);
+
+    let location = registry::lookup("location").unwrap();
+    assert!(location.block);
+    assert_eq!(location.contexts, &[Context::Server, Context::Location]);
+}
+
+#[test]
+fn arity() {
+    let arity = registry::lookup("rewrite").unwrap().arity;
+    assert!(!arity.accepts(1));
+    assert!(arity.accepts(2));
+    assert!(arity.accepts(3));
+    assert!(!arity.accepts(4));
+    assert_eq!(arity.to_string(), "2 to 3");
+    let arity = registry::lookup("error_page").unwrap().arity;
+    assert!(arity.accepts(10));
+    assert_eq!(arity.to_string(), "2 or more");
+}
+
+#[test]
+fn versions() {
+    assert_eq!("1.25.1".parse::<Version>().unwrap(), Version::new(1, 25, 1));
+    assert_eq!("1.9".parse::<Version>().unwrap(), Version::new(1, 9, 0));
+    assert!(Version::new(1, 9, 5) < Version::new(1, 25, 0));
+    assert_eq!(Version::new(1, 25, 1).to_string(), "1.25.1");
+    assert!("1".parse::<Version>().is_err());
+    assert!("1.x.2".parse::<Version>().is_err());
+    assert!("1.2.3.4".parse::<Version>().is_err());
+
+    let ssl = registry::lookup("ssl").unwrap();
+    assert!(ssl.available_in(Version::new(1, 24, 0)));
+    assert!(!ssl.available_in(Version::new(1, 25, 1)));
+    let http2 = registry::lookup("http2").unwrap();
+    assert!(!http2.available_in(Version::new(1, 24, 0)));
+    assert!(http2.available_in(Version::new(1, 25, 1)));
+}
@@ -23,6 +23,12 @@ fn roundtrip(filename: &str) {
 #[test] fn http() { roundtrip("http"); }
 #[test] fn listen() { roundtrip("listen"); }
 #[test] fn proxy() { roundtrip("proxy"); }
+#[test] fn fastcgi() { roundtrip("fastcgi"); }
+#[test] fn uwsgi() { roundtrip("uwsgi"); }
+#[test] fn scgi() { roundtrip("scgi"); }
+#[test] fn grpc() { roundtrip("grpc"); }
+#[test] fn limit() { roundtrip("limit"); }
+#[test] fn auth() { roundtrip("auth"); }
 #[test] fn location() { roundtrip("location"); }
 #[test] fn two_locations() { roundtrip("two_locations"); }
 #[test] fn gzip() { roundtrip("gzip"); }
@@ -55,5 +61,6 @@ fn roundtrip(filename: &str) {
 #[test] fn real_ip() { roundtrip("real_ip"); }
 #[test] fn error_log() { roundtrip("error_log"); }
 #[test] fn index() { roundtrip("index"); }
+#[test] fn modules() { roundtrip("modules"); }
 // not working yet
 //#[test] fn few_locations() { roundtrip("few_locations"); }
@@ -0,0 +1,85 @@
+extern crate clia_nginx_config;
+#[cfg(test)] #[macro_use] extern crate pretty_assertions;
+
+use clia_nginx_config::{parse_directives, Pos};
+use clia_nginx_config::ast::{Directive, Item, IfCondition};
+
+const CONFIG: &str = "\
+# comment\r
+server {\r
+\tlisten 80;  # trailing\r
+\tlocation /caf\u{e9} {\r
+\t\troot \"/srv/my site\";\r
+\t}\r
+\tif ($http_x != 'yes') { return 403; }\r
+}\r
+";
+
+fn texts(dirs: &[Directive]) -> Vec<&'static str> {
+    dirs.iter().map(|d| &CONFIG[d.span.range()]).collect()
+}
+
+#[test]
+fn directive_spans() {
+    let dirs = parse_directives(CONFIG).unwrap();
+    assert_eq!(texts(&dirs), vec![&CONFIG[11..CONFIG.len()-2]]);
+    let children = dirs[0].item.children().unwrap();
+    assert_eq!(texts(children), vec![
+        "listen 80;",
+        "location /caf\u{e9} {\r\n\t\troot \"/srv/my site\";\r\n\t}",
+        "if ($http_x != 'yes') { return 403; }",
+    ]);
+    assert_eq!(texts(children[1].item.children().unwrap()),
+        vec!["root \"/srv/my site\";"]);
+}
+
+#[test]
+fn line_and_column() {
+    let dirs = parse_directives(CONFIG).unwrap();
+    let children = dirs[0].item.children().unwrap();
+    assert_eq!(dirs[0].span.start, Pos { line: 2, column: 1, offset: 11 });
+    // tab is a single column
+    assert_eq!(children[0].span.start,
+        Pos { line: 3, column: 2, offset: 22 });
+    assert_eq!(children[0].span.end,
+        Pos { line: 3, column: 12, offset: 32 });
+    let root = &children[1].item.children().unwrap()[0];
+    assert_eq!(root.position, root.span.start);
+    assert_eq!(root.position.line, 5);
+    assert_eq!(root.position.column, 3);
+}
+
+#[test]
+fn value_spans() {
+    let dirs = parse_directives(CONFIG).unwrap();
+    let children = dirs[0].item.children().unwrap();
+    let root = &children[1].item.children().unwrap()[0];
+    let mut spans = Vec::new();
+    root.visit_values(|v| spans.push(v.span()));
+    assert_eq!(spans.iter().map(|s| &CONFIG[s.range()]).collect::<Vec<_>>(),
+        vec!["\"/srv/my site\""]);
+    assert_eq!(spans[0].start.column, 8);
+    match children[2].item {
+        Item::If(ref cond) => match cond.condition {
+            IfCondition::Neq(ref value, _) => {
+                assert_eq!(&CONFIG[value.span().range()], "$http_x");
+            }
+            ref c => panic!("unexpected condition {:?}", c),
+        },
+        ref item => panic!("unexpected {:?}", item),
+    }
+}
+
+#[test]
+fn block_spans() {
+    let dirs = parse_directives(CONFIG).unwrap();
+    let children = dirs[0].item.children().unwrap();
+    match children[1].item {
+        Item::Location(ref loc) => {
+            let (start, end) = loc.position;
+            assert_eq!(&CONFIG[start.offset..end.offset],
+                "{\r\n\t\troot \"/srv/my site\";\r\n\t}");
+        }
+        ref item => panic!("unexpected {:?}", item),
+    }
+}
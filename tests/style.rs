@@ -0,0 +1,205 @@
+extern crate clia_nginx_config;
+#[cfg(test)] #[macro_use] extern crate pretty_assertions;
+
+use clia_nginx_config::{parse_main, Style, Quotes};
+use clia_nginx_config::ast::{CachePathKey, Item, LimitReqZoneKey, QuoteStyle};
+
+const CONFIG: &str = "\
+http {
+    server {
+        listen 80;
+        proxy_set_header Host $host;
+        proxy_set_header X-Real-IP $remote_addr;
+        proxy_set_header X-Forwarded-For $proxy_add_x_forwarded_for;
+        add_header Strict-Transport-Security \"max-age=31536000\" always;
+        location / {
+            root /srv/www;
+        }
+    }
+}
+";
+
+fn format(style: &Style) -> String {
+    parse_main(CONFIG).unwrap().format(style)
+}
+
+#[test]
+fn default_style() {
+    assert_eq!(format(&Style::default()),
+               parse_main(CONFIG).unwrap().to_string());
+}
+
+#[test]
+fn tabs_and_margin() {
+    assert_eq!(format(Style::default().tabs(true).block_margin(0)), "\
+http {
+\tserver {
+\t\tlisten 80;
+\t\tproxy_set_header Host $host;
+\t\tproxy_set_header X-Real-IP $remote_addr;
+\t\tproxy_set_header X-Forwarded-For $proxy_add_x_forwarded_for;
+\t\tadd_header Strict-Transport-Security \"max-age=31536000\" always;
+\t\tlocation / {
+\t\t\troot /srv/www;
+\t\t}
+\t}
+}
+");
+}
+
+#[test]
+fn align_arguments() {
+    assert_eq!(format(Style::default().indent(2).align_arguments(true)), "\
+http {
+  server {
+    listen 80;
+    proxy_set_header Host            $host;
+    proxy_set_header X-Real-IP       $remote_addr;
+    proxy_set_header X-Forwarded-For $proxy_add_x_forwarded_for;
+    add_header Strict-Transport-Security \"max-age=31536000\" always;
+
+    location / {
+      root /srv/www;
+    }
+  }
+}
+");
+}
+
+#[test]
+fn max_width() {
+    let mut style = Style::default();
+    style.max_width(Some(50));
+    assert_eq!(parse_main("\
+        location / {
+            try_files $uri $uri/ /index.html /fallback/index.html \
+                @backend;
+            root /some/very/long/path/which/is/never/wrapped;
+        }
+    ").unwrap().format(&style), "\
+location / {
+    try_files $uri $uri/ /index.html
+        /fallback/index.html @backend;
+    root /some/very/long/path/which/is/never/wrapped;
+}
+");
+}
+
+#[test]
+fn quoted_arguments() {
+    let mut main = parse_main("\
+limit_req_zone $binary_remote_addr zone=one:10m rate=1r/s;
+limit_req_zone $server_name zone=two:10m rate=10r/s;
+").unwrap();
+    // zone name is quoted inside of the argument
+    if let Item::LimitReqZone(ref mut zone) = main.directives[0].item {
+        zone.name = "my zone".into();
+    }
+    assert_eq!(main.format(Style::default().align_arguments(true)), "\
+limit_req_zone $binary_remote_addr zone=\"my zone\":10m rate=1r/s;
+limit_req_zone $server_name        zone=two:10m       rate=10r/s;
+");
+    assert_eq!(main.format(Style::default().max_width(Some(30))), "\
+limit_req_zone $binary_remote_addr
+    zone=\"my zone\":10m
+    rate=1r/s;
+limit_req_zone $server_name
+    zone=two:10m rate=10r/s;
+");
+}
+
+#[test]
+fn edited_cache_path() {
+    let mut main = parse_main("\
+proxy_cache_path /c max_size=1g keys_zone=one:10m min_free=1m;
+").unwrap();
+    if let Item::ProxyCachePath(ref mut path) = main.directives[0].item {
+        path.order.retain(|k| *k != CachePathKey::MaxSize);
+        path.order.push(CachePathKey::Other("missing".into()));
+        path.inactive = Some("1h".parse().unwrap());
+        path.options.push(("manager_files".into(), "10".into()));
+    }
+    assert_eq!(main.to_string(), "\
+proxy_cache_path /c keys_zone=one:10m min_free=1m inactive=1h max_size=1g \
+manager_files=10;
+");
+}
+
+#[test]
+fn edited_limit_req_zone() {
+    let mut main = parse_main("\
+limit_req_zone $host rate=1r/s zone=one:10m;
+").unwrap();
+    if let Item::LimitReqZone(ref mut zone) = main.directives[0].item {
+        zone.order.retain(|k| *k != LimitReqZoneKey::Zone);
+        zone.sync = true;
+    }
+    assert_eq!(main.to_string(), "\
+limit_req_zone $host rate=1r/s zone=one:10m sync;
+");
+}
+
+#[test]
+fn quotes() {
+    let text = "add_header X-Frame-Options DENY;\nroot \"/srv/my site\";\n";
+    let ast = parse_main(text).unwrap();
+    assert_eq!(ast.format(Style::default().quotes(Quotes::Minimal)), text);
+    assert_eq!(ast.format(Style::default().quotes(Quotes::Double)), text);
+    assert_eq!(ast.format(Style::default()
+        .quotes(Quotes::Double).preserve_quotes(false)),
+        "add_header \"X-Frame-Options\" \"DENY\";\nroot \"/srv/my site\";\n");
+    assert_eq!(ast.format(Style::default()
+        .quotes(Quotes::Single).preserve_quotes(false)),
+        "add_header 'X-Frame-Options' 'DENY';\nroot '/srv/my site';\n");
+}
+
+#[test]
+fn preserve_quotes() {
+    let text = "root '/srv/www';\nadd_header \"X\" 'a b' always;\n";
+    assert_eq!(parse_main(text).unwrap().to_string(), text);
+}
+
+#[test]
+fn new_values() {
+    let mut ast = parse_main("root '/srv/www';\nalias /srv/alias;\n").unwrap();
+    for dir in &mut ast.directives {
+        match dir.item {
+            Item::Root(ref mut v) => *v = "/srv/new".parse().unwrap(),
+            Item::Alias(ref mut v) => *v = "'/srv/new'".parse().unwrap(),
+            _ => {}
+        }
+    }
+    assert_eq!(ast.to_string(), "root /srv/new;\nalias '/srv/new';\n");
+    assert_eq!(ast.format(Style::default().quotes(Quotes::Double)),
+        "root \"/srv/new\";\nalias '/srv/new';\n");
+    if let Item::Alias(ref mut v) = ast.directives[1].item {
+        assert_eq!(v.quote_style(), Some(QuoteStyle::Single));
+        v.set_quote_style(None);
+    }
+    assert_eq!(ast.format(Style::default().quotes(Quotes::Double)),
+        "root \"/srv/new\";\nalias \"/srv/new\";\n");
+}
+
+#[test]
+fn sort_map_entries() {
+    let ast = parse_main("\
+        map $http_host $backend {
+            default main;
+            ~^api\\. api;
+            www.example.com www;
+            ~^static\\. static;
+            *.example.org org;
+            admin.example.com admin;
+        }
+    ").unwrap();
+    assert_eq!(ast.format(Style::default().sort_map_entries(true)), "\
+map $http_host $backend {
+    default main;
+    *.example.org org;
+    admin.example.com admin;
+    www.example.com www;
+    ~^api\\. api;
+    ~^static\\. static;
+}
+");
+}
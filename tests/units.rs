@@ -0,0 +1,119 @@
+extern crate clia_nginx_config;
+#[cfg(test)] #[macro_use] extern crate pretty_assertions;
+
+use clia_nginx_config::parse_main;
+use clia_nginx_config::ast::{Item, Size, Duration, Rate, ExpiresTime};
+
+fn first(text: &str) -> Item<'static> {
+    parse_main(text).unwrap().directives.remove(0).item.into_owned()
+}
+
+#[test]
+fn size() {
+    assert_eq!("512".parse::<Size>().unwrap().bytes(), 512);
+    assert_eq!("16k".parse::<Size>().unwrap().bytes(), 16 << 10);
+    assert_eq!("10M".parse::<Size>().unwrap().bytes(), 10 << 20);
+    assert_eq!("1g".parse::<Size>().unwrap().bytes(), 1 << 30);
+    assert_eq!("1024".parse::<Size>().unwrap(), Size::from_bytes(1024));
+    assert_eq!("1024".parse::<Size>().unwrap(), "1k".parse().unwrap());
+    assert!("".parse::<Size>().is_err());
+    assert!("k".parse::<Size>().is_err());
+    assert!("10x".parse::<Size>().is_err());
+    assert!("-1k".parse::<Size>().is_err());
+}
+
+#[test]
+fn size_display() {
+    assert_eq!("10M".parse::<Size>().unwrap().to_string(), "10M");
+    assert_eq!(Size::from_bytes(2048).to_string(), "2k");
+    assert_eq!(Size::from_bytes(3 << 30).to_string(), "3g");
+    assert_eq!(Size::from_bytes(1000).to_string(), "1000");
+    assert_eq!(Size::from_bytes(0).to_string(), "0");
+}
+
+#[test]
+fn duration() {
+    let ms = |s: &str| s.parse::<Duration>().unwrap().as_millis();
+    assert_eq!(ms("30"), 30_000);
+    assert_eq!(ms("30s"), 30_000);
+    assert_eq!(ms("100ms"), 100);
+    assert_eq!(ms("1h30m"), 90*60_000);
+    assert_eq!(ms("1h 30m"), 90*60_000);
+    assert_eq!(ms("1d2h"), 26*3_600_000);
+    assert_eq!(ms("1w"), 7*24*3_600_000);
+    assert_eq!(ms("1M"), 30*24*3_600_000);
+    assert_eq!(ms("1y"), 365*24*3_600_000);
+    assert_eq!(ms("1m1"), 61_000);
+    assert!("".parse::<Duration>().is_err());
+    assert!("10x".parse::<Duration>().is_err());
+    assert!("30m1h".parse::<Duration>().is_err());
+    assert!("1s1s".parse::<Duration>().is_err());
+    assert!("1 1m".parse::<Duration>().is_err());
+}
+
+#[test]
+fn duration_display() {
+    assert_eq!("90m".parse::<Duration>().unwrap().to_string(), "90m");
+    assert_eq!(Duration::from_secs(5400).to_string(), "1h30m");
+    assert_eq!(Duration::from_millis(1500).to_string(), "1s500ms");
+    assert_eq!(Duration::from_millis(0).to_string(), "0s");
+    assert_eq!("90m".parse::<Duration>().unwrap(), Duration::from_secs(5400));
+    assert_eq!(Duration::from_secs(u64::MAX / 1000).as_millis(),
+               u64::MAX / 1000 * 1000);
+    assert_eq!(Duration::from_secs(u64::MAX / 1000 + 1).as_millis(),
+               u64::MAX);
+    assert_eq!(Duration::from_secs(u64::MAX).as_millis(), u64::MAX);
+}
+
+#[test]
+fn rate() {
+    assert_eq!("10r/s".parse::<Rate>().unwrap(), Rate::PerSecond(10));
+    assert_eq!("30r/m".parse::<Rate>().unwrap(), Rate::PerMinute(30));
+    assert_eq!(Rate::PerMinute(30).to_string(), "30r/m");
+    assert!("10r".parse::<Rate>().is_err());
+    assert!("r/s".parse::<Rate>().is_err());
+}
+
+#[test]
+fn typed_directives() {
+    assert_eq!(first("client_max_body_size 10m;"),
+        Item::ClientMaxBodySize(Size::from_bytes(10 << 20)));
+    assert_eq!(first("keepalive_timeout 75s 60;"),
+        Item::KeepaliveTimeout(Duration::from_secs(75),
+                               Some(Duration::from_secs(60))));
+    assert_eq!(first("proxy_read_timeout 1m;"),
+        Item::ProxyReadTimeout(Duration::from_secs(60)));
+    assert_eq!(first("proxy_connect_timeout 500ms;"),
+        Item::ProxyConnectTimeout(Duration::from_millis(500)));
+}
+
+#[test]
+fn expires() {
+    let time = |text: &str| match first(text) {
+        Item::Expires(e) => e.value,
+        _ => unreachable!(),
+    };
+    assert_eq!(time("expires off;"), ExpiresTime::Off);
+    assert_eq!(time("expires epoch;"), ExpiresTime::Epoch);
+    assert_eq!(time("expires max;"), ExpiresTime::Max);
+    assert_eq!(time("expires 1h;"),
+        ExpiresTime::After(Duration::from_secs(3600)));
+    assert_eq!(time("expires +24h;"),
+        ExpiresTime::After(Duration::from_secs(24*3600)));
+    assert_eq!(time("expires -1;"),
+        ExpiresTime::Before(Duration::from_secs(1)));
+    assert_eq!(time("expires modified @15h30m;"),
+        ExpiresTime::TimeOfDay(Duration::from_secs(15*3600 + 30*60)));
+    assert!(matches!(time("expires $expires;"), ExpiresTime::Variable(..)));
+}
+
+#[test]
+fn positioned_errors() {
+    let err = |text: &str| parse_main(text).unwrap_err().to_string();
+    assert_eq!(err("http {\n    client_max_body_size 10x;\n}"),
+        "parse error: Parse error at 2:26\n\
+         invalid size \"10x\"\n");
+    assert_eq!(err("proxy_read_timeout 5min;"),
+        "parse error: Parse error at 1:20\n\
+         invalid time \"5min\"\n");
+}
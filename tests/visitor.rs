@@ -2,6 +2,9 @@ extern crate clia_nginx_config;
 #[cfg(test)] #[macro_use] extern crate pretty_assertions;
 
 use clia_nginx_config::{parse_main, visitors};
+use clia_nginx_config::ast::{Directive, Location, Value};
+use clia_nginx_config::visitors::{walk, walk_mut, Ancestor, Control};
+use clia_nginx_config::visitors::{Visitor, VisitorMut};
 
 fn list(value: &str) -> Vec<String> {
     let ast = parse_main(&value).unwrap();
@@ -44,3 +47,115 @@ fn test_replace_vars() {
         }\n\
         ");
 }
+
+const CONFIG: &str = r#"
+    http {
+        server {
+            server_name example.com;
+            location /static {
+                root /srv/static;
+            }
+            location /admin {
+                root /srv/admin;
+                if ($remote_addr) {
+                    return 403;
+                }
+            }
+            location / {
+                proxy_pass http://backend;
+            }
+        }
+    }
+"#;
+
+#[derive(Default)]
+struct Trace {
+    events: Vec<String>,
+}
+
+impl<'ast> Visitor<'ast> for Trace {
+    fn visit_location(&mut self, loc: &'ast Location,
+        _ancestors: &[&'ast Directive]) -> Control
+    {
+        if loc.pattern.to_string() == "/admin" {
+            Control::SkipChildren
+        } else {
+            Control::Continue
+        }
+    }
+    fn visit_value(&mut self, value: &'ast Value,
+        ancestors: &[&'ast Directive]) -> Control
+    {
+        self.events.push(format!("{} {}", ancestors.len(), value));
+        if value.to_string().starts_with("http://") {
+            Control::Stop
+        } else {
+            Control::Continue
+        }
+    }
+    fn enter_block(&mut self, dir: &'ast Directive,
+        _ancestors: &[&'ast Directive]) -> Control
+    {
+        self.events.push(format!("enter {}", dir.item.directive_name()));
+        Control::Continue
+    }
+    fn exit_block(&mut self, dir: &'ast Directive,
+        _ancestors: &[&'ast Directive]) -> Control
+    {
+        self.events.push(format!("exit {}", dir.item.directive_name()));
+        Control::Continue
+    }
+}
+
+#[test]
+fn visitor_control_flow() {
+    let ast = parse_main(CONFIG).unwrap();
+    let mut trace = Trace::default();
+    assert_eq!(walk(&mut trace, &ast.directives), Control::Stop);
+    assert_eq!(trace.events, vec![
+        "enter http",
+        "enter server",
+        "enter location",
+        "3 /srv/static",
+        "exit location",
+        // children of /admin are skipped
+        "enter location",
+        "exit location",
+        "enter location",
+        "3 http://backend",
+    ]);
+}
+
+struct Relocate;
+
+impl VisitorMut for Relocate {
+    fn visit_value(&mut self, value: &mut Value, ancestors: &[Ancestor])
+        -> Control
+    {
+        let in_static = ancestors.iter()
+            .any(|a| a.name == "location" && a.args == "/static");
+        if in_static {
+            *value = "/var/www/static".parse().unwrap();
+        }
+        Control::Continue
+    }
+    fn visit_location(&mut self, _loc: &mut Location, ancestors: &[Ancestor])
+        -> Control
+    {
+        assert_eq!(ancestors.iter().map(|a| a.name).collect::<Vec<_>>(),
+                   vec!["http", "server"]);
+        Control::Continue
+    }
+}
+
+#[test]
+fn visitor_mut() {
+    let mut ast = parse_main(CONFIG).unwrap();
+    assert_eq!(walk_mut(&mut Relocate, &mut ast.directives),
+               Control::Continue);
+    let roots = ast.all_directives()
+        .filter(|d| d.item.directive_name() == "root")
+        .map(|d| d.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(roots, vec!["root /var/www/static;\n", "root /srv/admin;\n"]);
+}